@@ -0,0 +1,109 @@
+//! Compares per-service `ifconfig` probing with the merged `ifconfig -a` probe
+//! against a runner that simulates slow commands.
+//!
+//! Run with `cargo bench --bench service_probe`.
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use mac_net_repair_lib::bench::{
+    collect_network_services, parse_ifconfig, parse_service_order, CachedRunner, CommandRunner,
+};
+
+/// Latency of a single simulated command, roughly what `ifconfig` costs on a busy Mac
+const COMMAND_LATENCY: Duration = Duration::from_millis(40);
+
+struct SlowRunner {
+    services: usize,
+}
+
+impl SlowRunner {
+    fn interface(index: usize) -> String {
+        format!(
+            "en{index}: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500\n\
+             \tether 00:11:22:33:44:{index:02x}\n\
+             \tinet 192.168.{index}.10 netmask 0xffffff00 broadcast 192.168.{index}.255\n\
+             \tmedia: autoselect\n\
+             \tstatus: active\n"
+        )
+    }
+}
+
+impl CommandRunner for SlowRunner {
+    fn run(&self, program: &str, args: &[&str]) -> Result<String, String> {
+        sleep(COMMAND_LATENCY);
+
+        match (program, args) {
+            ("networksetup", ["-listnetworkserviceorder"]) => Ok((0..self.services)
+                .map(|i| {
+                    format!(
+                        "({}) Service {i}\n(Hardware Port: Ethernet {i}, Device: en{i})\n\n",
+                        i + 1
+                    )
+                })
+                .collect()),
+            ("networksetup", ["-getcurrentlocation"]) => Ok("Automatic\n".to_string()),
+            ("route", ["-n", "get", "default"]) => {
+                Ok("gateway: 192.168.0.1\ninterface: en0\n".to_string())
+            }
+            ("ifconfig", ["-a"]) => Ok((0..self.services).map(Self::interface).collect()),
+            ("ifconfig", [device]) => Ok(device
+                .trim_start_matches("en")
+                .parse()
+                .map(Self::interface)
+                .unwrap_or_default()),
+            _ => Err(format!(
+                "Unexpected command: {} {}",
+                program,
+                args.join(" ")
+            )),
+        }
+    }
+}
+
+/// The previous strategy: one `ifconfig <device>` per service, in order
+fn probe_sequentially(runner: &dyn CommandRunner) -> usize {
+    let order = runner
+        .run("networksetup", &["-listnetworkserviceorder"])
+        .unwrap();
+    let _ = runner.run("networksetup", &["-getcurrentlocation"]);
+
+    parse_service_order(&order)
+        .iter()
+        .filter_map(|entry| runner.run("ifconfig", &[&entry.device]).ok())
+        .map(|output| parse_ifconfig(&output).len())
+        .sum()
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+fn main() {
+    println!(
+        "{:>8} {:>12} {:>12} {:>12} {:>8}",
+        "services", "sequential", "merged", "cached", "speedup"
+    );
+
+    for services in [2, 4, 8, 16] {
+        let runner = SlowRunner { services };
+        let (probed, sequential) = time(|| probe_sequentially(&runner));
+        let (merged_services, merged) = time(|| collect_network_services(&runner).unwrap());
+        assert_eq!(probed, merged_services.len());
+
+        let cached_runner = CachedRunner::new(SlowRunner { services }, Duration::from_secs(2));
+        collect_network_services(&cached_runner).unwrap();
+        let (_, cached) = time(|| collect_network_services(&cached_runner).unwrap());
+
+        println!(
+            "{:>8} {:>10.0?} {:>10.0?} {:>10.0?} {:>7.1}x",
+            services,
+            sequential,
+            merged,
+            cached,
+            sequential.as_secs_f64() / merged.as_secs_f64()
+        );
+    }
+}
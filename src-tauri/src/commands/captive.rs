@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use tauri::AppHandle;
+use tauri_plugin_opener::OpenerExt;
+
+/// Apple's hotspot probe, the same one macOS uses to show its sign-in window
+pub const DEFAULT_PROBE_URL: &str = "http://captive.apple.com/hotspot-detect.html";
+pub const DEFAULT_EXPECTED_BODY: &str = "Success";
+
+/// Separates the response body from the status line appended by `curl -w`
+const CURL_TRAILER: &str = "\n__mac_net_repair__";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CaptiveStatus {
+    /// The probe returned the expected body
+    Open,
+    /// The probe was redirected or answered with a different page
+    CaptivePortal,
+    /// No HTTP response at all
+    Offline,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptivePortalResult {
+    pub probe_url: String,
+    pub status: CaptiveStatus,
+    pub http_status: Option<u16>,
+    /// Page to open so the user can sign in
+    pub login_url: Option<String>,
+    pub detail: String,
+}
+
+/// Target of a `<meta http-equiv="refresh" content="0; url=...">` tag or a
+/// `location.href = "..."` script, which portals use instead of a real redirect
+pub fn extract_login_url(body: &str) -> Option<String> {
+    let lower = body.to_ascii_lowercase();
+
+    let from_meta = lower.find("http-equiv=\"refresh\"").and_then(|start| {
+        let tag_end = lower[start..].find('>')? + start;
+        let url_start = lower[start..tag_end].find("url=")? + start + 4;
+        let url = body[url_start..tag_end]
+            .trim_start_matches(['\'', ' '])
+            .split(['"', '\''])
+            .next()?;
+        Some(url.trim().to_string())
+    });
+
+    let from_script = || {
+        ["location.href", "window.location"].iter().find_map(|key| {
+            let start = lower.find(key)? + key.len();
+            let rest = &body[start..];
+            let quote_start = rest.find(['"', '\''])?;
+            let quote = rest[quote_start..].chars().next()?;
+            let rest = &rest[quote_start + 1..];
+            let end = rest.find(quote)?;
+            Some(rest[..end].to_string())
+        })
+    };
+
+    from_meta
+        .or_else(from_script)
+        .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+}
+
+/// Classify a probe response. `redirect_url` is the Location of a 3xx answer.
+pub fn evaluate_probe(
+    probe_url: &str,
+    http_status: Option<u16>,
+    body: &str,
+    redirect_url: Option<&str>,
+    expected_body: &str,
+) -> CaptivePortalResult {
+    let result = |status, login_url: Option<String>, detail: String| CaptivePortalResult {
+        probe_url: probe_url.to_string(),
+        status,
+        http_status,
+        login_url,
+        detail,
+    };
+
+    let Some(code) = http_status else {
+        return result(
+            CaptiveStatus::Offline,
+            None,
+            "The probe received no HTTP response".to_string(),
+        );
+    };
+
+    if (300..400).contains(&code) {
+        let login_url = redirect_url
+            .filter(|url| !url.is_empty())
+            .map(str::to_string);
+        let detail = format!(
+            "The probe was redirected (HTTP {}) to {}",
+            code,
+            login_url.as_deref().unwrap_or("an unknown page")
+        );
+        return result(CaptiveStatus::CaptivePortal, login_url, detail);
+    }
+
+    if code == 200 && body.contains(expected_body) {
+        return result(
+            CaptiveStatus::Open,
+            None,
+            "The probe returned the expected page".to_string(),
+        );
+    }
+
+    result(
+        CaptiveStatus::CaptivePortal,
+        extract_login_url(body),
+        format!("The probe returned HTTP {} with an unexpected page", code),
+    )
+}
+
+/// Fetch the probe without following redirects, so a portal's Location is visible
+fn fetch_probe(probe_url: &str) -> Result<(Option<u16>, String, Option<String>), String> {
+    let write_out = format!("{}%{{http_code}} %{{redirect_url}}", CURL_TRAILER);
+    let output = Command::new("curl")
+        .args(["-s", "--max-time", "5", "-w", &write_out, probe_url])
+        .output()
+        .map_err(|e| format!("Failed to execute curl: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (body, trailer) = stdout.rsplit_once(CURL_TRAILER).unwrap_or((&stdout, ""));
+    let (code, redirect) = trailer.split_once(' ').unwrap_or((trailer, ""));
+    let http_status = code.trim().parse::<u16>().ok().filter(|code| *code != 0);
+    let redirect_url = Some(redirect.trim().to_string()).filter(|url| !url.is_empty());
+
+    Ok((http_status, body.to_string(), redirect_url))
+}
+
+pub(crate) fn probe_captive_portal(
+    probe_url: &str,
+    expected_body: &str,
+) -> Result<CaptivePortalResult, String> {
+    let (http_status, body, redirect_url) = fetch_probe(probe_url)?;
+
+    Ok(evaluate_probe(
+        probe_url,
+        http_status,
+        &body,
+        redirect_url.as_deref(),
+        expected_body,
+    ))
+}
+
+fn validate_probe_url(url: &str) -> Result<(), String> {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(format!("Probe URL '{}' must be an http(s) URL", url))
+    }
+}
+
+/// Detect a captive portal. The probe URL and expected body default to Apple's probe
+/// and can point at any endpoint that returns a fixed page.
+#[tauri::command(async)]
+pub fn check_captive_portal(
+    probe_url: Option<String>,
+    expected_body: Option<String>,
+) -> Result<CaptivePortalResult, String> {
+    let probe_url = probe_url.unwrap_or_else(|| DEFAULT_PROBE_URL.to_string());
+    validate_probe_url(&probe_url)?;
+    let expected_body = expected_body.unwrap_or_else(|| DEFAULT_EXPECTED_BODY.to_string());
+
+    probe_captive_portal(&probe_url, &expected_body)
+}
+
+/// Open the portal's sign-in page in the default browser. Without a URL the probe
+/// is run again; opening the probe itself lets the portal redirect the browser.
+#[tauri::command(async)]
+pub fn open_captive_portal(app: AppHandle, url: Option<String>) -> Result<String, String> {
+    let url = match url.filter(|u| !u.is_empty()) {
+        Some(url) => url,
+        None => probe_captive_portal(DEFAULT_PROBE_URL, DEFAULT_EXPECTED_BODY)?
+            .login_url
+            .unwrap_or_else(|| DEFAULT_PROBE_URL.to_string()),
+    };
+    validate_probe_url(&url)?;
+
+    app.opener()
+        .open_url(&url, None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", url, e))?;
+
+    Ok(format!("Opened {}", url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_http::{closed_port_url, respond, TestServer};
+    use super::*;
+
+    const APPLE_SUCCESS: &str =
+        "<HTML><HEAD><TITLE>Success</TITLE></HEAD><BODY>Success</BODY></HTML>";
+
+    /// Stand-in for captive.apple.com and for the portals that intercept it
+    fn probe_server() -> TestServer {
+        TestServer::start(|request, stream| match request.path.as_str() {
+            "/hotspot-detect.html" => respond(stream, "200 OK", &[], APPLE_SUCCESS.as_bytes()),
+            "/redirect" => respond(
+                stream,
+                "302 Found",
+                &[("Location", "https://portal.example/login?ap=42")],
+                b"",
+            ),
+            "/meta-refresh" => respond(
+                stream,
+                "200 OK",
+                &[("Content-Type", "text/html")],
+                b"<html><head><meta http-equiv=\"refresh\" \
+                  content=\"0; url=https://portal.example/splash\"></head></html>",
+            ),
+            "/substituted" => respond(stream, "200 OK", &[], b"<html>Welcome to Cafe Wi-Fi</html>"),
+            _ => respond(
+                stream,
+                "511 Network Authentication Required",
+                &[],
+                b"<script>location.href = 'https://portal.example/auth';</script>",
+            ),
+        })
+    }
+
+    fn check(url: String) -> CaptivePortalResult {
+        check_captive_portal(Some(url), None).unwrap()
+    }
+
+    #[test]
+    fn expected_body_means_open() {
+        let server = probe_server();
+        let result = check(server.url("/hotspot-detect.html"));
+
+        assert_eq!(result.status, CaptiveStatus::Open);
+        assert_eq!(result.http_status, Some(200));
+        assert_eq!(result.login_url, None);
+    }
+
+    #[test]
+    fn redirect_points_to_the_portal() {
+        let server = probe_server();
+        let result = check(server.url("/redirect"));
+
+        assert_eq!(result.status, CaptiveStatus::CaptivePortal);
+        assert_eq!(result.http_status, Some(302));
+        assert_eq!(
+            result.login_url.as_deref(),
+            Some("https://portal.example/login?ap=42")
+        );
+        assert_eq!(
+            result.detail,
+            "The probe was redirected (HTTP 302) to https://portal.example/login?ap=42"
+        );
+    }
+
+    #[test]
+    fn substituted_page_is_a_portal() {
+        let server = probe_server();
+
+        let result = check(server.url("/meta-refresh"));
+        assert_eq!(result.status, CaptiveStatus::CaptivePortal);
+        assert_eq!(
+            result.login_url.as_deref(),
+            Some("https://portal.example/splash")
+        );
+
+        let result = check(server.url("/substituted"));
+        assert_eq!(result.status, CaptiveStatus::CaptivePortal);
+        assert_eq!(result.http_status, Some(200));
+        assert_eq!(result.login_url, None);
+        assert_eq!(
+            result.detail,
+            "The probe returned HTTP 200 with an unexpected page"
+        );
+
+        let result = check(server.url("/anything-else"));
+        assert_eq!(result.http_status, Some(511));
+        assert_eq!(
+            result.login_url.as_deref(),
+            Some("https://portal.example/auth")
+        );
+    }
+
+    #[test]
+    fn custom_expected_body() {
+        let server = probe_server();
+        let result = check_captive_portal(
+            Some(server.url("/substituted")),
+            Some("Cafe Wi-Fi".to_string()),
+        )
+        .unwrap();
+        assert_eq!(result.status, CaptiveStatus::Open);
+    }
+
+    #[test]
+    fn unreachable_probe_is_offline() {
+        let result = check(closed_port_url());
+
+        assert_eq!(result.status, CaptiveStatus::Offline);
+        assert_eq!(result.http_status, None);
+        assert_eq!(result.detail, "The probe received no HTTP response");
+    }
+
+    #[test]
+    fn probe_url_must_be_http() {
+        let err = check_captive_portal(Some("file:///etc/passwd".to_string()), None).unwrap_err();
+        assert_eq!(err, "Probe URL 'file:///etc/passwd' must be an http(s) URL");
+    }
+
+    #[test]
+    fn login_url_extraction() {
+        assert_eq!(
+            extract_login_url(
+                "<META HTTP-EQUIV=\"Refresh\" CONTENT=\"0;URL='https://portal.example/a'\">"
+            )
+            .as_deref(),
+            Some("https://portal.example/a")
+        );
+        assert_eq!(
+            extract_login_url("<script>window.location = \"http://10.0.0.1/login\"</script>")
+                .as_deref(),
+            Some("http://10.0.0.1/login")
+        );
+        // Relative and script URLs are not opened
+        assert_eq!(extract_login_url("location.href = '/login'"), None);
+        assert_eq!(extract_login_url("location.href = 'javascript:go()'"), None);
+        assert_eq!(extract_login_url("<html>Success</html>"), None);
+    }
+}
@@ -0,0 +1,543 @@
+use serde::{Deserialize, Serialize};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::network::{run_with_admin_privileges, shell_quote};
+
+const SYSTEM_CONFIGURATION_DIR: &str = "/Library/Preferences/SystemConfiguration";
+
+/// Files macOS regenerates on the next boot when they are missing
+const NETWORK_CONFIG_FILES: [&str; 4] = [
+    "preferences.plist",
+    "NetworkInterfaces.plist",
+    "com.apple.airport.preferences.plist",
+    "com.apple.network.identification.plist",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestorePoint {
+    pub id: String,
+    pub created_at: u64,
+    pub root: String,
+    pub archive: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeepResetResult {
+    pub restore_point: RestorePoint,
+    pub removed_files: Vec<String>,
+    pub reboot_required: bool,
+}
+
+/// Run a shell command, through the admin prompt when touching the real system files
+fn run_shell(command: &str, privileged: bool) -> Result<String, String> {
+    if privileged {
+        return run_with_admin_privileges(command);
+    }
+
+    let output = Command::new("sh")
+        .args(["-c", command])
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "命令执行失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Resolve the configuration root; only the real system directory needs admin rights
+fn resolve_root(root: Option<String>) -> Result<(PathBuf, bool), String> {
+    resolve_root_against(root, Path::new(SYSTEM_CONFIGURATION_DIR))
+}
+
+/// Paths are compared canonicalized so "SystemConfiguration/", "./" components or a
+/// symlink to the system directory still go through the admin prompt
+fn resolve_root_against(root: Option<String>, system: &Path) -> Result<(PathBuf, bool), String> {
+    let system = system
+        .canonicalize()
+        .unwrap_or_else(|_| system.to_path_buf());
+    let root = match root {
+        Some(root) if !root.is_empty() => Path::new(&root)
+            .canonicalize()
+            .map_err(|e| format!("Configuration directory {} not found: {}", root, e))?,
+        _ => return Ok((system, true)),
+    };
+
+    let privileged = root == system;
+    Ok((root, privileged))
+}
+
+fn resolve_backup_dir(backup_dir: Option<String>) -> Result<PathBuf, String> {
+    match backup_dir {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => {
+            let home = std::env::var("HOME").map_err(|_| "HOME is not set".to_string())?;
+            Ok(Path::new(&home).join("Library/Application Support/mac-net-repair/network-backups"))
+        }
+    }
+}
+
+fn manifest_path(backup_dir: &Path, id: &str) -> PathBuf {
+    backup_dir.join(format!("{}.json", id))
+}
+
+fn read_restore_point(backup_dir: &Path, id: &str) -> Result<RestorePoint, String> {
+    if id.is_empty() || id.contains('/') || id.contains("..") {
+        return Err(format!("Invalid backup id '{}'", id));
+    }
+
+    let content = std::fs::read_to_string(manifest_path(backup_dir, id))
+        .map_err(|e| format!("Backup '{}' not found: {}", id, e))?;
+    let restore_point: RestorePoint = serde_json::from_str(&content)
+        .map_err(|e| format!("Backup '{}' is corrupted: {}", id, e))?;
+
+    // The manifest decides what gets extracted as root, so it must describe exactly
+    // what `deep_reset_network` writes
+    let archive = backup_dir.join(format!("{}.tar.gz", id));
+    if Path::new(&restore_point.archive) != archive {
+        return Err(format!(
+            "Backup '{}' points to an unexpected archive {}",
+            id, restore_point.archive
+        ));
+    }
+    if let Some(file) = restore_point
+        .files
+        .iter()
+        .find(|file| !NETWORK_CONFIG_FILES.contains(&file.as_str()))
+    {
+        return Err(format!(
+            "Backup '{}' contains an unexpected file {}",
+            id, file
+        ));
+    }
+
+    Ok(restore_point)
+}
+
+/// Back up the network configuration plists, then delete them so macOS rebuilds them
+#[tauri::command]
+pub fn deep_reset_network(
+    root: Option<String>,
+    backup_dir: Option<String>,
+) -> Result<DeepResetResult, String> {
+    let (root, privileged) = resolve_root(root)?;
+    let backup_dir = resolve_backup_dir(backup_dir)?;
+
+    let files: Vec<String> = NETWORK_CONFIG_FILES
+        .iter()
+        .filter(|file| root.join(file).exists())
+        .map(|file| file.to_string())
+        .collect();
+    if files.is_empty() {
+        return Err(format!(
+            "No network configuration files found in {}",
+            root.display()
+        ));
+    }
+
+    std::fs::create_dir_all(&backup_dir)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut id = format!("network-backup-{}", created_at);
+    let mut suffix = 1;
+    while manifest_path(&backup_dir, &id).exists() {
+        id = format!("network-backup-{}-{}", created_at, suffix);
+        suffix += 1;
+    }
+    let archive = backup_dir.join(format!("{}.tar.gz", id));
+
+    let file_args: Vec<String> = files.iter().map(|f| shell_quote(f)).collect();
+    let mut command = format!(
+        "tar -czf {} -C {} {}",
+        shell_quote(&archive.to_string_lossy()),
+        shell_quote(&root.to_string_lossy()),
+        file_args.join(" ")
+    );
+    if privileged {
+        // Hand the archive back to the user who owns the backup directory
+        let uid = std::fs::metadata(&backup_dir)
+            .map(|m| m.uid())
+            .map_err(|e| format!("Failed to read backup directory: {}", e))?;
+        command.push_str(&format!(
+            " && chown {} {}",
+            uid,
+            shell_quote(&archive.to_string_lossy())
+        ));
+    }
+    run_shell(&command, privileged)?;
+
+    if !archive.exists() {
+        return Err("Backup archive was not created, nothing was removed".to_string());
+    }
+
+    let restore_point = RestorePoint {
+        id: id.clone(),
+        created_at,
+        root: root.to_string_lossy().to_string(),
+        archive: archive.to_string_lossy().to_string(),
+        files: files.clone(),
+    };
+    let manifest = serde_json::to_string_pretty(&restore_point)
+        .map_err(|e| format!("Failed to serialize restore point: {}", e))?;
+    std::fs::write(manifest_path(&backup_dir, &id), manifest)
+        .map_err(|e| format!("Failed to write restore point: {}", e))?;
+
+    let targets: Vec<String> = files
+        .iter()
+        .map(|f| shell_quote(&root.join(f).to_string_lossy()))
+        .collect();
+    run_shell(&format!("rm -f {}", targets.join(" ")), privileged)?;
+
+    Ok(DeepResetResult {
+        restore_point,
+        removed_files: files,
+        reboot_required: privileged,
+    })
+}
+
+#[tauri::command]
+pub fn list_network_backups(backup_dir: Option<String>) -> Result<Vec<RestorePoint>, String> {
+    let backup_dir = resolve_backup_dir(backup_dir)?;
+    if !backup_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = std::fs::read_dir(&backup_dir)
+        .map_err(|e| format!("Failed to read backup directory: {}", e))?;
+
+    let mut points: Vec<RestorePoint> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    points.sort_by_key(|point| std::cmp::Reverse(point.created_at));
+
+    Ok(points)
+}
+
+/// Extract a backup created by `deep_reset_network` back into the configuration root.
+///
+/// `root` defaults to the system directory and must be the directory the backup was
+/// taken from.
+#[tauri::command]
+pub fn restore_network_backup(
+    id: String,
+    root: Option<String>,
+    backup_dir: Option<String>,
+) -> Result<String, String> {
+    let backup_dir = resolve_backup_dir(backup_dir)?;
+    let restore_point = read_restore_point(&backup_dir, &id)?;
+
+    if !Path::new(&restore_point.archive).exists() {
+        return Err(format!(
+            "Backup archive {} is missing",
+            restore_point.archive
+        ));
+    }
+
+    let (root, privileged) = resolve_root(root)?;
+    let source = Path::new(&restore_point.root);
+    if source.canonicalize().as_deref().unwrap_or(source) != root {
+        return Err(format!(
+            "Backup '{}' was taken from {}, not {}",
+            id,
+            restore_point.root,
+            root.display()
+        ));
+    }
+
+    let file_args: Vec<String> = restore_point.files.iter().map(|f| shell_quote(f)).collect();
+    let command = format!(
+        "tar -xzf {} -C {} {}",
+        shell_quote(&restore_point.archive),
+        shell_quote(&root.to_string_lossy()),
+        file_args.join(" ")
+    );
+    run_shell(&command, privileged)?;
+
+    Ok(format!(
+        "Restored {} file(s) from backup '{}'{}",
+        restore_point.files.len(),
+        id,
+        if privileged { ", restart to apply" } else { "" }
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "deep-reset-{}-{}-{:?}",
+                name,
+                std::process::id(),
+                std::thread::current().id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn path(&self, name: &str) -> String {
+            self.0.join(name).to_string_lossy().to_string()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// A configuration root with two of the network plists and one unrelated file
+    fn config_root(tmp: &TempDir) -> String {
+        let root = tmp.path("SystemConfiguration");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(Path::new(&root).join("preferences.plist"), "<prefs/>").unwrap();
+        fs::write(
+            Path::new(&root).join("NetworkInterfaces.plist"),
+            "<ifaces/>",
+        )
+        .unwrap();
+        fs::write(Path::new(&root).join("com.apple.Boot.plist"), "<boot/>").unwrap();
+        root
+    }
+
+    fn rewrite_manifest(backup_dir: &str, id: &str, edit: impl FnOnce(&mut RestorePoint)) {
+        let path = manifest_path(Path::new(backup_dir), id);
+        let mut point: RestorePoint =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        edit(&mut point);
+        fs::write(&path, serde_json::to_string(&point).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn root_is_compared_canonicalized() {
+        let tmp = TempDir::new("root");
+        let system = tmp.0.join("SystemConfiguration");
+        fs::create_dir_all(&system).unwrap();
+        let canonical = system.canonicalize().unwrap();
+        std::os::unix::fs::symlink(&system, tmp.0.join("link")).unwrap();
+
+        assert_eq!(
+            resolve_root_against(None, &system).unwrap(),
+            (canonical.clone(), true)
+        );
+        assert_eq!(
+            resolve_root_against(Some(String::new()), &system).unwrap(),
+            (canonical.clone(), true)
+        );
+        for alias in [
+            format!("{}/", system.display()),
+            format!("{}/./", system.display()),
+            format!("{}/../SystemConfiguration", system.display()),
+            tmp.path("link"),
+        ] {
+            assert_eq!(
+                resolve_root_against(Some(alias.clone()), &system).unwrap(),
+                (canonical.clone(), true),
+                "{}",
+                alias
+            );
+        }
+
+        let other = tmp.0.join("other");
+        fs::create_dir_all(&other).unwrap();
+        let (root, privileged) = resolve_root_against(Some(tmp.path("other/")), &system).unwrap();
+        assert_eq!(root, other.canonicalize().unwrap());
+        assert!(!privileged);
+
+        let err = resolve_root_against(Some(tmp.path("missing")), &system).unwrap_err();
+        assert!(err.contains("not found"), "{}", err);
+    }
+
+    #[test]
+    fn reset_backs_up_removes_lists_and_restores() {
+        let tmp = TempDir::new("roundtrip");
+        let root = config_root(&tmp);
+        let backups = tmp.path("backups");
+
+        let result = deep_reset_network(Some(root.clone()), Some(backups.clone())).unwrap();
+        assert_eq!(
+            result.removed_files,
+            ["preferences.plist", "NetworkInterfaces.plist"]
+        );
+        assert!(!result.reboot_required);
+        let root_path = Path::new(&root);
+        assert!(!root_path.join("preferences.plist").exists());
+        assert!(!root_path.join("NetworkInterfaces.plist").exists());
+        assert!(root_path.join("com.apple.Boot.plist").exists());
+
+        let point = &result.restore_point;
+        assert!(point.id.starts_with("network-backup-"));
+        assert!(Path::new(&point.archive).exists());
+        assert_eq!(
+            Path::new(&point.root),
+            root_path.canonicalize().unwrap().as_path()
+        );
+
+        let listed = list_network_backups(Some(backups.clone())).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, point.id);
+        assert_eq!(listed[0].files, point.files);
+
+        let message =
+            restore_network_backup(point.id.clone(), Some(root.clone()), Some(backups)).unwrap();
+        assert_eq!(
+            message,
+            format!("Restored 2 file(s) from backup '{}'", point.id)
+        );
+        assert_eq!(
+            fs::read_to_string(root_path.join("preferences.plist")).unwrap(),
+            "<prefs/>"
+        );
+        assert_eq!(
+            fs::read_to_string(root_path.join("NetworkInterfaces.plist")).unwrap(),
+            "<ifaces/>"
+        );
+    }
+
+    #[test]
+    fn repeated_resets_get_distinct_ids() {
+        let tmp = TempDir::new("repeat");
+        let root = config_root(&tmp);
+        let backups = tmp.path("backups");
+
+        let first = deep_reset_network(Some(root.clone()), Some(backups.clone())).unwrap();
+        fs::write(Path::new(&root).join("preferences.plist"), "<rebuilt/>").unwrap();
+        let second = deep_reset_network(Some(root), Some(backups.clone())).unwrap();
+
+        assert_ne!(first.restore_point.id, second.restore_point.id);
+        assert_eq!(second.removed_files, ["preferences.plist"]);
+        assert_eq!(list_network_backups(Some(backups)).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn reset_without_config_files_removes_nothing() {
+        let tmp = TempDir::new("empty");
+        let backups = tmp.path("backups");
+
+        let err = deep_reset_network(Some(tmp.path("")), Some(backups.clone())).unwrap_err();
+        assert!(
+            err.starts_with("No network configuration files found"),
+            "{}",
+            err
+        );
+        assert!(!Path::new(&backups).exists());
+    }
+
+    #[test]
+    fn listing_skips_unreadable_manifests() {
+        let tmp = TempDir::new("list");
+        assert!(list_network_backups(Some(tmp.path("missing")))
+            .unwrap()
+            .is_empty());
+
+        let backups = tmp.path("backups");
+        fs::create_dir_all(&backups).unwrap();
+        let point = |id: &str, created_at| RestorePoint {
+            id: id.to_string(),
+            created_at,
+            root: "/tmp".to_string(),
+            archive: format!("{}/{}.tar.gz", backups, id),
+            files: vec!["preferences.plist".to_string()],
+        };
+        for p in [point("older", 100), point("newer", 200)] {
+            fs::write(
+                manifest_path(Path::new(&backups), &p.id),
+                serde_json::to_string(&p).unwrap(),
+            )
+            .unwrap();
+        }
+        fs::write(Path::new(&backups).join("corrupt.json"), "{").unwrap();
+        fs::write(Path::new(&backups).join("notes.txt"), "{}").unwrap();
+
+        let ids: Vec<String> = list_network_backups(Some(backups))
+            .unwrap()
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(ids, ["newer", "older"]);
+    }
+
+    #[test]
+    fn restore_requires_the_original_root() {
+        let tmp = TempDir::new("origin");
+        let root = config_root(&tmp);
+        let backups = tmp.path("backups");
+        let id = deep_reset_network(Some(root), Some(backups.clone()))
+            .unwrap()
+            .restore_point
+            .id;
+
+        // Without a root the system directory is meant, which this backup is not from
+        let err = restore_network_backup(id.clone(), None, Some(backups.clone())).unwrap_err();
+        assert!(err.contains("was taken from"), "{}", err);
+
+        let other = tmp.path("other");
+        fs::create_dir_all(&other).unwrap();
+        let err = restore_network_backup(id, Some(other.clone()), Some(backups)).unwrap_err();
+        assert!(err.contains("was taken from"), "{}", err);
+        assert!(fs::read_dir(&other).unwrap().next().is_none());
+    }
+
+    #[test]
+    fn tampered_manifests_are_rejected() {
+        let tmp = TempDir::new("tamper");
+        let root = config_root(&tmp);
+        let backups = tmp.path("backups");
+        let id = deep_reset_network(Some(root.clone()), Some(backups.clone()))
+            .unwrap()
+            .restore_point
+            .id;
+        let restore =
+            || restore_network_backup(id.clone(), Some(root.clone()), Some(backups.clone()));
+
+        let other = tmp.path("other");
+        fs::create_dir_all(&other).unwrap();
+        rewrite_manifest(&backups, &id, |p| p.root = other.clone());
+        assert!(restore().unwrap_err().contains("was taken from"));
+
+        rewrite_manifest(&backups, &id, |p| {
+            p.root = root.clone();
+            p.files.push("../../etc/passwd".to_string());
+        });
+        assert!(restore().unwrap_err().contains("unexpected file"));
+
+        let decoy = tmp.path("decoy.tar.gz");
+        fs::copy(format!("{}/{}.tar.gz", backups, id), &decoy).unwrap();
+        rewrite_manifest(&backups, &id, |p| {
+            p.files.pop();
+            p.archive = decoy.clone();
+        });
+        assert!(restore().unwrap_err().contains("unexpected archive"));
+
+        for bad_id in ["", "../backups/x", "a/b"] {
+            let err = restore_network_backup(
+                bad_id.to_string(),
+                Some(root.clone()),
+                Some(backups.clone()),
+            )
+            .unwrap_err();
+            assert!(err.starts_with("Invalid backup id"), "{}", err);
+        }
+    }
+}
@@ -0,0 +1,439 @@
+use serde::{Deserialize, Serialize};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use super::ip::{get_ip_config_internal, parse_ipv4, IpConfig, IpMode};
+use super::network::{parse_service_order, run_with_admin_privileges};
+use super::runner::{system_runner, CommandRunner, SystemRunner};
+
+/// How long `renew_dhcp` waits for the server to answer
+const RENEW_TIMEOUT: Duration = Duration::from_secs(10);
+const RENEW_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DhcpOption {
+    pub name: String,
+    /// Encoding reported by ipconfig, e.g. "ip", "ip_mult", "uint32", "string"
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DhcpLease {
+    pub interface: String,
+    pub ip_address: Option<String>,
+    pub server_identifier: Option<String>,
+    pub message_type: Option<String>,
+    pub lease_time_secs: Option<u64>,
+    pub renewal_time_secs: Option<u64>,
+    pub rebinding_time_secs: Option<u64>,
+    pub subnet_mask: Option<String>,
+    pub routers: Vec<String>,
+    pub dns_servers: Vec<String>,
+    pub domain_name: Option<String>,
+    pub options: Vec<DhcpOption>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DhcpRenewResult {
+    pub service_name: String,
+    pub interface: String,
+    pub before: Option<DhcpLease>,
+    pub after: DhcpLease,
+    /// Human readable differences between the two leases
+    pub changes: Vec<String>,
+    pub duration_ms: u64,
+}
+
+/// "0x15180" or "86400" -> 86400
+fn parse_number(value: &str) -> Option<u64> {
+    match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+/// "{192.168.1.1, 8.8.8.8}" -> ["192.168.1.1", "8.8.8.8"]
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .trim_start_matches('{')
+        .trim_end_matches('}')
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Parse `ipconfig getpacket <interface>` output. Returns `None` when the
+/// interface has no DHCP packet, e.g. it is manually configured or still waiting.
+pub fn parse_getpacket(interface: &str, output: &str) -> Option<DhcpLease> {
+    let mut lease = DhcpLease {
+        interface: interface.to_string(),
+        ip_address: None,
+        server_identifier: None,
+        message_type: None,
+        lease_time_secs: None,
+        renewal_time_secs: None,
+        rebinding_time_secs: None,
+        subnet_mask: None,
+        routers: Vec::new(),
+        dns_servers: Vec::new(),
+        domain_name: None,
+        options: Vec::new(),
+    };
+    let mut in_options = false;
+
+    for line in output.lines() {
+        let line = line.trim();
+        if line == "options:" {
+            in_options = true;
+            continue;
+        }
+
+        if !in_options {
+            // BOOTP header fields: "yiaddr = 192.168.1.23"
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "yiaddr" {
+                    lease.ip_address = Some(value.trim().to_string()).filter(|v| v != "0.0.0.0");
+                }
+            }
+            continue;
+        }
+
+        // Options: "lease_time (uint32): 0x15180"
+        let Some((head, value)) = line.split_once("):") else {
+            continue;
+        };
+        let Some((name, kind)) = head.split_once(" (") else {
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim());
+        if name == "end" {
+            continue;
+        }
+
+        match name {
+            "dhcp_message_type" => {
+                lease.message_type = value.split_whitespace().next().map(|s| s.to_string())
+            }
+            "server_identifier" => lease.server_identifier = Some(value.to_string()),
+            "lease_time" => lease.lease_time_secs = parse_number(value),
+            "renewal_t1_time_value" => lease.renewal_time_secs = parse_number(value),
+            "rebinding_t2_time_value" => lease.rebinding_time_secs = parse_number(value),
+            "subnet_mask" => lease.subnet_mask = Some(value.to_string()),
+            "router" => lease.routers = parse_list(value),
+            "domain_name_server" => lease.dns_servers = parse_list(value),
+            "domain_name" => lease.domain_name = Some(value.to_string()),
+            _ => {}
+        }
+        lease.options.push(DhcpOption {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    (lease.ip_address.is_some() || !lease.options.is_empty()).then_some(lease)
+}
+
+/// Differences between two leases of the same interface
+pub fn diff_leases(before: Option<&DhcpLease>, after: &DhcpLease) -> Vec<String> {
+    let Some(before) = before else {
+        return vec!["New lease obtained".to_string()];
+    };
+
+    let mut changes = Vec::new();
+    let mut compare = |what: &str, old: String, new: String| {
+        if old != new {
+            changes.push(format!("{}: {} -> {}", what, old, new));
+        }
+    };
+    let show = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+    let show_secs = |value: Option<u64>| value.map_or("none".to_string(), |s| format!("{}s", s));
+
+    compare(
+        "IP address",
+        show(&before.ip_address),
+        show(&after.ip_address),
+    );
+    compare(
+        "Subnet mask",
+        show(&before.subnet_mask),
+        show(&after.subnet_mask),
+    );
+    compare(
+        "Router",
+        before.routers.join(", "),
+        after.routers.join(", "),
+    );
+    compare(
+        "DNS",
+        before.dns_servers.join(", "),
+        after.dns_servers.join(", "),
+    );
+    compare(
+        "Domain",
+        show(&before.domain_name),
+        show(&after.domain_name),
+    );
+    compare(
+        "DHCP server",
+        show(&before.server_identifier),
+        show(&after.server_identifier),
+    );
+    compare(
+        "Lease time",
+        show_secs(before.lease_time_secs),
+        show_secs(after.lease_time_secs),
+    );
+
+    changes
+}
+
+fn validate_interface(interface: &str) -> Result<(), String> {
+    if interface.is_empty() {
+        return Err("Interface cannot be empty".to_string());
+    }
+    if !interface.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid interface name '{}'", interface));
+    }
+
+    Ok(())
+}
+
+fn read_lease(runner: &dyn CommandRunner, interface: &str) -> Result<DhcpLease, String> {
+    let stdout = runner.run("ipconfig", &["getpacket", interface])?;
+
+    parse_getpacket(interface, &stdout).ok_or_else(|| format!("No DHCP lease on {}", interface))
+}
+
+#[tauri::command]
+pub fn get_dhcp_lease(interface: String) -> Result<DhcpLease, String> {
+    validate_interface(&interface)?;
+
+    read_lease(system_runner(), &interface)
+}
+
+/// The `ipconfig set` command that renews the lease without changing how the service
+/// is configured. Services that do not use DHCP are refused, since `ipconfig set
+/// <interface> DHCP` would silently switch them over.
+fn renew_command(interface: &str, config: &IpConfig) -> Result<String, String> {
+    let refuse = |configured: &str| {
+        Err(format!(
+            "Network service '{}' uses {}; renewing would switch it to DHCP",
+            config.service_name, configured
+        ))
+    };
+
+    match config.mode {
+        IpMode::Dhcp => Ok(format!("ipconfig set {} DHCP", interface)),
+        // INFORM keeps the manual address and only asks the server for the rest
+        IpMode::ManualWithDhcpRouter => {
+            let ip = parse_ipv4(
+                config.ip_address.as_deref().unwrap_or_default(),
+                "IP address",
+            )?;
+            Ok(format!("ipconfig set {} INFORM {}", interface, ip))
+        }
+        IpMode::Manual => refuse("a manual address"),
+        IpMode::Bootp => refuse("BOOTP"),
+        IpMode::Off => refuse("no IPv4"),
+        IpMode::Unknown => refuse("an unknown IPv4 configuration"),
+    }
+}
+
+/// Renew the DHCP lease of a service and report what changed
+#[tauri::command(async)]
+pub fn renew_dhcp(service_name: String) -> Result<DhcpRenewResult, String> {
+    if service_name.is_empty() {
+        return Err("Service name cannot be empty".to_string());
+    }
+
+    let order = system_runner().run("networksetup", &["-listnetworkserviceorder"])?;
+    let entry = parse_service_order(&order)
+        .into_iter()
+        .find(|entry| entry.name == service_name)
+        .ok_or_else(|| format!("Network service '{}' does not exist", service_name))?;
+    if !entry.enabled {
+        return Err(format!("Network service '{}' is disabled", service_name));
+    }
+    let interface = entry.device;
+    validate_interface(&interface)?;
+    let command = renew_command(&interface, &get_ip_config_internal(&service_name)?)?;
+
+    let before = read_lease(&SystemRunner, &interface).ok();
+    let started = Instant::now();
+
+    run_with_admin_privileges(&command)?;
+
+    // The old packet is dropped when the interface is reset, wait for the new ACK
+    let after = loop {
+        sleep(RENEW_POLL_INTERVAL);
+        if let Ok(lease) = read_lease(&SystemRunner, &interface) {
+            break lease;
+        }
+        if started.elapsed() >= RENEW_TIMEOUT {
+            return Err(format!(
+                "No DHCP lease received on {} within {}s",
+                interface,
+                RENEW_TIMEOUT.as_secs()
+            ));
+        }
+    };
+
+    Ok(DhcpRenewResult {
+        changes: diff_leases(before.as_ref(), &after),
+        service_name,
+        interface,
+        before,
+        after,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GETPACKET: &str = "\
+op = BOOTREPLY
+htype = 1
+flags = 0
+hlen = 6
+hops = 0
+xid = 0x3e5a1b2c
+secs = 0
+ciaddr = 0.0.0.0
+yiaddr = 192.168.1.23
+siaddr = 192.168.1.1
+giaddr = 0.0.0.0
+chaddr = a4:83:e7:12:34:56
+sname = 
+file = 
+options:
+Options count is 9
+dhcp_message_type (uint8): ACK 0x5
+server_identifier (ip): 192.168.1.1
+lease_time (uint32): 0x15180
+renewal_t1_time_value (uint32): 0xa8c0
+rebinding_t2_time_value (uint32): 0x12750
+subnet_mask (ip): 255.255.255.0
+router (ip_mult): {192.168.1.1, 192.168.1.2}
+domain_name_server (ip_mult): {192.168.1.1, 1.1.1.1, 8.8.8.8}
+domain_name (string): home.example
+end (none): 
+";
+
+    /// Servers that hand out infinite leases omit the lease timers
+    const GETPACKET_NO_LEASE_TIME: &str = "\
+op = BOOTREPLY
+yiaddr = 10.0.0.5
+options:
+Options count is 4
+dhcp_message_type (uint8): ACK 0x5
+server_identifier (ip): 10.0.0.1
+subnet_mask (ip): 255.255.0.0
+router (ip_mult): {10.0.0.1}
+end (none): 
+";
+
+    fn config(mode: IpMode, ip_address: Option<&str>) -> IpConfig {
+        IpConfig {
+            service_name: "Ethernet".to_string(),
+            mode,
+            ip_address: ip_address.map(str::to_string),
+            subnet_mask: None,
+            router: None,
+            client_id: None,
+            ipv6_mode: Default::default(),
+            ipv6_addresses: Vec::new(),
+            ipv6_prefix_length: None,
+            ipv6_router: None,
+        }
+    }
+
+    #[test]
+    fn parses_getpacket_output() {
+        let lease = parse_getpacket("en0", GETPACKET).unwrap();
+        assert_eq!(lease.interface, "en0");
+        assert_eq!(lease.ip_address.as_deref(), Some("192.168.1.23"));
+        assert_eq!(lease.message_type.as_deref(), Some("ACK"));
+        assert_eq!(lease.server_identifier.as_deref(), Some("192.168.1.1"));
+        assert_eq!(lease.lease_time_secs, Some(86400));
+        assert_eq!(lease.renewal_time_secs, Some(43200));
+        assert_eq!(lease.rebinding_time_secs, Some(75600));
+        assert_eq!(lease.subnet_mask.as_deref(), Some("255.255.255.0"));
+        assert_eq!(lease.routers, ["192.168.1.1", "192.168.1.2"]);
+        assert_eq!(lease.dns_servers, ["192.168.1.1", "1.1.1.1", "8.8.8.8"]);
+        assert_eq!(lease.domain_name.as_deref(), Some("home.example"));
+        assert_eq!(lease.options.len(), 9);
+        assert_eq!(
+            lease.options[6],
+            DhcpOption {
+                name: "router".to_string(),
+                kind: "ip_mult".to_string(),
+                value: "{192.168.1.1, 192.168.1.2}".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_getpacket_without_lease_time() {
+        let lease = parse_getpacket("en5", GETPACKET_NO_LEASE_TIME).unwrap();
+        assert_eq!(lease.ip_address.as_deref(), Some("10.0.0.5"));
+        assert_eq!(lease.lease_time_secs, None);
+        assert_eq!(lease.renewal_time_secs, None);
+        assert_eq!(lease.routers, ["10.0.0.1"]);
+        assert!(lease.dns_servers.is_empty());
+        assert_eq!(lease.domain_name, None);
+
+        assert_eq!(parse_getpacket("en0", ""), None);
+    }
+
+    #[test]
+    fn diffs_leases() {
+        let before = parse_getpacket("en0", GETPACKET).unwrap();
+        assert_eq!(diff_leases(None, &before), ["New lease obtained"]);
+        assert!(diff_leases(Some(&before), &before).is_empty());
+
+        let after = DhcpLease {
+            ip_address: Some("192.168.1.42".to_string()),
+            routers: vec!["192.168.1.1".to_string()],
+            lease_time_secs: None,
+            ..before.clone()
+        };
+        assert_eq!(
+            diff_leases(Some(&before), &after),
+            [
+                "IP address: 192.168.1.23 -> 192.168.1.42",
+                "Router: 192.168.1.1, 192.168.1.2 -> 192.168.1.1",
+                "Lease time: 86400s -> none",
+            ]
+        );
+    }
+
+    #[test]
+    fn renews_only_dhcp_services() {
+        assert_eq!(
+            renew_command("en0", &config(IpMode::Dhcp, None)),
+            Ok("ipconfig set en0 DHCP".to_string())
+        );
+        assert_eq!(
+            renew_command(
+                "en0",
+                &config(IpMode::ManualWithDhcpRouter, Some("192.168.1.50"))
+            ),
+            Ok("ipconfig set en0 INFORM 192.168.1.50".to_string())
+        );
+        assert_eq!(
+            renew_command("en0", &config(IpMode::Manual, Some("192.168.1.50"))),
+            Err(
+                "Network service 'Ethernet' uses a manual address; renewing would switch it to DHCP"
+                    .to_string()
+            )
+        );
+        for mode in [IpMode::Bootp, IpMode::Off, IpMode::Unknown] {
+            assert!(renew_command("en0", &config(mode, None)).is_err());
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
 use std::process::Command;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PingResult {
@@ -10,6 +13,106 @@ pub struct PingResult {
     pub error: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct StackProbe {
+    pub addresses: Vec<String>,
+    pub reachable: bool,
+    pub connect_ms: Option<f64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DualStackResult {
+    pub host: String,
+    pub port: u16,
+    pub ipv4: StackProbe,
+    pub ipv6: StackProbe,
+    pub disable_ipv6_recommended: bool,
+    pub recommendation: String,
+}
+
+/// Try each address of one family until a TCP connection succeeds
+fn probe_stack(addrs: &[SocketAddr]) -> StackProbe {
+    let mut probe = StackProbe {
+        addresses: addrs.iter().map(|a| a.ip().to_string()).collect(),
+        ..Default::default()
+    };
+    if addrs.is_empty() {
+        probe.error = Some("No addresses".to_string());
+        return probe;
+    }
+
+    for addr in addrs.iter().take(3) {
+        let start = Instant::now();
+        match TcpStream::connect_timeout(addr, CONNECT_TIMEOUT) {
+            Ok(_) => {
+                probe.reachable = true;
+                probe.connect_ms = Some(start.elapsed().as_secs_f64() * 1000.0);
+                probe.error = None;
+                break;
+            }
+            Err(e) => probe.error = Some(format!("{}: {}", addr, e)),
+        }
+    }
+
+    probe
+}
+
+/// Decide whether IPv6 is black-holed: the host publishes AAAA records, IPv4 works,
+/// but IPv6 connections never complete
+pub fn assess_dual_stack(ipv4: &StackProbe, ipv6: &StackProbe) -> (bool, String) {
+    match (ipv4.reachable, ipv6.reachable) {
+        (true, false) if !ipv6.addresses.is_empty() => (
+            true,
+            "IPv4 works but IPv6 is black-holed; set IPv6 to link-local only or off".to_string(),
+        ),
+        (true, false) => (
+            false,
+            "Host has no IPv6 address, only IPv4 could be tested".to_string(),
+        ),
+        (true, true) => (false, "IPv4 and IPv6 both work".to_string()),
+        (false, true) => (
+            false,
+            "Only IPv6 works; IPv4 routing or the IPv4 gateway is broken".to_string(),
+        ),
+        (false, false) => (
+            false,
+            "Neither IPv4 nor IPv6 could connect; the problem is not IPv6 specific".to_string(),
+        ),
+    }
+}
+
+/// Compare IPv4 and IPv6 reachability of the same dual-stack host
+#[tauri::command(async)]
+pub fn check_dual_stack(
+    host: Option<String>,
+    port: Option<u16>,
+) -> Result<DualStackResult, String> {
+    let host = host
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "www.apple.com".to_string());
+    let port = port.unwrap_or(443);
+
+    let addrs: Vec<SocketAddr> = (host.as_str(), port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .collect();
+    let (v4, v6): (Vec<SocketAddr>, Vec<SocketAddr>) = addrs.into_iter().partition(|a| a.is_ipv4());
+
+    let ipv4 = probe_stack(&v4);
+    let ipv6 = probe_stack(&v6);
+    let (disable_ipv6_recommended, recommendation) = assess_dual_stack(&ipv4, &ipv6);
+
+    Ok(DualStackResult {
+        host,
+        port,
+        ipv4,
+        ipv6,
+        disable_ipv6_recommended,
+        recommendation,
+    })
+}
+
 #[tauri::command]
 pub fn ping_host(target: String, count: Option<u32>) -> Result<PingResult, String> {
     let count = count.unwrap_or(4);
@@ -61,75 +164,54 @@ pub fn ping_host(target: String, count: Option<u32>) -> Result<PingResult, Strin
     }
 }
 
-#[tauri::command]
-pub fn reset_network() -> Result<String, String> {
-    let mut results = Vec::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // 1. Flush DNS cache
-    if let Ok(_) = Command::new("dscacheutil").arg("-flushcache").output() {
-        results.push("DNS cache flushed");
+    fn probe(addresses: &[&str], reachable: bool) -> StackProbe {
+        StackProbe {
+            addresses: addresses.iter().map(|a| a.to_string()).collect(),
+            reachable,
+            connect_ms: reachable.then_some(12.0),
+            error: (!reachable).then(|| "Operation timed out".to_string()),
+        }
     }
 
-    // 2. Restart mDNSResponder
-    if let Ok(_) = Command::new("killall")
-        .args(["-HUP", "mDNSResponder"])
-        .output()
-    {
-        results.push("mDNSResponder restarted");
+    #[test]
+    fn recommends_disabling_black_holed_ipv6() {
+        let (disable, recommendation) = assess_dual_stack(
+            &probe(&["17.253.144.10"], true),
+            &probe(&["2600:1406:3a00:21::173e:2e65"], false),
+        );
+        assert!(disable);
+        assert!(recommendation.contains("black-holed"));
     }
 
-    // 3. Flush ARP cache
-    if let Ok(_) = Command::new("arp").arg("-d").arg("-a").output() {
-        results.push("ARP cache cleared");
+    #[test]
+    fn ipv4_only_host_is_not_a_black_hole() {
+        let (disable, recommendation) =
+            assess_dual_stack(&probe(&["93.184.216.34"], true), &probe(&[], false));
+        assert!(!disable);
+        assert_eq!(
+            recommendation,
+            "Host has no IPv6 address, only IPv4 could be tested"
+        );
     }
 
-    // 4. Release and renew DHCP (for primary interface)
-    // Get primary interface first
-    if let Ok(output) = Command::new("route")
-        .args(["-n", "get", "default"])
-        .output()
-    {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines() {
-            if line.contains("interface:") {
-                let interface = line.split(':').nth(1).map(|s| s.trim());
-                if let Some(iface) = interface {
-                    // Try to get the service name for this interface
-                    if let Ok(services_output) = Command::new("networksetup")
-                        .arg("-listallhardwareports")
-                        .output()
-                    {
-                        let services_str = String::from_utf8_lossy(&services_output.stdout);
-                        let mut current_port = String::new();
-
-                        for svc_line in services_str.lines() {
-                            if svc_line.starts_with("Hardware Port:") {
-                                current_port =
-                                    svc_line.replace("Hardware Port: ", "").trim().to_string();
-                            } else if svc_line.starts_with("Device:") {
-                                let device = svc_line.replace("Device: ", "").trim().to_string();
-                                if device == iface && !current_port.is_empty() {
-                                    // Renew DHCP for this service
-                                    if let Ok(_) = Command::new("ipconfig")
-                                        .args(["set", iface, "DHCP"])
-                                        .output()
-                                    {
-                                        results.push("DHCP renewed");
-                                    }
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                }
-                break;
-            }
+    #[test]
+    fn other_outcomes_keep_ipv6() {
+        let v4 = ["17.253.144.10"];
+        let v6 = ["2600:1406:3a00:21::173e:2e65"];
+        let cases = [
+            (true, true, "IPv4 and IPv6 both work"),
+            (false, true, "Only IPv6 works"),
+            (false, false, "Neither IPv4 nor IPv6 could connect"),
+        ];
+        for (v4_up, v6_up, expected) in cases {
+            let (disable, recommendation) =
+                assess_dual_stack(&probe(&v4, v4_up), &probe(&v6, v6_up));
+            assert!(!disable, "{}", expected);
+            assert!(recommendation.starts_with(expected), "{}", recommendation);
         }
     }
-
-    if results.is_empty() {
-        Ok("Network reset attempted".to_string())
-    } else {
-        Ok(format!("Network reset completed: {}", results.join(", ")))
-    }
 }
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
+use super::location::current_location;
+use super::runner::invalidate_command_cache;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DnsSettings {
     pub servers: Vec<String>,
     pub service_name: String,
+    pub location: Option<String>,
 }
 
 #[tauri::command]
@@ -28,6 +32,7 @@ pub fn get_dns_servers(service_name: String) -> Result<DnsSettings, String> {
     Ok(DnsSettings {
         servers,
         service_name,
+        location: current_location(),
     })
 }
 
@@ -45,6 +50,7 @@ pub fn set_dns_servers(service_name: String, servers: Vec<String>) -> Result<Str
         .args(&args)
         .output()
         .map_err(|e| format!("Failed to execute command: {}", e))?;
+    invalidate_command_cache();
 
     if output.status.success() {
         if servers.is_empty() {
@@ -73,6 +79,57 @@ pub fn flush_dns_cache() -> Result<String, String> {
     let _ = Command::new("killall")
         .args(["-HUP", "mDNSResponder"])
         .output();
+    invalidate_command_cache();
 
     Ok("DNS cache flushed successfully".to_string())
 }
+
+#[tauri::command]
+pub fn get_search_domains(service_name: String) -> Result<Vec<String>, String> {
+    let output = Command::new("networksetup")
+        .args(["-getsearchdomains", &service_name])
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let domains = stdout
+        .lines()
+        .map(|line| line.trim())
+        // Skip messages like "There aren't any Search Domains set on Wi-Fi."
+        .filter(|line| !line.is_empty() && !line.contains("aren't any"))
+        .map(|line| line.to_string())
+        .collect();
+
+    Ok(domains)
+}
+
+#[tauri::command]
+pub fn set_search_domains(service_name: String, domains: Vec<String>) -> Result<String, String> {
+    let mut args = vec!["-setsearchdomains".to_string(), service_name.clone()];
+
+    if domains.is_empty() {
+        args.push("Empty".to_string());
+    } else {
+        args.extend(domains.clone());
+    }
+
+    let output = Command::new("networksetup")
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+    invalidate_command_cache();
+
+    if output.status.success() {
+        if domains.is_empty() {
+            Ok(format!("Search domains cleared for {}", service_name))
+        } else {
+            Ok(format!(
+                "Search domains set to {:?} for {}",
+                domains, service_name
+            ))
+        }
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("Failed to set search domains: {}", stderr))
+    }
+}
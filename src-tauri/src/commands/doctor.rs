@@ -0,0 +1,2055 @@
+use serde::{Deserialize, Serialize};
+use std::net::ToSocketAddrs;
+use std::process::Command;
+
+use super::captive::{
+    probe_captive_portal, CaptivePortalResult, CaptiveStatus, DEFAULT_EXPECTED_BODY,
+};
+use super::dhcp::{get_dhcp_lease, DhcpLease};
+use super::diagnostics::{ping_host, PingResult};
+use super::firewall::{
+    pf_blocking_rule, read_application_firewall, read_packet_filter, ApplicationFirewall,
+    PfDirection, PfStatus, TrafficProbe,
+};
+use super::ip::{get_ip_config_internal, validate_manual_config, IpConfig, IpMode};
+use super::neighbor::{list_neighbors, normalize_mac, NeighborEntry};
+use super::network::{
+    get_default_route, get_interface_status, get_network_services, DefaultRoute, InterfaceStatus,
+    NetworkService,
+};
+use super::ports::{check_proxy_listeners, collect_listeners, Listener};
+use super::proxy::{get_proxy_settings, ProxySettings};
+use super::runner::system_runner;
+use super::vpn::{get_vpn_status, VpnState};
+
+const DNS_PROBE_HOST: &str = "www.apple.com";
+const HTTP_PROBE_URL: &str = "http://captive.apple.com/hotspot-detect.html";
+
+// Fix action ids understood by the frontend
+pub const FIX_ENABLE_SERVICE: &str = "enable_service";
+pub const FIX_RENEW_DHCP: &str = "renew_dhcp";
+pub const FIX_SET_DHCP: &str = "set_dhcp";
+pub const FIX_RESET_NETWORK: &str = "reset_network";
+pub const FIX_FLUSH_DNS: &str = "flush_dns_cache";
+pub const FIX_SET_DNS_SERVERS: &str = "set_dns_servers";
+pub const FIX_CLEAR_PROXIES: &str = "clear_all_proxies";
+pub const FIX_STOP_VPN: &str = "stop_vpn";
+pub const FIX_OPEN_CAPTIVE_PORTAL: &str = "open_captive_portal";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Skipped,
+    Ok,
+    Info,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+    pub summary: String,
+    pub evidence: Vec<String>,
+    pub fix_action: Option<String>,
+    pub children: Vec<Finding>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiagnosisReport {
+    pub service_name: Option<String>,
+    pub device: Option<String>,
+    pub overall: Severity,
+    pub findings: Vec<Finding>,
+}
+
+impl Finding {
+    fn new(id: &str, title: &str, severity: Severity, summary: impl Into<String>) -> Self {
+        Finding {
+            id: id.to_string(),
+            title: title.to_string(),
+            severity,
+            summary: summary.into(),
+            evidence: Vec::new(),
+            fix_action: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn evidence(mut self, line: impl Into<String>) -> Self {
+        self.evidence.push(line.into());
+        self
+    }
+
+    fn fix(mut self, action: &str) -> Self {
+        self.fix_action = Some(action.to_string());
+        self
+    }
+
+    fn skipped(id: &str, title: &str, blocked_by: &str) -> Self {
+        Finding::new(
+            id,
+            title,
+            Severity::Skipped,
+            format!("Skipped because '{}' failed", blocked_by),
+        )
+    }
+
+    fn blocks_children(&self) -> bool {
+        matches!(self.severity, Severity::Critical | Severity::Skipped)
+    }
+
+    /// Highest severity in this finding and everything below it
+    fn worst(&self) -> Severity {
+        self.children
+            .iter()
+            .map(Finding::worst)
+            .fold(self.severity, Severity::max)
+    }
+}
+
+/// Everything the diagnosis pipeline needs from the system.
+///
+/// `SystemEnv` talks to the real machine; tests can provide fixtures instead.
+pub trait DiagnosisEnv {
+    fn network_services(&self) -> Result<Vec<NetworkService>, String>;
+    fn interface_status(&self, device: &str) -> InterfaceStatus;
+    fn ipv4_address(&self, device: &str) -> Option<String>;
+    fn default_route(&self) -> Option<DefaultRoute>;
+    fn ping(&self, target: &str) -> Result<PingResult, String>;
+    fn resolve_host(&self, host: &str) -> Result<Vec<String>, String>;
+    fn proxy_settings(&self, service_name: &str) -> Result<ProxySettings, String>;
+    fn listeners(&self) -> Result<Vec<Listener>, String>;
+    fn http_status(&self, url: &str) -> Result<u16, String>;
+    fn ip_config(&self, service_name: &str) -> Result<IpConfig, String>;
+    fn dhcp_lease(&self, device: &str) -> Option<DhcpLease>;
+    fn neighbors(&self) -> Vec<NeighborEntry>;
+    fn vpn_state(&self) -> Option<VpnState>;
+    fn captive_portal(&self, probe_url: &str) -> Result<CaptivePortalResult, String>;
+    fn application_firewall(&self) -> Option<ApplicationFirewall>;
+    /// `None` when pf cannot be read, which is the case without root
+    fn packet_filter(&self) -> Option<PfStatus>;
+}
+
+pub struct SystemEnv;
+
+impl DiagnosisEnv for SystemEnv {
+    fn network_services(&self) -> Result<Vec<NetworkService>, String> {
+        get_network_services()
+    }
+
+    fn interface_status(&self, device: &str) -> InterfaceStatus {
+        get_interface_status(device)
+    }
+
+    fn ipv4_address(&self, device: &str) -> Option<String> {
+        let output = Command::new("ipconfig")
+            .args(["getifaddr", device])
+            .output()
+            .ok()?;
+        let address = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !address.is_empty() {
+            Some(address)
+        } else {
+            None
+        }
+    }
+
+    fn default_route(&self) -> Option<DefaultRoute> {
+        get_default_route()
+    }
+
+    fn ping(&self, target: &str) -> Result<PingResult, String> {
+        ping_host(target.to_string(), Some(2))
+    }
+
+    fn resolve_host(&self, host: &str) -> Result<Vec<String>, String> {
+        let addrs = (host, 80)
+            .to_socket_addrs()
+            .map_err(|e| format!("Failed to resolve {}: {}", host, e))?;
+        Ok(addrs.map(|addr| addr.ip().to_string()).collect())
+    }
+
+    fn proxy_settings(&self, service_name: &str) -> Result<ProxySettings, String> {
+        get_proxy_settings(service_name.to_string())
+    }
+
+    fn listeners(&self) -> Result<Vec<Listener>, String> {
+        collect_listeners(system_runner())
+    }
+
+    fn http_status(&self, url: &str) -> Result<u16, String> {
+        let output = Command::new("curl")
+            .args([
+                "-s",
+                "-o",
+                "/dev/null",
+                "--max-time",
+                "5",
+                "-w",
+                "%{http_code}",
+                url,
+            ])
+            .output()
+            .map_err(|e| format!("Failed to execute curl: {}", e))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match stdout.trim().parse::<u16>() {
+            Ok(code) if code != 0 => Ok(code),
+            _ => Err(format!(
+                "No HTTP response from {} (curl exit code {})",
+                url,
+                output.status.code().unwrap_or(-1)
+            )),
+        }
+    }
+
+    fn ip_config(&self, service_name: &str) -> Result<IpConfig, String> {
+        get_ip_config_internal(service_name)
+    }
+
+    fn dhcp_lease(&self, device: &str) -> Option<DhcpLease> {
+        get_dhcp_lease(device.to_string()).ok()
+    }
+
+    fn neighbors(&self) -> Vec<NeighborEntry> {
+        list_neighbors().unwrap_or_default()
+    }
+
+    fn vpn_state(&self) -> Option<VpnState> {
+        get_vpn_status().ok()
+    }
+
+    fn captive_portal(&self, probe_url: &str) -> Result<CaptivePortalResult, String> {
+        probe_captive_portal(probe_url, DEFAULT_EXPECTED_BODY)
+    }
+
+    fn application_firewall(&self) -> Option<ApplicationFirewall> {
+        read_application_firewall(system_runner()).ok()
+    }
+
+    fn packet_filter(&self) -> Option<PfStatus> {
+        read_packet_filter(system_runner())
+    }
+}
+
+/// Pick the service to diagnose: the one carrying the default route, else the first
+/// connected enabled service, else the first enabled one
+fn select_service<'a>(
+    services: &'a [NetworkService],
+    route: Option<&DefaultRoute>,
+) -> Option<&'a NetworkService> {
+    route
+        .and_then(|route| services.iter().find(|s| s.device == route.interface))
+        .or_else(|| {
+            services
+                .iter()
+                .find(|s| s.enabled && s.status == InterfaceStatus::Connected)
+        })
+        .or_else(|| services.iter().find(|s| s.enabled))
+}
+
+fn check_link(env: &dyn DiagnosisEnv, service: &NetworkService) -> Finding {
+    let title = "Interface link";
+    if !service.enabled {
+        return Finding::new(
+            "interface_link",
+            title,
+            Severity::Critical,
+            format!("Service '{}' is disabled", service.name),
+        )
+        .fix(FIX_ENABLE_SERVICE);
+    }
+
+    let status = env.interface_status(&service.device);
+    let evidence = format!("{} ({}): {:?}", service.name, service.device, status);
+    match status {
+        InterfaceStatus::Connected => {
+            Finding::new("interface_link", title, Severity::Ok, "Link is up").evidence(evidence)
+        }
+        InterfaceStatus::Disconnected => Finding::new(
+            "interface_link",
+            title,
+            Severity::Critical,
+            "Interface has no link (cable unplugged or Wi-Fi not joined)",
+        )
+        .evidence(evidence),
+        InterfaceStatus::Unavailable => Finding::new(
+            "interface_link",
+            title,
+            Severity::Critical,
+            "Interface is not available",
+        )
+        .evidence(evidence)
+        .fix(FIX_RESET_NETWORK),
+    }
+}
+
+fn check_ip_address(env: &dyn DiagnosisEnv, service: &NetworkService) -> Finding {
+    let title = "IP address / DHCP lease";
+    match env.ipv4_address(&service.device) {
+        None => Finding::new(
+            "ip_address",
+            title,
+            Severity::Critical,
+            "No IPv4 address assigned",
+        )
+        .evidence(format!(
+            "ipconfig getifaddr {} returned nothing",
+            service.device
+        ))
+        .fix(FIX_RENEW_DHCP),
+        Some(address) if address.starts_with("169.254.") => Finding::new(
+            "ip_address",
+            title,
+            Severity::Critical,
+            "Self-assigned address, DHCP server did not answer",
+        )
+        .evidence(format!("{}: {}", service.device, address))
+        .fix(FIX_RENEW_DHCP),
+        Some(address) => Finding::new("ip_address", title, Severity::Ok, "IPv4 address assigned")
+            .evidence(format!("{}: {}", service.device, address)),
+    }
+}
+
+fn check_default_route(route: Option<&DefaultRoute>, service: &NetworkService) -> Finding {
+    let title = "Default route";
+    match route {
+        None => Finding::new(
+            "default_route",
+            title,
+            Severity::Critical,
+            "There is no default route",
+        )
+        .fix(FIX_RENEW_DHCP),
+        Some(route) if route.gateway.is_none() => Finding::new(
+            "default_route",
+            title,
+            Severity::Critical,
+            "Default route has no gateway",
+        )
+        .evidence(format!("interface: {}", route.interface))
+        .fix(FIX_RENEW_DHCP),
+        Some(route) if route.interface != service.device => Finding::new(
+            "default_route",
+            title,
+            Severity::Warning,
+            format!(
+                "Default route uses {} instead of {}",
+                route.interface, service.device
+            ),
+        )
+        .evidence(format!(
+            "gateway: {}, interface: {}",
+            route.gateway.as_deref().unwrap_or_default(),
+            route.interface
+        )),
+        Some(route) => Finding::new(
+            "default_route",
+            title,
+            Severity::Ok,
+            "Default route present",
+        )
+        .evidence(format!(
+            "gateway: {}, interface: {}",
+            route.gateway.as_deref().unwrap_or_default(),
+            route.interface
+        )),
+    }
+}
+
+fn check_gateway(env: &dyn DiagnosisEnv, gateway: &str) -> Finding {
+    let title = "Gateway reachability";
+    match env.ping(gateway) {
+        Ok(result) if result.success => {
+            Finding::new("gateway_ping", title, Severity::Ok, "Gateway answers ping").evidence(
+                format!("{}: {:.1} ms", gateway, result.time_ms.unwrap_or_default()),
+            )
+        }
+        // Some routers drop ICMP, so an unanswered ping alone is not fatal
+        Ok(result) => Finding::new(
+            "gateway_ping",
+            title,
+            Severity::Warning,
+            "Gateway does not answer ping",
+        )
+        .evidence(format!(
+            "{}: {}",
+            gateway,
+            result.error.unwrap_or_default().trim()
+        ))
+        .fix(FIX_RESET_NETWORK),
+        Err(e) => Finding::new(
+            "gateway_ping",
+            title,
+            Severity::Warning,
+            "Ping could not run",
+        )
+        .evidence(e),
+    }
+}
+
+fn check_dns(env: &dyn DiagnosisEnv) -> Finding {
+    let title = "DNS resolution";
+    match env.resolve_host(DNS_PROBE_HOST) {
+        Ok(addrs) if !addrs.is_empty() => Finding::new(
+            "dns_resolution",
+            title,
+            Severity::Ok,
+            format!("{} resolves", DNS_PROBE_HOST),
+        )
+        .evidence(addrs.join(", ")),
+        Ok(_) => Finding::new(
+            "dns_resolution",
+            title,
+            Severity::Critical,
+            format!("{} resolved to no addresses", DNS_PROBE_HOST),
+        )
+        .fix(FIX_SET_DNS_SERVERS),
+        Err(e) => Finding::new(
+            "dns_resolution",
+            title,
+            Severity::Critical,
+            format!("{} does not resolve", DNS_PROBE_HOST),
+        )
+        .evidence(e)
+        .fix(FIX_FLUSH_DNS),
+    }
+}
+
+fn check_proxy(env: &dyn DiagnosisEnv, service: &NetworkService) -> Finding {
+    let title = "Proxy settings";
+    let settings = match env.proxy_settings(&service.name) {
+        Ok(settings) => settings,
+        Err(e) => {
+            return Finding::new(
+                "proxy_settings",
+                title,
+                Severity::Info,
+                "Proxy settings could not be read",
+            )
+            .evidence(e)
+        }
+    };
+
+    let enabled: Vec<String> = [
+        ("HTTP", &settings.http_proxy),
+        ("HTTPS", &settings.https_proxy),
+        ("SOCKS", &settings.socks_proxy),
+    ]
+    .iter()
+    .filter(|(_, config)| config.enabled)
+    .map(|(kind, config)| format!("{} proxy: {}:{}", kind, config.server, config.port))
+    .collect();
+
+    if enabled.is_empty() {
+        return Finding::new("proxy_settings", title, Severity::Ok, "No proxy configured");
+    }
+
+    let mut evidence = enabled;
+    let mut not_listening = Vec::new();
+    match env.listeners() {
+        Ok(listeners) => {
+            for check in check_proxy_listeners(&settings, &listeners) {
+                let owner = check
+                    .listeners
+                    .iter()
+                    .map(|l| match (&l.process, l.pid) {
+                        (Some(process), Some(pid)) => format!("{} (pid {})", process, pid),
+                        (None, Some(pid)) => format!("pid {}", pid),
+                        (Some(process), None) => process.clone(),
+                        (None, None) => "an unknown process".to_string(),
+                    })
+                    .next();
+                match (check.listening, owner) {
+                    (Some(true), Some(owner)) => evidence.push(format!(
+                        "{}:{} is served by {}",
+                        check.server, check.port, owner
+                    )),
+                    (Some(false), _) => {
+                        evidence.push(format!(
+                            "Nothing listens on {}:{}",
+                            check.server, check.port
+                        ));
+                        not_listening.push(check.kind);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Err(e) => evidence.push(format!("Listening ports could not be read: {}", e)),
+    }
+
+    let mut finding = if not_listening.is_empty() {
+        Finding::new(
+            "proxy_settings",
+            title,
+            Severity::Warning,
+            "A system proxy is active; traffic fails if the proxy is down",
+        )
+    } else {
+        Finding::new(
+            "proxy_settings",
+            title,
+            Severity::Critical,
+            format!(
+                "The local {} {} not running, so proxied traffic fails",
+                not_listening.join(" and "),
+                if not_listening.len() == 1 {
+                    "proxy is"
+                } else {
+                    "proxies are"
+                }
+            ),
+        )
+    }
+    .fix(FIX_CLEAR_PROXIES);
+    finding.evidence = evidence;
+    finding
+}
+
+/// Judge a service's IPv4 addressing from its configured mode, the addresses on the
+/// interface, its DHCP lease and the neighbour table: self-assigned (169.254) addresses,
+/// a missing router and a manual address that another host also uses
+pub fn assess_addressing(
+    service: &NetworkService,
+    config: Option<&IpConfig>,
+    lease: Option<&DhcpLease>,
+    neighbors: &[NeighborEntry],
+) -> Finding {
+    let id = "addressing";
+    let title = "Address configuration";
+    let addresses: Vec<&str> = service
+        .details
+        .iter()
+        .flat_map(|details| details.ipv4.iter())
+        .map(|addr| addr.address.as_str())
+        .filter(|addr| !addr.starts_with("127."))
+        .collect();
+    let self_assigned: Vec<&str> = addresses
+        .iter()
+        .copied()
+        .filter(|addr| addr.starts_with("169.254."))
+        .collect();
+    let has_routable = addresses.len() > self_assigned.len();
+
+    let finding = |severity: Severity, summary: String| {
+        let mut finding = Finding::new(id, title, severity, summary)
+            .evidence(format!("Service: {} ({})", service.name, service.device))
+            .evidence(format!("Addresses: {}", addresses.join(", ")));
+        if let Some(config) = config {
+            finding = finding.evidence(format!("Mode: {:?}", config.mode));
+        }
+        if let Some(lease) = lease {
+            finding = finding.evidence(format!(
+                "Lease: {} from {}, router {}",
+                lease.ip_address.as_deref().unwrap_or("none"),
+                lease
+                    .server_identifier
+                    .as_deref()
+                    .unwrap_or("unknown server"),
+                lease.routers.first().map_or("none", |r| r.as_str())
+            ));
+        }
+        finding
+    };
+
+    let Some(config) = config else {
+        if !has_routable && !self_assigned.is_empty() {
+            return finding(
+                Severity::Critical,
+                "Self-assigned address, DHCP server did not answer".to_string(),
+            )
+            .fix(FIX_RENEW_DHCP);
+        }
+        return finding(
+            Severity::Info,
+            "IP configuration could not be read".to_string(),
+        );
+    };
+
+    match config.mode {
+        IpMode::Manual => {
+            let (Some(ip), Some(mask), Some(router)) = (
+                config.ip_address.as_deref(),
+                config.subnet_mask.as_deref(),
+                config.router.as_deref(),
+            ) else {
+                let missing: Vec<&str> = [
+                    ("IP address", &config.ip_address),
+                    ("subnet mask", &config.subnet_mask),
+                    ("router", &config.router),
+                ]
+                .into_iter()
+                .filter(|(_, value)| value.is_none())
+                .map(|(name, _)| name)
+                .collect();
+                let missing = match missing.split_last() {
+                    Some((last, rest)) if !rest.is_empty() => {
+                        format!("{} and {}", rest.join(", "), last)
+                    }
+                    _ => missing.join(""),
+                };
+                return finding(
+                    Severity::Critical,
+                    format!("Manual configuration has no {}", missing),
+                )
+                .fix(FIX_SET_DHCP);
+            };
+            if let Err(e) = validate_manual_config(ip, mask, router) {
+                return finding(
+                    Severity::Critical,
+                    format!("Manual configuration is inconsistent: {}", e),
+                )
+                .fix(FIX_SET_DHCP);
+            }
+
+            let own_mac = service
+                .details
+                .as_ref()
+                .and_then(|d| d.mac_address.as_deref())
+                .map(normalize_mac);
+            let conflict = neighbors.iter().find(|n| {
+                n.ip_address == ip && n.mac_address.is_some() && n.mac_address != own_mac
+            });
+            if let Some(other) = conflict {
+                return finding(
+                    Severity::Critical,
+                    format!(
+                        "IP address {} is also used by {}",
+                        ip,
+                        other.mac_address.as_deref().unwrap_or_default()
+                    ),
+                )
+                .fix(FIX_SET_DHCP);
+            }
+            if !addresses.contains(&ip) {
+                // macOS drops a conflicting manual address and falls back to 169.254
+                let summary = if self_assigned.is_empty() {
+                    format!("Manual address {} is not active on {}", ip, service.device)
+                } else {
+                    format!(
+                        "Manual address {} was replaced by a self-assigned address, \
+                         likely an IP conflict",
+                        ip
+                    )
+                };
+                return finding(Severity::Critical, summary).fix(FIX_SET_DHCP);
+            }
+
+            finding(
+                Severity::Ok,
+                "Manual configuration is consistent".to_string(),
+            )
+        }
+        IpMode::Dhcp | IpMode::Bootp => {
+            if !has_routable && !self_assigned.is_empty() {
+                return finding(
+                    Severity::Critical,
+                    format!(
+                        "Self-assigned address {}, DHCP server did not answer",
+                        self_assigned[0]
+                    ),
+                )
+                .fix(FIX_RENEW_DHCP);
+            }
+            let Some(lease) = lease else {
+                return finding(Severity::Critical, "No DHCP lease".to_string())
+                    .fix(FIX_RENEW_DHCP);
+            };
+            if lease.routers.is_empty() {
+                return finding(
+                    Severity::Warning,
+                    "DHCP lease provides no router".to_string(),
+                )
+                .fix(FIX_RENEW_DHCP);
+            }
+            if let Some(leased) = lease.ip_address.as_deref() {
+                if !addresses.contains(&leased) {
+                    return finding(
+                        Severity::Warning,
+                        format!("Leased address {} is not active on the interface", leased),
+                    )
+                    .fix(FIX_RENEW_DHCP);
+                }
+            }
+
+            finding(Severity::Ok, "DHCP lease is healthy".to_string())
+        }
+        IpMode::ManualWithDhcpRouter => {
+            if !has_routable && !self_assigned.is_empty() {
+                return finding(
+                    Severity::Critical,
+                    "Manual address was replaced by a self-assigned address".to_string(),
+                )
+                .fix(FIX_SET_DHCP);
+            }
+            if config.router.is_none() {
+                return finding(
+                    Severity::Critical,
+                    "DHCP server did not provide a router".to_string(),
+                )
+                .fix(FIX_SET_DHCP);
+            }
+
+            finding(Severity::Ok, "Router received from DHCP".to_string())
+        }
+        IpMode::Off => finding(
+            Severity::Info,
+            "IPv4 is turned off for this service".to_string(),
+        )
+        .fix(FIX_SET_DHCP),
+        IpMode::Unknown => finding(
+            Severity::Info,
+            "IP configuration mode is unknown".to_string(),
+        ),
+    }
+}
+
+fn check_addressing(env: &dyn DiagnosisEnv, service: &NetworkService) -> Finding {
+    let config = env.ip_config(&service.name).ok();
+    let lease = env.dhcp_lease(&service.device);
+
+    assess_addressing(service, config.as_ref(), lease.as_ref(), &env.neighbors())
+}
+
+fn check_http(env: &dyn DiagnosisEnv) -> Finding {
+    let title = "HTTP reachability";
+    match env.http_status(HTTP_PROBE_URL) {
+        Ok(code) if (200..400).contains(&code) => Finding::new(
+            "http_reachability",
+            title,
+            Severity::Ok,
+            "Internet is reachable over HTTP",
+        )
+        .evidence(format!("{} -> HTTP {}", HTTP_PROBE_URL, code)),
+        Ok(code) => Finding::new(
+            "http_reachability",
+            title,
+            Severity::Warning,
+            format!("Unexpected HTTP status {}", code),
+        )
+        .evidence(format!("{} -> HTTP {}", HTTP_PROBE_URL, code)),
+        Err(e) => Finding::new(
+            "http_reachability",
+            title,
+            Severity::Critical,
+            "HTTP request failed",
+        )
+        .evidence(e)
+        .fix(FIX_RESET_NETWORK),
+    }
+}
+
+fn check_captive_portal(env: &dyn DiagnosisEnv) -> Finding {
+    let title = "Captive portal";
+    let result = match env.captive_portal(HTTP_PROBE_URL) {
+        Ok(result) => result,
+        Err(e) => {
+            return Finding::new(
+                "captive_portal",
+                title,
+                Severity::Info,
+                "Captive portal probe could not run",
+            )
+            .evidence(e)
+        }
+    };
+
+    match result.status {
+        CaptiveStatus::Open => {
+            Finding::new("captive_portal", title, Severity::Ok, "No captive portal")
+                .evidence(result.detail)
+        }
+        CaptiveStatus::CaptivePortal => {
+            let mut finding = Finding::new(
+                "captive_portal",
+                title,
+                Severity::Critical,
+                "The network requires signing in through a captive portal",
+            )
+            .evidence(result.detail)
+            .fix(FIX_OPEN_CAPTIVE_PORTAL);
+            if let Some(url) = result.login_url {
+                finding = finding.evidence(format!("Login page: {}", url));
+            }
+            finding
+        }
+        CaptiveStatus::Offline => Finding::new(
+            "captive_portal",
+            title,
+            Severity::Warning,
+            "The captive portal probe got no response",
+        )
+        .evidence(result.detail),
+    }
+}
+
+/// Severity of the finding with `id` anywhere in the tree
+fn severity_of(findings: &[Finding], id: &str) -> Option<Severity> {
+    findings.iter().find_map(|finding| {
+        if finding.id == id {
+            Some(finding.severity)
+        } else {
+            severity_of(&finding.children, id)
+        }
+    })
+}
+
+/// Whether the finding with `id` reported a problem
+fn failed(findings: &[Finding], id: &str) -> bool {
+    severity_of(findings, id).is_some_and(|s| matches!(s, Severity::Warning | Severity::Critical))
+}
+
+/// Whether an active VPN explains DNS, routing or proxy problems found elsewhere
+fn check_vpn(vpn: Option<&VpnState>, findings: &[Finding]) -> Finding {
+    let title = "VPN";
+    let Some(vpn) = vpn.filter(|vpn| vpn.is_active()) else {
+        return Finding::new("vpn", title, Severity::Ok, "No VPN is active");
+    };
+
+    let name = vpn.active_name().unwrap_or_else(|| "A VPN".to_string());
+    let mut evidence = Vec::new();
+    for tunnel in &vpn.tunnels {
+        let mut effects = Vec::new();
+        if tunnel.takes_default_route {
+            effects.push("default route".to_string());
+        }
+        if !tunnel.dns_servers.is_empty() {
+            effects.push(format!("DNS {}", tunnel.dns_servers.join(", ")));
+        }
+        evidence.push(format!(
+            "{}: {} ({})",
+            tunnel.name,
+            tunnel.addresses.join(", "),
+            if effects.is_empty() {
+                "split tunnel".to_string()
+            } else {
+                effects.join(", ")
+            }
+        ));
+    }
+
+    let mut suspects = Vec::new();
+    if vpn.dns_via_tunnel
+        && (failed(findings, "dns_resolution") || failed(findings, "http_reachability"))
+    {
+        suspects.push("DNS");
+    }
+    if vpn.default_route_via_tunnel
+        && (failed(findings, "default_route") || failed(findings, "http_reachability"))
+    {
+        suspects.push("routing");
+    }
+    // Any enabled proxy is a warning on its own; only a broken one implicates the VPN,
+    // since VPN clients commonly push a proxy or PAC configuration
+    if severity_of(findings, "proxy_settings") == Some(Severity::Critical) {
+        suspects.push("proxy");
+    }
+
+    let mut finding = if suspects.is_empty() {
+        Finding::new("vpn", title, Severity::Info, format!("{} is active", name))
+    } else {
+        Finding::new(
+            "vpn",
+            title,
+            Severity::Warning,
+            format!(
+                "{} is active and the likely cause of the {} problems",
+                name,
+                suspects.join(" and ")
+            ),
+        )
+        .fix(FIX_STOP_VPN)
+    };
+    finding.evidence = evidence;
+    finding
+}
+
+/// Whether the application firewall or pf rules get in the way of the traffic
+/// the other checks send
+fn check_firewall(
+    firewall: Option<&ApplicationFirewall>,
+    pf: Option<&PfStatus>,
+    service: &NetworkService,
+    findings: &[Finding],
+) -> Finding {
+    let title = "Firewall";
+    let mut problems = Vec::new();
+    let mut evidence = Vec::new();
+
+    match firewall {
+        Some(firewall) => {
+            evidence.push(format!(
+                "Application firewall {}, stealth mode {}",
+                if firewall.enabled { "on" } else { "off" },
+                if firewall.stealth_mode { "on" } else { "off" }
+            ));
+            for app in firewall.apps.iter().filter(|app| !app.allow_incoming) {
+                evidence.push(format!("Incoming connections blocked for {}", app.path));
+            }
+            if firewall.enabled && firewall.block_all {
+                problems.push(
+                    "\"Block all incoming connections\" is on, which also blocks sharing \
+                     services and incoming connections of every app"
+                        .to_string(),
+                );
+            }
+        }
+        None => evidence.push("Application firewall state is unavailable".to_string()),
+    }
+
+    match pf {
+        Some(pf) if pf.enabled => {
+            evidence.push(format!("pf is enabled with {} anchors", pf.anchors.len()));
+            let probes = [
+                ("HTTP", "tcp", Some(80), "http_reachability"),
+                ("DNS", "udp", Some(53), "dns_resolution"),
+                ("ping", "icmp", None, "gateway_ping"),
+            ];
+            for (name, protocol, port, check_id) in probes {
+                let probe = TrafficProbe {
+                    direction: PfDirection::Out,
+                    protocol: protocol.to_string(),
+                    port,
+                    interface: Some(service.device.clone()),
+                };
+                let Some(rule) = pf_blocking_rule(pf, &probe) else {
+                    continue;
+                };
+                let anchor = rule
+                    .ruleset
+                    .as_ref()
+                    .map(|anchor| format!(" (anchor {})", anchor))
+                    .unwrap_or_default();
+                evidence.push(format!("{}{}", rule.text, anchor));
+                problems.push(if failed(findings, check_id) {
+                    format!(
+                        "pf blocks outgoing {}, the likely cause of its failure",
+                        name
+                    )
+                } else {
+                    format!("pf blocks outgoing {}", name)
+                });
+            }
+        }
+        Some(_) => evidence.push("pf is disabled".to_string()),
+        None => {
+            evidence.push("pf rules can only be read with administrator privileges".to_string())
+        }
+    }
+
+    let mut finding = if !problems.is_empty() {
+        Finding::new("firewall", title, Severity::Warning, problems.join("; "))
+    } else if firewall.is_some_and(|f| f.enabled) || pf.is_some_and(|pf| pf.enabled) {
+        Finding::new(
+            "firewall",
+            title,
+            Severity::Info,
+            "The firewall is on and does not block the tested traffic",
+        )
+    } else {
+        Finding::new(
+            "firewall",
+            title,
+            Severity::Ok,
+            "No firewall restrictions found",
+        )
+    };
+    finding.evidence = evidence;
+    finding
+}
+
+/// Run `check` as a child of `parent`, or record it as skipped when the parent failed
+fn then<F>(parent: &mut Finding, id: &str, title: &str, check: F)
+where
+    F: FnOnce() -> Finding,
+{
+    let child = if parent.blocks_children() {
+        Finding::skipped(id, title, &parent.title)
+    } else {
+        check()
+    };
+    parent.children.push(child);
+}
+
+// Dependency order: link -> ip -> route -> { gateway ping, dns -> http -> captive portal }
+
+fn diagnose_link(
+    env: &dyn DiagnosisEnv,
+    service: &NetworkService,
+    route: Option<&DefaultRoute>,
+) -> Finding {
+    let mut finding = check_link(env, service);
+    then(
+        &mut finding,
+        "ip_address",
+        "IP address / DHCP lease",
+        || diagnose_ip(env, service, route),
+    );
+    finding
+}
+
+fn diagnose_ip(
+    env: &dyn DiagnosisEnv,
+    service: &NetworkService,
+    route: Option<&DefaultRoute>,
+) -> Finding {
+    let mut finding = check_ip_address(env, service);
+    then(&mut finding, "default_route", "Default route", || {
+        diagnose_route(env, service, route)
+    });
+    finding
+}
+
+fn diagnose_route(
+    env: &dyn DiagnosisEnv,
+    service: &NetworkService,
+    route: Option<&DefaultRoute>,
+) -> Finding {
+    let mut finding = check_default_route(route, service);
+    let gateway = route.and_then(|r| r.gateway.as_deref()).unwrap_or_default();
+    then(&mut finding, "gateway_ping", "Gateway reachability", || {
+        check_gateway(env, gateway)
+    });
+    then(&mut finding, "dns_resolution", "DNS resolution", || {
+        diagnose_dns(env)
+    });
+    finding
+}
+
+fn diagnose_dns(env: &dyn DiagnosisEnv) -> Finding {
+    let mut finding = check_dns(env);
+    then(
+        &mut finding,
+        "http_reachability",
+        "HTTP reachability",
+        || diagnose_http(env),
+    );
+    finding
+}
+
+fn diagnose_http(env: &dyn DiagnosisEnv) -> Finding {
+    let mut finding = check_http(env);
+    then(&mut finding, "captive_portal", "Captive portal", || {
+        check_captive_portal(env)
+    });
+    finding
+}
+
+fn report(service: Option<&NetworkService>, findings: Vec<Finding>) -> DiagnosisReport {
+    let overall = findings
+        .iter()
+        .map(Finding::worst)
+        .fold(Severity::Skipped, Severity::max);
+
+    DiagnosisReport {
+        service_name: service.map(|s| s.name.clone()),
+        device: service.map(|s| s.device.clone()),
+        overall,
+        findings,
+    }
+}
+
+/// Run every check in dependency order against the given environment
+pub fn run_diagnosis(env: &dyn DiagnosisEnv) -> DiagnosisReport {
+    let services = match env.network_services() {
+        Ok(services) => services,
+        Err(e) => {
+            let finding = Finding::new(
+                "network_services",
+                "Network services",
+                Severity::Critical,
+                "Network services could not be listed",
+            )
+            .evidence(e);
+            return report(None, vec![finding]);
+        }
+    };
+
+    let route = env.default_route();
+    let Some(service) = select_service(&services, route.as_ref()) else {
+        let finding = Finding::new(
+            "network_services",
+            "Network services",
+            Severity::Critical,
+            "No enabled network service found",
+        )
+        .fix(FIX_ENABLE_SERVICE);
+        return report(None, vec![finding]);
+    };
+
+    let mut findings = vec![
+        diagnose_link(env, service, route.as_ref()),
+        check_addressing(env, service),
+        check_proxy(env, service),
+    ];
+    findings.push(check_firewall(
+        env.application_firewall().as_ref(),
+        env.packet_filter().as_ref(),
+        service,
+        &findings,
+    ));
+    findings.push(check_vpn(env.vpn_state().as_ref(), &findings));
+
+    report(Some(service), findings)
+}
+
+#[tauri::command(async)]
+pub fn run_full_diagnosis() -> Result<DiagnosisReport, String> {
+    Ok(run_diagnosis(&SystemEnv))
+}
+
+/// Check the addressing of every enabled, connected service
+#[tauri::command(async)]
+pub fn diagnose_addressing() -> Result<Vec<Finding>, String> {
+    let env = SystemEnv;
+    let neighbors = env.neighbors();
+
+    Ok(env
+        .network_services()?
+        .iter()
+        .filter(|s| s.enabled && s.status == InterfaceStatus::Connected)
+        .map(|service| {
+            let config = env.ip_config(&service.name).ok();
+            let lease = env.dhcp_lease(&service.device);
+            assess_addressing(service, config.as_ref(), lease.as_ref(), &neighbors)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::firewall::parse_pf_rules;
+    use super::super::interface::{InterfaceDetails, Ipv4Address};
+    use super::super::ip::Ipv6Mode;
+    use super::super::neighbor::NeighborProtocol;
+    use super::super::ports::ListenerFamily;
+    use super::super::proxy::ProxyConfig;
+    use super::super::vpn::{TunnelInterface, VpnService, VpnStatus};
+    use super::*;
+
+    /// Fixture environment; `FakeEnv::healthy()` passes every check
+    struct FakeEnv {
+        /// `None` makes listing the services fail
+        services: Option<Vec<(&'static str, &'static str, bool)>>,
+        link: InterfaceStatus,
+        ipv4: Option<&'static str>,
+        route: Option<DefaultRoute>,
+        ping_answers: bool,
+        dns: Result<Vec<String>, String>,
+        proxy: ProxySettings,
+        listeners: Vec<Listener>,
+        http: Result<u16, String>,
+        ip_config: Option<IpConfig>,
+        lease: Option<DhcpLease>,
+        neighbors: Vec<NeighborEntry>,
+        vpn: Option<VpnState>,
+        captive: Result<CaptivePortalResult, String>,
+        firewall: Option<ApplicationFirewall>,
+        pf: Option<PfStatus>,
+    }
+
+    impl FakeEnv {
+        fn healthy() -> Self {
+            FakeEnv {
+                services: Some(vec![("Wi-Fi", "en0", true)]),
+                link: InterfaceStatus::Connected,
+                ipv4: Some("192.168.1.20"),
+                route: Some(DefaultRoute {
+                    gateway: Some("192.168.1.1".to_string()),
+                    interface: "en0".to_string(),
+                }),
+                ping_answers: true,
+                dns: Ok(vec!["17.253.144.10".to_string()]),
+                proxy: ProxySettings::default(),
+                listeners: Vec::new(),
+                http: Ok(200),
+                ip_config: Some(IpConfig {
+                    service_name: "Wi-Fi".to_string(),
+                    mode: IpMode::Dhcp,
+                    ip_address: Some("192.168.1.20".to_string()),
+                    subnet_mask: Some("255.255.255.0".to_string()),
+                    router: Some("192.168.1.1".to_string()),
+                    client_id: None,
+                    ipv6_mode: Ipv6Mode::Automatic,
+                    ipv6_addresses: Vec::new(),
+                    ipv6_prefix_length: None,
+                    ipv6_router: None,
+                }),
+                lease: Some(DhcpLease {
+                    interface: "en0".to_string(),
+                    ip_address: Some("192.168.1.20".to_string()),
+                    server_identifier: Some("192.168.1.1".to_string()),
+                    message_type: Some("ACK".to_string()),
+                    lease_time_secs: Some(86400),
+                    renewal_time_secs: None,
+                    rebinding_time_secs: None,
+                    subnet_mask: Some("255.255.255.0".to_string()),
+                    routers: vec!["192.168.1.1".to_string()],
+                    dns_servers: vec!["192.168.1.1".to_string()],
+                    domain_name: None,
+                    options: Vec::new(),
+                }),
+                neighbors: Vec::new(),
+                vpn: None,
+                captive: Ok(CaptivePortalResult {
+                    probe_url: HTTP_PROBE_URL.to_string(),
+                    status: CaptiveStatus::Open,
+                    http_status: Some(200),
+                    login_url: None,
+                    detail: "Expected response".to_string(),
+                }),
+                firewall: None,
+                pf: None,
+            }
+        }
+    }
+
+    impl DiagnosisEnv for FakeEnv {
+        fn network_services(&self) -> Result<Vec<NetworkService>, String> {
+            let services = self
+                .services
+                .as_ref()
+                .ok_or("networksetup -listnetworkserviceorder failed")?;
+            Ok(services
+                .iter()
+                .enumerate()
+                .map(|(order, &(name, device, enabled))| NetworkService {
+                    name: name.to_string(),
+                    hardware_port: name.to_string(),
+                    device: device.to_string(),
+                    enabled,
+                    is_default: order == 0,
+                    is_primary: order == 0,
+                    skip_reason: None,
+                    is_vpn: false,
+                    order: order as i32,
+                    status: self.link.clone(),
+                    location: None,
+                    details: Some(InterfaceDetails {
+                        name: device.to_string(),
+                        mac_address: Some("a4:83:e7:00:00:01".to_string()),
+                        ipv4: self
+                            .ipv4
+                            .iter()
+                            .map(|address| Ipv4Address {
+                                address: address.to_string(),
+                                prefix_len: Some(24),
+                                broadcast: None,
+                                destination: None,
+                            })
+                            .collect(),
+                        ..Default::default()
+                    }),
+                })
+                .collect())
+        }
+
+        fn interface_status(&self, _device: &str) -> InterfaceStatus {
+            self.link.clone()
+        }
+
+        fn ipv4_address(&self, _device: &str) -> Option<String> {
+            self.ipv4.map(str::to_string)
+        }
+
+        fn default_route(&self) -> Option<DefaultRoute> {
+            self.route.clone()
+        }
+
+        fn ping(&self, target: &str) -> Result<PingResult, String> {
+            Ok(PingResult {
+                target: target.to_string(),
+                success: self.ping_answers,
+                time_ms: self.ping_answers.then_some(3.2),
+                error: (!self.ping_answers).then(|| "Request timeout".to_string()),
+            })
+        }
+
+        fn resolve_host(&self, _host: &str) -> Result<Vec<String>, String> {
+            self.dns.clone()
+        }
+
+        fn proxy_settings(&self, _service_name: &str) -> Result<ProxySettings, String> {
+            Ok(self.proxy.clone())
+        }
+
+        fn listeners(&self) -> Result<Vec<Listener>, String> {
+            Ok(self.listeners.clone())
+        }
+
+        fn http_status(&self, _url: &str) -> Result<u16, String> {
+            self.http.clone()
+        }
+
+        fn ip_config(&self, _service_name: &str) -> Result<IpConfig, String> {
+            self.ip_config.clone().ok_or("no configuration".to_string())
+        }
+
+        fn dhcp_lease(&self, _device: &str) -> Option<DhcpLease> {
+            self.lease.clone()
+        }
+
+        fn neighbors(&self) -> Vec<NeighborEntry> {
+            self.neighbors.clone()
+        }
+
+        fn vpn_state(&self) -> Option<VpnState> {
+            self.vpn.clone()
+        }
+
+        fn captive_portal(&self, _probe_url: &str) -> Result<CaptivePortalResult, String> {
+            self.captive.clone()
+        }
+
+        fn application_firewall(&self) -> Option<ApplicationFirewall> {
+            self.firewall.clone()
+        }
+
+        fn packet_filter(&self) -> Option<PfStatus> {
+            self.pf.clone()
+        }
+    }
+
+    fn find<'a>(findings: &'a [Finding], id: &str) -> &'a Finding {
+        fn search<'a>(findings: &'a [Finding], id: &str) -> Option<&'a Finding> {
+            findings.iter().find_map(|f| {
+                if f.id == id {
+                    Some(f)
+                } else {
+                    search(&f.children, id)
+                }
+            })
+        }
+        search(findings, id).unwrap_or_else(|| panic!("no finding {}", id))
+    }
+
+    fn severity(report: &DiagnosisReport, id: &str) -> Severity {
+        find(&report.findings, id).severity
+    }
+
+    #[test]
+    fn healthy_network_passes_every_check() {
+        let report = run_diagnosis(&FakeEnv::healthy());
+
+        assert_eq!(report.overall, Severity::Ok);
+        assert_eq!(report.service_name.as_deref(), Some("Wi-Fi"));
+        assert_eq!(report.device.as_deref(), Some("en0"));
+        for id in [
+            "interface_link",
+            "ip_address",
+            "default_route",
+            "gateway_ping",
+            "dns_resolution",
+            "http_reachability",
+            "captive_portal",
+            "addressing",
+            "proxy_settings",
+            "firewall",
+            "vpn",
+        ] {
+            assert_eq!(severity(&report, id), Severity::Ok, "{}", id);
+        }
+    }
+
+    #[test]
+    fn no_service_stops_the_diagnosis() {
+        let env = FakeEnv {
+            services: Some(Vec::new()),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(report.overall, Severity::Critical);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.service_name, None);
+        let finding = find(&report.findings, "network_services");
+        assert_eq!(finding.summary, "No enabled network service found");
+        assert_eq!(finding.fix_action.as_deref(), Some(FIX_ENABLE_SERVICE));
+
+        let env = FakeEnv {
+            services: None,
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(report.findings.len(), 1);
+        let finding = find(&report.findings, "network_services");
+        assert_eq!(finding.severity, Severity::Critical);
+        assert_eq!(
+            finding.evidence,
+            ["networksetup -listnetworkserviceorder failed"]
+        );
+    }
+
+    #[test]
+    fn disabled_services_are_not_diagnosed() {
+        let env = FakeEnv {
+            services: Some(vec![("Wi-Fi", "en0", false)]),
+            route: None,
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(severity(&report, "network_services"), Severity::Critical);
+
+        // A disabled service that still carries the default route is reported as such
+        let env = FakeEnv {
+            services: Some(vec![("Wi-Fi", "en0", false)]),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        let link = find(&report.findings, "interface_link");
+        assert_eq!(link.severity, Severity::Critical);
+        assert_eq!(link.fix_action.as_deref(), Some(FIX_ENABLE_SERVICE));
+    }
+
+    #[test]
+    fn link_down_skips_everything_below_it() {
+        let env = FakeEnv {
+            link: InterfaceStatus::Disconnected,
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        assert_eq!(report.overall, Severity::Critical);
+        let link = find(&report.findings, "interface_link");
+        assert_eq!(link.severity, Severity::Critical);
+        assert_eq!(link.children.len(), 1);
+        assert_eq!(severity(&report, "ip_address"), Severity::Skipped);
+        assert!(link.children[0].children.is_empty());
+        // The independent checks still run
+        assert_eq!(severity(&report, "proxy_settings"), Severity::Ok);
+        assert_eq!(severity(&report, "vpn"), Severity::Ok);
+        assert_eq!(
+            find(&report.findings, "ip_address").summary,
+            "Skipped because 'Interface link' failed"
+        );
+    }
+
+    #[test]
+    fn missing_gateway_skips_ping_and_dns() {
+        let env = FakeEnv {
+            route: Some(DefaultRoute {
+                gateway: None,
+                interface: "en0".to_string(),
+            }),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        let route = find(&report.findings, "default_route");
+        assert_eq!(route.severity, Severity::Critical);
+        assert_eq!(route.summary, "Default route has no gateway");
+        assert_eq!(route.fix_action.as_deref(), Some(FIX_RENEW_DHCP));
+        assert_eq!(severity(&report, "ip_address"), Severity::Ok);
+        assert_eq!(severity(&report, "gateway_ping"), Severity::Skipped);
+        assert_eq!(severity(&report, "dns_resolution"), Severity::Skipped);
+        assert!(find(&report.findings, "dns_resolution").children.is_empty());
+
+        let env = FakeEnv {
+            route: None,
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(
+            find(&report.findings, "default_route").summary,
+            "There is no default route"
+        );
+    }
+
+    #[test]
+    fn dns_failure_with_working_gateway() {
+        let env = FakeEnv {
+            dns: Err("nodename nor servname provided".to_string()),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        assert_eq!(severity(&report, "gateway_ping"), Severity::Ok);
+        let dns = find(&report.findings, "dns_resolution");
+        assert_eq!(dns.severity, Severity::Critical);
+        assert_eq!(dns.fix_action.as_deref(), Some(FIX_FLUSH_DNS));
+        assert_eq!(dns.evidence, ["nodename nor servname provided"]);
+        assert_eq!(severity(&report, "http_reachability"), Severity::Skipped);
+
+        let env = FakeEnv {
+            dns: Ok(Vec::new()),
+            ..FakeEnv::healthy()
+        };
+        let dns = run_diagnosis(&env);
+        let dns = find(&dns.findings, "dns_resolution");
+        assert_eq!(dns.fix_action.as_deref(), Some(FIX_SET_DNS_SERVERS));
+    }
+
+    #[test]
+    fn unanswered_gateway_ping_does_not_block_dns() {
+        let env = FakeEnv {
+            ping_answers: false,
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        assert_eq!(severity(&report, "gateway_ping"), Severity::Warning);
+        assert_eq!(severity(&report, "dns_resolution"), Severity::Ok);
+        assert_eq!(severity(&report, "http_reachability"), Severity::Ok);
+        assert_eq!(report.overall, Severity::Warning);
+    }
+
+    #[test]
+    fn failing_parent_skips_children() {
+        let env = FakeEnv {
+            ipv4: Some("169.254.12.7"),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        let ip = find(&report.findings, "ip_address");
+        assert_eq!(ip.severity, Severity::Critical);
+        assert_eq!(ip.fix_action.as_deref(), Some(FIX_RENEW_DHCP));
+        assert_eq!(ip.children.len(), 1);
+        let route = &ip.children[0];
+        assert_eq!(route.id, "default_route");
+        assert_eq!(route.severity, Severity::Skipped);
+        // Nothing below a skipped check runs
+        assert!(route.children.is_empty());
+
+        let env = FakeEnv {
+            http: Err("curl: (7) Failed to connect".to_string()),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(severity(&report, "dns_resolution"), Severity::Ok);
+        assert_eq!(severity(&report, "http_reachability"), Severity::Critical);
+        assert_eq!(severity(&report, "captive_portal"), Severity::Skipped);
+    }
+
+    #[test]
+    fn active_proxy_is_a_warning() {
+        let env = FakeEnv {
+            proxy: ProxySettings {
+                https_proxy: ProxyConfig {
+                    enabled: true,
+                    server: "proxy.corp.example".to_string(),
+                    port: 3128,
+                },
+                ..ProxySettings::default()
+            },
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        let proxy = find(&report.findings, "proxy_settings");
+        assert_eq!(proxy.severity, Severity::Warning);
+        assert_eq!(proxy.fix_action.as_deref(), Some(FIX_CLEAR_PROXIES));
+        assert_eq!(proxy.evidence, ["HTTPS proxy: proxy.corp.example:3128"]);
+        assert_eq!(report.overall, Severity::Warning);
+    }
+
+    #[test]
+    fn route_interface_selects_the_service() {
+        let env = FakeEnv {
+            services: Some(vec![("Ethernet", "en1", true), ("Wi-Fi", "en0", true)]),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(report.service_name.as_deref(), Some("Wi-Fi"));
+
+        let env = FakeEnv {
+            services: Some(vec![("Ethernet", "en1", false), ("Wi-Fi", "en0", true)]),
+            route: None,
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(report.service_name.as_deref(), Some("Wi-Fi"));
+    }
+
+    #[test]
+    fn captive_portal_is_critical_with_its_login_page() {
+        let env = FakeEnv {
+            captive: Ok(CaptivePortalResult {
+                probe_url: HTTP_PROBE_URL.to_string(),
+                status: CaptiveStatus::CaptivePortal,
+                http_status: Some(302),
+                login_url: Some("https://portal.example/login".to_string()),
+                detail: "The probe was redirected (HTTP 302) to https://portal.example/login"
+                    .to_string(),
+            }),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        let captive = find(&report.findings, "captive_portal");
+        assert_eq!(captive.severity, Severity::Critical);
+        assert_eq!(captive.fix_action.as_deref(), Some(FIX_OPEN_CAPTIVE_PORTAL));
+        assert_eq!(
+            captive.evidence.last().map(String::as_str),
+            Some("Login page: https://portal.example/login")
+        );
+        assert_eq!(report.overall, Severity::Critical);
+
+        let env = FakeEnv {
+            captive: Err("Failed to execute curl".to_string()),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(severity(&report, "captive_portal"), Severity::Info);
+    }
+
+    #[test]
+    fn pf_rule_blocking_http_is_the_likely_cause() {
+        let pf = PfStatus {
+            enabled: true,
+            anchors: Vec::new(),
+            rules: parse_pf_rules(
+                "pass out all\n\
+                 block drop out quick on en0 proto tcp from any to any port { 80 443 }\n",
+                None,
+            ),
+        };
+        let env = FakeEnv {
+            pf: Some(pf.clone()),
+            http: Err("Connection timed out".to_string()),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        let firewall = find(&report.findings, "firewall");
+        assert_eq!(firewall.severity, Severity::Warning);
+        assert_eq!(
+            firewall.summary,
+            "pf blocks outgoing HTTP, the likely cause of its failure"
+        );
+        assert!(firewall
+            .evidence
+            .iter()
+            .any(|line| line.starts_with("block drop out quick on en0")));
+
+        // Without an HTTP failure the rule is still reported
+        let env = FakeEnv {
+            pf: Some(pf),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(
+            find(&report.findings, "firewall").summary,
+            "pf blocks outgoing HTTP"
+        );
+    }
+
+    #[test]
+    fn firewall_without_restrictions_is_info() {
+        let firewall = ApplicationFirewall {
+            enabled: true,
+            block_all: false,
+            stealth_mode: true,
+            allow_signed_builtin: Some(true),
+            allow_signed_downloaded: Some(true),
+            apps: Vec::new(),
+        };
+        let env = FakeEnv {
+            firewall: Some(firewall.clone()),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        let finding = find(&report.findings, "firewall");
+        assert_eq!(finding.severity, Severity::Info);
+        assert_eq!(
+            finding.evidence[0],
+            "Application firewall on, stealth mode on"
+        );
+
+        let env = FakeEnv {
+            firewall: Some(ApplicationFirewall {
+                block_all: true,
+                ..firewall
+            }),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(severity(&report, "firewall"), Severity::Warning);
+    }
+
+    #[test]
+    fn local_proxy_that_is_not_listening_is_critical() {
+        let local = ProxySettings {
+            http_proxy: ProxyConfig {
+                enabled: true,
+                server: "127.0.0.1".to_string(),
+                port: 7890,
+            },
+            https_proxy: ProxyConfig {
+                enabled: true,
+                server: "127.0.0.1".to_string(),
+                port: 7890,
+            },
+            ..ProxySettings::default()
+        };
+        // Only an IPv6 socket, which 127.0.0.1 does not reach
+        let env = FakeEnv {
+            proxy: local.clone(),
+            listeners: vec![Listener {
+                process: Some("ClashX".to_string()),
+                pid: Some(1234),
+                user: Some("me".to_string()),
+                family: ListenerFamily::Ipv6,
+                address: "*".to_string(),
+                port: 7890,
+            }],
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        let proxy = find(&report.findings, "proxy_settings");
+        assert_eq!(proxy.severity, Severity::Critical);
+        assert_eq!(
+            proxy.summary,
+            "The local HTTP and HTTPS proxies are not running, so proxied traffic fails"
+        );
+        assert_eq!(proxy.fix_action.as_deref(), Some(FIX_CLEAR_PROXIES));
+        assert!(proxy
+            .evidence
+            .contains(&"Nothing listens on 127.0.0.1:7890".to_string()));
+        assert_eq!(report.overall, Severity::Critical);
+
+        let env = FakeEnv {
+            proxy: local,
+            listeners: vec![Listener {
+                process: Some("ClashX".to_string()),
+                pid: Some(1234),
+                user: Some("me".to_string()),
+                family: ListenerFamily::Ipv4,
+                address: "127.0.0.1".to_string(),
+                port: 7890,
+            }],
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        let proxy = find(&report.findings, "proxy_settings");
+        assert_eq!(proxy.severity, Severity::Warning);
+        assert!(proxy
+            .evidence
+            .contains(&"127.0.0.1:7890 is served by ClashX (pid 1234)".to_string()));
+    }
+
+    fn manual(ip: Option<&str>, mask: Option<&str>, router: Option<&str>) -> Option<IpConfig> {
+        let healthy = FakeEnv::healthy().ip_config?;
+        Some(IpConfig {
+            mode: IpMode::Manual,
+            ip_address: ip.map(str::to_string),
+            subnet_mask: mask.map(str::to_string),
+            router: router.map(str::to_string),
+            ..healthy
+        })
+    }
+
+    fn addressing(env: &FakeEnv) -> Finding {
+        find(&run_diagnosis(env).findings, "addressing").clone()
+    }
+
+    #[test]
+    fn self_assigned_address_means_dhcp_failed() {
+        let env = FakeEnv {
+            ipv4: Some("169.254.12.34"),
+            lease: None,
+            ..FakeEnv::healthy()
+        };
+        let finding = addressing(&env);
+        assert_eq!(finding.severity, Severity::Critical);
+        assert_eq!(
+            finding.summary,
+            "Self-assigned address 169.254.12.34, DHCP server did not answer"
+        );
+        assert_eq!(finding.fix_action.as_deref(), Some(FIX_RENEW_DHCP));
+        assert!(finding
+            .evidence
+            .contains(&"Addresses: 169.254.12.34".to_string()));
+
+        // Also detected when the IP configuration cannot be read
+        let env = FakeEnv {
+            ip_config: None,
+            ..env
+        };
+        let finding = addressing(&env);
+        assert_eq!(finding.severity, Severity::Critical);
+        assert_eq!(finding.fix_action.as_deref(), Some(FIX_RENEW_DHCP));
+    }
+
+    #[test]
+    fn dhcp_lease_problems() {
+        let env = FakeEnv {
+            lease: None,
+            ..FakeEnv::healthy()
+        };
+        let finding = addressing(&env);
+        assert_eq!(finding.severity, Severity::Critical);
+        assert_eq!(finding.summary, "No DHCP lease");
+
+        let mut lease = FakeEnv::healthy().lease.unwrap();
+        lease.routers.clear();
+        let env = FakeEnv {
+            lease: Some(lease),
+            ..FakeEnv::healthy()
+        };
+        let finding = addressing(&env);
+        assert_eq!(finding.severity, Severity::Warning);
+        assert_eq!(finding.summary, "DHCP lease provides no router");
+        assert_eq!(finding.fix_action.as_deref(), Some(FIX_RENEW_DHCP));
+    }
+
+    #[test]
+    fn incomplete_manual_configuration_names_what_is_missing() {
+        for (ip, mask, router, summary) in [
+            (
+                Some("192.168.1.20"),
+                Some("255.255.255.0"),
+                None,
+                "Manual configuration has no router",
+            ),
+            (
+                None,
+                Some("255.255.255.0"),
+                Some("192.168.1.1"),
+                "Manual configuration has no IP address",
+            ),
+            (
+                None,
+                None,
+                Some("192.168.1.1"),
+                "Manual configuration has no IP address and subnet mask",
+            ),
+            (
+                None,
+                None,
+                None,
+                "Manual configuration has no IP address, subnet mask and router",
+            ),
+        ] {
+            let env = FakeEnv {
+                ip_config: manual(ip, mask, router),
+                ..FakeEnv::healthy()
+            };
+            let finding = addressing(&env);
+            assert_eq!(finding.severity, Severity::Critical);
+            assert_eq!(finding.summary, summary);
+            assert_eq!(finding.fix_action.as_deref(), Some(FIX_SET_DHCP));
+        }
+    }
+
+    #[test]
+    fn manual_configuration_problems() {
+        let env = FakeEnv {
+            ip_config: manual(
+                Some("192.168.1.20"),
+                Some("255.255.255.0"),
+                Some("10.0.0.1"),
+            ),
+            ..FakeEnv::healthy()
+        };
+        let finding = addressing(&env);
+        assert_eq!(finding.severity, Severity::Critical);
+        assert!(finding
+            .summary
+            .starts_with("Manual configuration is inconsistent: "));
+
+        let env = FakeEnv {
+            ip_config: manual(
+                Some("192.168.1.20"),
+                Some("255.255.255.0"),
+                Some("192.168.1.1"),
+            ),
+            neighbors: vec![NeighborEntry {
+                protocol: NeighborProtocol::Arp,
+                ip_address: "192.168.1.20".to_string(),
+                mac_address: Some("00:11:22:33:44:55".to_string()),
+                interface: Some("en0".to_string()),
+                expire: None,
+                permanent: false,
+                state: None,
+            }],
+            ..FakeEnv::healthy()
+        };
+        let finding = addressing(&env);
+        assert_eq!(finding.severity, Severity::Critical);
+        assert_eq!(
+            finding.summary,
+            "IP address 192.168.1.20 is also used by 00:11:22:33:44:55"
+        );
+        assert_eq!(finding.fix_action.as_deref(), Some(FIX_SET_DHCP));
+
+        // macOS falls back to a self-assigned address when the manual one conflicts
+        let env = FakeEnv {
+            ipv4: Some("169.254.7.7"),
+            ip_config: manual(
+                Some("192.168.1.20"),
+                Some("255.255.255.0"),
+                Some("192.168.1.1"),
+            ),
+            ..FakeEnv::healthy()
+        };
+        assert_eq!(
+            addressing(&env).summary,
+            "Manual address 192.168.1.20 was replaced by a self-assigned address, \
+             likely an IP conflict"
+        );
+
+        let env = FakeEnv {
+            ip_config: manual(
+                Some("192.168.1.20"),
+                Some("255.255.255.0"),
+                Some("192.168.1.1"),
+            ),
+            ..FakeEnv::healthy()
+        };
+        let finding = addressing(&env);
+        assert_eq!(finding.severity, Severity::Ok);
+        assert_eq!(finding.summary, "Manual configuration is consistent");
+    }
+
+    fn vpn(status: VpnStatus, default_route: bool, dns: bool) -> Option<VpnState> {
+        let connected = status == VpnStatus::Connected;
+        Some(VpnState {
+            services: vec![VpnService {
+                id: "6C1E4B7A-0F3D-4C55-9B1E-2D8A7F0C9E11".to_string(),
+                name: "Corp VPN".to_string(),
+                vpn_type: "IPSec".to_string(),
+                subtype: None,
+                enabled: true,
+                status,
+                takes_default_route: connected && default_route,
+                injects_dns: connected && dns,
+            }],
+            tunnels: if connected {
+                vec![TunnelInterface {
+                    name: "utun4".to_string(),
+                    addresses: vec!["10.8.0.2".to_string()],
+                    takes_default_route: default_route,
+                    dns_servers: if dns {
+                        vec!["10.8.0.1".to_string()]
+                    } else {
+                        Vec::new()
+                    },
+                    dns_domains: Vec::new(),
+                }]
+            } else {
+                Vec::new()
+            },
+            default_route_via_tunnel: connected && default_route,
+            dns_via_tunnel: connected && dns,
+        })
+    }
+
+    #[test]
+    fn vpn_dns_is_blamed_for_dns_failures() {
+        let env = FakeEnv {
+            vpn: vpn(VpnStatus::Connected, false, true),
+            dns: Err("nodename nor servname provided".to_string()),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+
+        let finding = find(&report.findings, "vpn");
+        assert_eq!(finding.severity, Severity::Warning);
+        assert_eq!(
+            finding.summary,
+            "Corp VPN is active and the likely cause of the DNS problems"
+        );
+        assert_eq!(finding.fix_action.as_deref(), Some(FIX_STOP_VPN));
+        assert_eq!(finding.evidence, ["utun4: 10.8.0.2 (DNS 10.8.0.1)"]);
+    }
+
+    #[test]
+    fn full_tunnel_is_blamed_for_http_failures() {
+        let env = FakeEnv {
+            vpn: vpn(VpnStatus::Connected, true, true),
+            http: Err("Connection timed out".to_string()),
+            ..FakeEnv::healthy()
+        };
+        let finding = find(&run_diagnosis(&env).findings, "vpn").clone();
+        assert_eq!(
+            finding.summary,
+            "Corp VPN is active and the likely cause of the DNS and routing problems"
+        );
+        assert_eq!(
+            finding.evidence,
+            ["utun4: 10.8.0.2 (default route, DNS 10.8.0.1)"]
+        );
+    }
+
+    #[test]
+    fn active_vpn_without_problems_is_info() {
+        let env = FakeEnv {
+            vpn: vpn(VpnStatus::Connected, true, true),
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        let finding = find(&report.findings, "vpn");
+        assert_eq!(finding.severity, Severity::Info);
+        assert_eq!(finding.summary, "Corp VPN is active");
+        assert_eq!(finding.fix_action, None);
+
+        // A VPN that only routes its own networks is not blamed for DNS failures
+        let env = FakeEnv {
+            vpn: vpn(VpnStatus::Connected, false, false),
+            dns: Err("nodename nor servname provided".to_string()),
+            ..FakeEnv::healthy()
+        };
+        let finding = find(&run_diagnosis(&env).findings, "vpn").clone();
+        assert_eq!(finding.severity, Severity::Info);
+        assert_eq!(finding.evidence, ["utun4: 10.8.0.2 (split tunnel)"]);
+
+        let env = FakeEnv {
+            vpn: vpn(VpnStatus::Disconnected, true, true),
+            ..FakeEnv::healthy()
+        };
+        let finding = find(&run_diagnosis(&env).findings, "vpn").clone();
+        assert_eq!(finding.severity, Severity::Ok);
+        assert_eq!(finding.summary, "No VPN is active");
+    }
+
+    #[test]
+    fn working_proxy_does_not_implicate_the_vpn() {
+        let corporate = ProxySettings {
+            https_proxy: ProxyConfig {
+                enabled: true,
+                server: "proxy.corp.example".to_string(),
+                port: 3128,
+            },
+            ..ProxySettings::default()
+        };
+        let env = FakeEnv {
+            vpn: vpn(VpnStatus::Connected, true, true),
+            proxy: corporate,
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(severity(&report, "proxy_settings"), Severity::Warning);
+        let finding = find(&report.findings, "vpn");
+        assert_eq!(finding.severity, Severity::Info);
+        assert_eq!(finding.fix_action, None);
+
+        // A local proxy that is not running is blamed on the VPN
+        let local = ProxySettings {
+            http_proxy: ProxyConfig {
+                enabled: true,
+                server: "127.0.0.1".to_string(),
+                port: 7890,
+            },
+            ..ProxySettings::default()
+        };
+        let env = FakeEnv {
+            vpn: vpn(VpnStatus::Connected, true, true),
+            proxy: local,
+            ..FakeEnv::healthy()
+        };
+        let report = run_diagnosis(&env);
+        assert_eq!(severity(&report, "proxy_settings"), Severity::Critical);
+        let finding = find(&report.findings, "vpn");
+        assert_eq!(finding.severity, Severity::Warning);
+        assert_eq!(
+            finding.summary,
+            "Corp VPN is active and the likely cause of the proxy problems"
+        );
+        assert_eq!(finding.fix_action.as_deref(), Some(FIX_STOP_VPN));
+    }
+}
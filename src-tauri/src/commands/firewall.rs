@@ -0,0 +1,906 @@
+use serde::{Deserialize, Serialize};
+
+use super::network::run_with_admin_privileges;
+use super::runner::{system_runner, CommandRunner};
+
+const SOCKETFILTERFW: &str = "/usr/libexec/ApplicationFirewall/socketfilterfw";
+
+/// Prints `pfctl -s info`, the anchor list, the main ruleset and the rules of every
+/// anchor, separated by "== " marker lines. Without root only the markers appear.
+const PF_REPORT_SCRIPT: &str = "pfctl -s info 2>/dev/null; \
+    echo '== anchors'; pfctl -vsA 2>/dev/null; \
+    echo '== rules'; pfctl -s rules 2>/dev/null; \
+    for a in $(pfctl -vsA 2>/dev/null); do \
+    echo \"== anchor $a\"; pfctl -a \"$a\" -s rules 2>/dev/null; done; true";
+
+/// Nested anchors deeper than this are not evaluated
+const MAX_ANCHOR_DEPTH: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppFirewallRule {
+    pub path: String,
+    pub allow_incoming: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplicationFirewall {
+    pub enabled: bool,
+    /// "Block all incoming connections", which also stops sharing services
+    pub block_all: bool,
+    pub stealth_mode: bool,
+    pub allow_signed_builtin: Option<bool>,
+    pub allow_signed_downloaded: Option<bool>,
+    pub apps: Vec<AppFirewallRule>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PfAction {
+    Pass,
+    Block,
+    /// Evaluates the rules of another anchor at this point
+    Anchor,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PfDirection {
+    In,
+    Out,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PfRule {
+    /// Anchor the rule is loaded in, `None` for the main ruleset
+    pub ruleset: Option<String>,
+    pub action: PfAction,
+    /// `None` matches both directions
+    pub direction: Option<PfDirection>,
+    pub quick: bool,
+    pub interface: Option<String>,
+    pub family: Option<String>,
+    pub protocol: Option<String>,
+    pub from: Option<String>,
+    pub from_port: Option<String>,
+    pub to: Option<String>,
+    pub to_port: Option<String>,
+    /// Target of an `anchor` rule, e.g. "com.apple/*"
+    pub anchor: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PfStatus {
+    pub enabled: bool,
+    pub anchors: Vec<String>,
+    pub rules: Vec<PfRule>,
+}
+
+/// Traffic the diagnosis sends, matched against the pf rules
+#[derive(Debug, Clone)]
+pub struct TrafficProbe {
+    pub direction: PfDirection,
+    pub protocol: String,
+    pub port: Option<u16>,
+    pub interface: Option<String>,
+}
+
+/// "Stealth mode enabled", "Firewall stealth mode is on", "Block all DISABLED!"
+fn parse_switch(output: &str) -> Option<bool> {
+    let lower = output.to_ascii_lowercase();
+    if lower.contains("disabled") || lower.contains(" is off") {
+        Some(false)
+    } else if lower.contains("enabled") || lower.contains(" is on") {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Parse `socketfilterfw --getglobalstate`, e.g. "Firewall is enabled. (State = 1)".
+/// Returns whether the firewall is on and whether it blocks everything (state 2).
+pub fn parse_global_state(output: &str) -> Option<(bool, bool)> {
+    let state = output
+        .split_once("State =")
+        .and_then(|(_, rest)| rest.trim().trim_end_matches(')').trim().parse::<u8>().ok());
+
+    match state {
+        Some(state) => Some((state > 0, state == 2)),
+        None => parse_switch(output).map(|enabled| (enabled, false)),
+    }
+}
+
+/// Parse `socketfilterfw --getblockall`
+pub fn parse_block_all(output: &str) -> bool {
+    let lower = output.to_ascii_lowercase();
+    match parse_switch(output) {
+        Some(enabled) => enabled,
+        None => lower.contains("block all") || lower.contains("blocking all"),
+    }
+}
+
+/// Parse `socketfilterfw --getallowsigned` into (built-in, downloaded)
+pub fn parse_allow_signed(output: &str) -> (Option<bool>, Option<bool>) {
+    let find = |needle: &str| {
+        output
+            .lines()
+            .find(|line| line.contains(needle))
+            .and_then(parse_switch)
+    };
+
+    (find("built-in"), find("downloaded"))
+}
+
+/// Parse `socketfilterfw --listapps`:
+///
+/// ```text
+/// 1 :  /Applications/Dropbox.app
+///      ( Allow incoming connections )
+/// ```
+pub fn parse_listapps(output: &str) -> Vec<AppFirewallRule> {
+    let mut apps: Vec<AppFirewallRule> = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some((index, path)) = line.split_once(" : ") {
+            if index.trim().parse::<u32>().is_ok() {
+                apps.push(AppFirewallRule {
+                    path: path.trim().to_string(),
+                    allow_incoming: true,
+                });
+                continue;
+            }
+        }
+        if let Some(app) = apps.last_mut() {
+            if line.contains("Block incoming") {
+                app.allow_incoming = false;
+            }
+        }
+    }
+
+    apps
+}
+
+/// Parse `pfctl -s info`, `None` when pf could not be queried
+pub fn parse_pf_info(output: &str) -> Option<bool> {
+    output.lines().find_map(|line| {
+        let status = line.trim().strip_prefix("Status:")?.trim();
+        Some(status.starts_with("Enabled"))
+    })
+}
+
+/// Parse one line of `pfctl -s rules`. Only pass, block and anchor rules are
+/// returned; scrub, nat and rdr rules do not filter traffic.
+pub fn parse_pf_rule(line: &str, ruleset: Option<&str>) -> Option<PfRule> {
+    // Lists may be written "{ tcp udp }", "{tcp,udp}" or "{ 80, 443 }"
+    let spaced = line
+        .replace('{', " { ")
+        .replace('}', " } ")
+        .replace(',', " ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut rule = PfRule {
+        ruleset: ruleset.map(|s| s.to_string()),
+        action: match *tokens.first()? {
+            "pass" => PfAction::Pass,
+            "block" => PfAction::Block,
+            "anchor" => PfAction::Anchor,
+            _ => return None,
+        },
+        direction: None,
+        quick: false,
+        interface: None,
+        family: None,
+        protocol: None,
+        from: None,
+        from_port: None,
+        to: None,
+        to_port: None,
+        anchor: None,
+        text: line.trim().to_string(),
+    };
+
+    // "{ tcp udp }" is kept as one operand, normalized to single spaces
+    let list = |i: &mut usize| -> Option<String> {
+        let end = *i + tokens[*i..].iter().position(|t| *t == "}")?;
+        let items = tokens[*i + 1..end].join(" ");
+        *i = end;
+        Some(format!("{{ {} }}", items))
+    };
+    // Address or port operands may be negated: "! 10.0.0.0/8"
+    let operand = |i: &mut usize| -> Option<String> {
+        *i += 1;
+        let token = *tokens.get(*i)?;
+        if token == "{" {
+            return list(i);
+        }
+        if token == "!" {
+            *i += 1;
+            return tokens.get(*i).map(|next| format!("! {}", next));
+        }
+        Some(token.to_string())
+    };
+    // "= 80", "!= 80", ">= 1024", "1000:2000", "1000 >< 2000" or "{ 80 443 }"
+    let port = |i: &mut usize| -> Option<String> {
+        *i += 1;
+        let first = *tokens.get(*i)?;
+        if first == "{" {
+            return list(i);
+        }
+        if ["=", "!=", "<", "<=", ">", ">="].contains(&first) {
+            *i += 1;
+            return tokens.get(*i).map(|n| format!("{} {}", first, n));
+        }
+        match tokens.get(*i + 1) {
+            Some(op) if *op == "><" || *op == "<>" => {
+                let last = tokens.get(*i + 2)?;
+                *i += 2;
+                Some(format!("{} {} {}", first, op, last))
+            }
+            _ => Some(first.to_string()),
+        }
+    };
+
+    let mut last_address = "";
+    let mut i = 1;
+    while i < tokens.len() {
+        match tokens[i] {
+            name if rule.action == PfAction::Anchor && i == 1 && name.starts_with('"') => {
+                rule.anchor = Some(name.trim_matches('"').to_string());
+            }
+            "in" => rule.direction = Some(PfDirection::In),
+            "out" => rule.direction = Some(PfDirection::Out),
+            "quick" => rule.quick = true,
+            "on" => rule.interface = operand(&mut i),
+            "inet" | "inet6" => rule.family = Some(tokens[i].to_string()),
+            "proto" => rule.protocol = operand(&mut i),
+            "all" => {
+                rule.from = Some("any".to_string());
+                rule.to = Some("any".to_string());
+            }
+            "from" => {
+                rule.from = operand(&mut i);
+                last_address = "from";
+            }
+            "to" => {
+                rule.to = operand(&mut i);
+                last_address = "to";
+            }
+            "port" if last_address == "from" => rule.from_port = port(&mut i),
+            "port" => rule.to_port = port(&mut i),
+            // Everything after the match criteria, e.g. "flags S/SA keep state"
+            "flags" | "keep" | "modulate" | "synproxy" | "label" | "tag" => break,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    Some(rule)
+}
+
+pub fn parse_pf_rules(output: &str, ruleset: Option<&str>) -> Vec<PfRule> {
+    output
+        .lines()
+        .filter_map(|line| parse_pf_rule(line, ruleset))
+        .collect()
+}
+
+/// Parse the output of `PF_REPORT_SCRIPT`
+pub fn parse_pf_report(output: &str) -> Option<PfStatus> {
+    let mut info = String::new();
+    let mut anchors = Vec::new();
+    let mut rules = Vec::new();
+    let mut section = "info";
+    let mut ruleset: Option<&str> = None;
+
+    for line in output.lines() {
+        if let Some(marker) = line.strip_prefix("== ") {
+            match marker.strip_prefix("anchor ") {
+                Some(name) => {
+                    section = "rules";
+                    ruleset = Some(name.trim());
+                }
+                None => {
+                    section = marker.trim();
+                    ruleset = None;
+                }
+            }
+            continue;
+        }
+
+        match section {
+            "info" => {
+                info.push_str(line);
+                info.push('\n');
+            }
+            "anchors" if !line.trim().is_empty() => anchors.push(line.trim().to_string()),
+            "rules" => rules.extend(parse_pf_rule(line, ruleset)),
+            _ => {}
+        }
+    }
+
+    Some(PfStatus {
+        enabled: parse_pf_info(&info)?,
+        anchors,
+        rules,
+    })
+}
+
+/// The items of a "{ 80 443 }" operand, `None` for a single value. An operator
+/// stays with the number that follows it: "{ = 80 > 1024 }".
+fn list_items(spec: &str) -> Option<Vec<String>> {
+    let inner = spec.strip_prefix('{')?.strip_suffix('}')?;
+    let mut items = Vec::new();
+    let mut operator: Option<&str> = None;
+
+    for token in inner.split_whitespace() {
+        if ["=", "!=", "<", "<=", ">", ">="].contains(&token) {
+            operator = Some(token);
+            continue;
+        }
+        items.push(match operator.take() {
+            Some(op) => format!("{} {}", op, token),
+            None => token.to_string(),
+        });
+    }
+    Some(items)
+}
+
+/// Whether an operand like "tcp" or "{ tcp udp }" includes `value`
+fn operand_matches(spec: &str, value: &str) -> bool {
+    match list_items(spec) {
+        Some(items) => items.iter().any(|item| item == value),
+        None => spec == value,
+    }
+}
+
+/// Whether a port operand like "= 80", "1000:2000" or "{ 80 443 }" includes `port`
+fn port_matches(spec: &str, port: u16) -> bool {
+    if let Some(items) = list_items(spec) {
+        return items.iter().any(|item| port_matches(item, port));
+    }
+    let parts: Vec<&str> = spec.split_whitespace().collect();
+    let number = |s: &str| s.parse::<u16>().ok();
+
+    match parts.as_slice() {
+        [op, n] => match (*op, number(n)) {
+            ("=", Some(n)) => port == n,
+            ("!=", Some(n)) => port != n,
+            ("<", Some(n)) => port < n,
+            ("<=", Some(n)) => port <= n,
+            (">", Some(n)) => port > n,
+            (">=", Some(n)) => port >= n,
+            _ => false,
+        },
+        [low, "><", high] => {
+            matches!((number(low), number(high)), (Some(l), Some(h)) if l < port && port < h)
+        }
+        [low, "<>", high] => {
+            matches!((number(low), number(high)), (Some(l), Some(h)) if port < l || port > h)
+        }
+        [single] => match single.split_once(':') {
+            Some((low, high)) => {
+                matches!((number(low), number(high)), (Some(l), Some(h)) if l <= port && port <= h)
+            }
+            None => number(single) == Some(port),
+        },
+        _ => false,
+    }
+}
+
+/// Whether `rule` applies to the probe. Specific addresses and negations are
+/// treated as not matching, since the probe's remote address is not known.
+fn rule_matches(rule: &PfRule, probe: &TrafficProbe) -> bool {
+    let any = |address: &Option<String>| matches!(address.as_deref(), None | Some("any"));
+    let direction = match rule.direction {
+        Some(direction) => direction == probe.direction,
+        None => true,
+    };
+    let interface = match (&rule.interface, &probe.interface) {
+        (None, _) => true,
+        (Some(rule_if), Some(probe_if)) => operand_matches(rule_if, probe_if),
+        (Some(_), None) => false,
+    };
+    let protocol = match &rule.protocol {
+        Some(protocol) => operand_matches(protocol, &probe.protocol),
+        None => true,
+    };
+    let port = match (&rule.to_port, probe.port) {
+        (None, _) => true,
+        (Some(spec), Some(port)) => port_matches(spec, port),
+        (Some(_), None) => false,
+    };
+
+    direction
+        && interface
+        && protocol
+        && port
+        && rule.family.as_deref() != Some("inet6")
+        && any(&rule.from)
+        && any(&rule.to)
+        && rule.from_port.is_none()
+}
+
+/// Anchors an `anchor` rule evaluates. "com.apple/*" means every anchor directly
+/// below com.apple, in the alphabetical order pf uses.
+fn resolve_anchor<'a>(status: &'a PfStatus, parent: Option<&str>, target: &str) -> Vec<&'a str> {
+    let full = |name: &str| match parent {
+        Some(parent) => format!("{}/{}", parent, name),
+        None => name.to_string(),
+    };
+
+    let mut names: Vec<&str> = match target.strip_suffix("/*") {
+        Some(base) => {
+            let base = full(base);
+            status
+                .anchors
+                .iter()
+                .filter(|name| {
+                    name.strip_prefix(base.as_str())
+                        .and_then(|rest| rest.strip_prefix('/'))
+                        .is_some_and(|child| !child.is_empty() && !child.contains('/'))
+                })
+                .map(|name| name.as_str())
+                .collect()
+        }
+        None => {
+            let name = full(target);
+            status
+                .anchors
+                .iter()
+                .filter(|anchor| **anchor == name)
+                .map(|anchor| anchor.as_str())
+                .collect()
+        }
+    };
+    names.sort_unstable();
+    names
+}
+
+/// Walk a ruleset the way pf does: the last matching rule wins unless a `quick`
+/// rule matches first. Returns the deciding rule and whether it was quick.
+fn evaluate<'a>(
+    status: &'a PfStatus,
+    ruleset: Option<&str>,
+    probe: &TrafficProbe,
+    depth: usize,
+) -> Option<(&'a PfRule, bool)> {
+    let mut decision = None;
+
+    for rule in status
+        .rules
+        .iter()
+        .filter(|r| r.ruleset.as_deref() == ruleset)
+    {
+        if rule.action == PfAction::Anchor {
+            if depth >= MAX_ANCHOR_DEPTH || !rule_matches(rule, probe) {
+                continue;
+            }
+            let target = rule.anchor.as_deref().unwrap_or_default();
+            for anchor in resolve_anchor(status, ruleset, target) {
+                match evaluate(status, Some(anchor), probe, depth + 1) {
+                    Some((rule, true)) => return Some((rule, true)),
+                    Some(found) => decision = Some(found),
+                    None => {}
+                }
+            }
+            continue;
+        }
+
+        if rule_matches(rule, probe) {
+            if rule.quick {
+                return Some((rule, true));
+            }
+            decision = Some((rule, false));
+        }
+    }
+
+    decision
+}
+
+/// The rule that blocks the probe, if pf is enabled and would drop it
+pub fn pf_blocking_rule<'a>(status: &'a PfStatus, probe: &TrafficProbe) -> Option<&'a PfRule> {
+    if !status.enabled {
+        return None;
+    }
+
+    evaluate(status, None, probe, 0)
+        .map(|(rule, _)| rule)
+        .filter(|rule| rule.action == PfAction::Block)
+}
+
+pub(crate) fn read_application_firewall(
+    runner: &dyn CommandRunner,
+) -> Result<ApplicationFirewall, String> {
+    let run = |flag: &str| runner.run(SOCKETFILTERFW, &[flag]);
+
+    let (enabled, blocks_everything) = parse_global_state(&run("--getglobalstate")?)
+        .ok_or("Unexpected output from socketfilterfw")?;
+    let block_all = blocks_everything || parse_block_all(&run("--getblockall")?);
+    let stealth_mode = parse_switch(&run("--getstealthmode")?).unwrap_or(false);
+    let (allow_signed_builtin, allow_signed_downloaded) =
+        parse_allow_signed(&run("--getallowsigned").unwrap_or_default());
+    let apps = parse_listapps(&run("--listapps").unwrap_or_default());
+
+    Ok(ApplicationFirewall {
+        enabled,
+        block_all,
+        stealth_mode,
+        allow_signed_builtin,
+        allow_signed_downloaded,
+        apps,
+    })
+}
+
+/// Read pf without privileges; `None` unless the app runs as root
+pub(crate) fn read_packet_filter(runner: &dyn CommandRunner) -> Option<PfStatus> {
+    let output = runner.run("sh", &["-c", PF_REPORT_SCRIPT]).ok()?;
+
+    parse_pf_report(&output)
+}
+
+/// Application firewall state, stealth mode and per-app rules
+#[tauri::command]
+pub fn get_firewall_status() -> Result<ApplicationFirewall, String> {
+    read_application_firewall(system_runner())
+}
+
+/// pf state, anchors and rules. pfctl needs root, so this asks for the password.
+#[tauri::command]
+pub fn get_packet_filter_status() -> Result<PfStatus, String> {
+    let output = run_with_admin_privileges(PF_REPORT_SCRIPT)?;
+
+    parse_pf_report(&output).ok_or_else(|| "Unexpected output from pfctl".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTAPPS: &str = "\
+ALF: total number of apps = 2
+
+1 :  /Applications/Dropbox.app
+ \t ( Allow incoming connections )
+
+2 :  /usr/local/bin/node
+ \t ( Block incoming connections )
+";
+
+    const PF_REPORT: &str = "\
+Status: Enabled for 0 days 02:11:45           Debug: Urgent
+
+State Table                          Total             Rate
+  current entries                        4
+== anchors
+  com.apple
+  com.apple/250.ApplicationFirewall
+  com.apple/200.AirDrop
+  com.apple/200.AirDrop/Bonjour
+  corp
+== rules
+scrub-anchor \"com.apple/*\" all fragment reassemble
+anchor \"com.apple/*\" all
+anchor \"corp\" all
+pass out quick proto udp from any to any port = 53 keep state
+== anchor com.apple/250.ApplicationFirewall
+== anchor com.apple/200.AirDrop
+anchor \"Bonjour\" all
+== anchor com.apple/200.AirDrop/Bonjour
+pass in quick on awdl0 inet6 proto udp from any to any port = 5353 keep state
+== anchor corp
+block drop out proto tcp from any to any port { 80 443 }
+pass out proto tcp from any to any port = 443 flags S/SA keep state
+";
+
+    fn probe(protocol: &str, port: Option<u16>) -> TrafficProbe {
+        TrafficProbe {
+            direction: PfDirection::Out,
+            protocol: protocol.to_string(),
+            port,
+            interface: Some("en0".to_string()),
+        }
+    }
+
+    fn status(rules: &str, anchors: &[&str]) -> PfStatus {
+        let mut parsed = Vec::new();
+        let mut ruleset = None;
+        for line in rules.lines() {
+            match line.strip_prefix("== anchor ") {
+                Some(name) => ruleset = Some(name),
+                None => parsed.extend(parse_pf_rule(line, ruleset)),
+            }
+        }
+        PfStatus {
+            enabled: true,
+            anchors: anchors.iter().map(|a| a.to_string()).collect(),
+            rules: parsed,
+        }
+    }
+
+    fn blocking(status: &PfStatus, protocol: &str, port: Option<u16>) -> Option<String> {
+        pf_blocking_rule(status, &probe(protocol, port)).map(|rule| rule.text.clone())
+    }
+
+    #[test]
+    fn parses_socketfilterfw_global_state() {
+        let parse = parse_global_state;
+        assert_eq!(
+            parse("Firewall is disabled. (State = 0)"),
+            Some((false, false))
+        );
+        assert_eq!(
+            parse("Firewall is enabled. (State = 1)"),
+            Some((true, false))
+        );
+        assert_eq!(
+            parse("Firewall is blocking all non-essential incoming connections. (State = 2)"),
+            Some((true, true))
+        );
+        assert_eq!(parse("Firewall is enabled."), Some((true, false)));
+        assert_eq!(parse("Firewall is off"), Some((false, false)));
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn parses_socketfilterfw_switches() {
+        assert!(parse_block_all("Block all ENABLED!"));
+        assert!(!parse_block_all("Block all DISABLED!"));
+        assert!(parse_block_all(
+            "Firewall is set to block all non-essential incoming connections"
+        ));
+        assert!(!parse_block_all(
+            "Firewall has block all state set to disabled."
+        ));
+        assert_eq!(parse_switch("Stealth mode enabled"), Some(true));
+        assert_eq!(parse_switch("Firewall stealth mode is off"), Some(false));
+        assert_eq!(parse_switch("Unknown option"), None);
+
+        let output = "Automatically allow built-in signed software ENABLED.\n\
+                      Automatically allow downloaded signed software DISABLED.\n";
+        assert_eq!(parse_allow_signed(output), (Some(true), Some(false)));
+        assert_eq!(parse_allow_signed(""), (None, None));
+    }
+
+    #[test]
+    fn parses_socketfilterfw_app_list() {
+        let apps = parse_listapps(LISTAPPS);
+        assert_eq!(apps.len(), 2);
+        assert_eq!(apps[0].path, "/Applications/Dropbox.app");
+        assert!(apps[0].allow_incoming);
+        assert_eq!(apps[1].path, "/usr/local/bin/node");
+        assert!(!apps[1].allow_incoming);
+        assert!(parse_listapps("ALF: total number of apps = 0\n").is_empty());
+    }
+
+    #[test]
+    fn parses_pf_info() {
+        assert_eq!(
+            parse_pf_info("Status: Enabled for 0 days 02:11:45           Debug: Urgent\n"),
+            Some(true)
+        );
+        assert_eq!(
+            parse_pf_info("Status: Disabled                              Debug: Urgent\n"),
+            Some(false)
+        );
+        assert_eq!(parse_pf_info("pfctl: /dev/pf: Permission denied\n"), None);
+    }
+
+    #[test]
+    fn parses_pf_rules() {
+        let rule = parse_pf_rule(
+            "block drop in quick on en0 inet proto tcp from ! 10.0.0.0/8 port >= 1024 \
+             to any port 1000 >< 2000 flags S/SA",
+            Some("corp"),
+        )
+        .unwrap();
+        assert_eq!(rule.ruleset.as_deref(), Some("corp"));
+        assert_eq!(rule.action, PfAction::Block);
+        assert_eq!(rule.direction, Some(PfDirection::In));
+        assert!(rule.quick);
+        assert_eq!(rule.interface.as_deref(), Some("en0"));
+        assert_eq!(rule.family.as_deref(), Some("inet"));
+        assert_eq!(rule.protocol.as_deref(), Some("tcp"));
+        assert_eq!(rule.from.as_deref(), Some("! 10.0.0.0/8"));
+        assert_eq!(rule.from_port.as_deref(), Some(">= 1024"));
+        assert_eq!(rule.to.as_deref(), Some("any"));
+        assert_eq!(rule.to_port.as_deref(), Some("1000 >< 2000"));
+
+        let rule = parse_pf_rule("pass out all flags S/SA keep state", None).unwrap();
+        assert_eq!(rule.action, PfAction::Pass);
+        assert_eq!(rule.from.as_deref(), Some("any"));
+        assert_eq!(rule.to.as_deref(), Some("any"));
+        assert!(!rule.quick);
+
+        let rule = parse_pf_rule("anchor \"com.apple/*\" all", None).unwrap();
+        assert_eq!(rule.action, PfAction::Anchor);
+        assert_eq!(rule.anchor.as_deref(), Some("com.apple/*"));
+
+        assert!(
+            parse_pf_rule("scrub-anchor \"com.apple/*\" all fragment reassemble", None).is_none()
+        );
+        assert!(parse_pf_rule("nat-anchor \"com.apple/*\" all", None).is_none());
+        assert!(parse_pf_rule("", None).is_none());
+    }
+
+    #[test]
+    fn parses_brace_lists() {
+        let rule = parse_pf_rule(
+            "block drop out on { en0 en1 } proto { tcp udp } from any to any port { 80 443 }",
+            None,
+        )
+        .unwrap();
+        assert_eq!(rule.interface.as_deref(), Some("{ en0 en1 }"));
+        assert_eq!(rule.protocol.as_deref(), Some("{ tcp udp }"));
+        assert_eq!(rule.to.as_deref(), Some("any"));
+        assert_eq!(rule.to_port.as_deref(), Some("{ 80 443 }"));
+
+        // pf.conf spelling without spaces, with commas
+        let rule = parse_pf_rule("block out proto {tcp,udp} to any port {80, 443}", None).unwrap();
+        assert_eq!(rule.protocol.as_deref(), Some("{ tcp udp }"));
+        assert_eq!(rule.to_port.as_deref(), Some("{ 80 443 }"));
+
+        let rules = status(
+            "block drop out proto { tcp udp } from any to any port { 80 443 }\n\
+             block drop out proto tcp from any to any port { = 22 > 8000 }\n",
+            &[],
+        );
+        assert!(blocking(&rules, "tcp", Some(80)).is_some());
+        assert!(blocking(&rules, "udp", Some(443)).is_some());
+        assert!(blocking(&rules, "tcp", Some(22)).is_some());
+        assert!(blocking(&rules, "tcp", Some(8080)).is_some());
+        assert!(blocking(&rules, "tcp", Some(53)).is_none());
+        assert!(blocking(&rules, "icmp", None).is_none());
+    }
+
+    #[test]
+    fn matches_port_operands() {
+        assert!(port_matches("= 80", 80));
+        assert!(!port_matches("= 80", 81));
+        assert!(port_matches("!= 80", 81));
+        assert!(port_matches("< 1024", 80));
+        assert!(port_matches("<= 1024", 1024));
+        assert!(port_matches("> 1024", 8080));
+        assert!(port_matches(">= 1024", 1024));
+        assert!(port_matches("1000:2000", 1000));
+        assert!(port_matches("1000:2000", 2000));
+        assert!(!port_matches("1000 >< 2000", 1000));
+        assert!(port_matches("1000 >< 2000", 1500));
+        assert!(port_matches("1000 <> 2000", 999));
+        assert!(!port_matches("1000 <> 2000", 1500));
+        assert!(port_matches("80", 80));
+        assert!(port_matches("{ 80 443 }", 443));
+        assert!(!port_matches("{ 80 443 }", 8443));
+        assert!(!port_matches("http", 80));
+    }
+
+    #[test]
+    fn parses_pf_report() {
+        let status = parse_pf_report(PF_REPORT).unwrap();
+        assert!(status.enabled);
+        assert_eq!(status.anchors.len(), 5);
+        assert_eq!(status.anchors[0], "com.apple");
+        // The scrub-anchor is dropped
+        assert_eq!(status.rules.len(), 7);
+        let ruleset = |text: &str| {
+            status
+                .rules
+                .iter()
+                .find(|rule| rule.text == text)
+                .map(|rule| rule.ruleset.clone())
+        };
+        assert_eq!(ruleset("anchor \"corp\" all"), Some(None));
+        assert_eq!(
+            ruleset("anchor \"Bonjour\" all"),
+            Some(Some("com.apple/200.AirDrop".to_string()))
+        );
+
+        // Without root only the markers are printed
+        assert!(parse_pf_report("== anchors\n== rules\n").is_none());
+    }
+
+    #[test]
+    fn quick_rule_wins() {
+        let rules = status(
+            "pass out quick proto tcp from any to any port = 80\n\
+             block drop out all\n",
+            &[],
+        );
+        assert_eq!(blocking(&rules, "tcp", Some(80)), None);
+        assert_eq!(
+            blocking(&rules, "udp", Some(53)).as_deref(),
+            Some("block drop out all")
+        );
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let rules = status(
+            "block drop out all\npass out proto tcp from any to any\n",
+            &[],
+        );
+        assert_eq!(blocking(&rules, "tcp", Some(80)), None);
+        assert!(blocking(&rules, "udp", Some(53)).is_some());
+
+        let rules = status("pass out all\nblock drop all\n", &[]);
+        assert_eq!(
+            blocking(&rules, "tcp", Some(80)).as_deref(),
+            Some("block drop all")
+        );
+
+        let mut disabled = rules.clone();
+        disabled.enabled = false;
+        assert_eq!(blocking(&disabled, "tcp", Some(80)), None);
+    }
+
+    #[test]
+    fn rules_that_do_not_apply_are_ignored() {
+        let rules = status(
+            "block drop in all\n\
+             block drop out on en1 all\n\
+             block drop out inet6 all\n\
+             block drop out from any to 10.0.0.1\n\
+             block drop out proto tcp from any port = 80 to any\n",
+            &[],
+        );
+        assert_eq!(blocking(&rules, "tcp", Some(80)), None);
+
+        // An interface rule cannot match traffic whose interface is unknown
+        let rules = status("block drop out on en0 all\n", &[]);
+        let mut unknown = probe("tcp", Some(80));
+        unknown.interface = None;
+        assert!(pf_blocking_rule(&rules, &unknown).is_none());
+        assert!(blocking(&rules, "tcp", Some(80)).is_some());
+    }
+
+    #[test]
+    fn evaluates_anchors() {
+        let status = parse_pf_report(PF_REPORT).unwrap();
+
+        // The corp anchor's last match decides, and the main ruleset has no later match
+        assert_eq!(
+            blocking(&status, "tcp", Some(80)).as_deref(),
+            Some("block drop out proto tcp from any to any port { 80 443 }")
+        );
+        assert_eq!(blocking(&status, "tcp", Some(443)), None);
+        // The quick rule in the main ruleset
+        assert_eq!(blocking(&status, "udp", Some(53)), None);
+    }
+
+    #[test]
+    fn quick_rule_in_an_anchor_stops_the_evaluation() {
+        let rules = status(
+            "anchor \"com.apple/*\" all\n\
+             pass out all\n\
+             == anchor com.apple/100.Blocker\n\
+             block drop out quick proto tcp all\n",
+            &["com.apple", "com.apple/100.Blocker"],
+        );
+        assert_eq!(
+            blocking(&rules, "tcp", Some(80)).as_deref(),
+            Some("block drop out quick proto tcp all")
+        );
+        assert_eq!(blocking(&rules, "udp", Some(53)), None);
+    }
+
+    #[test]
+    fn resolves_anchor_wildcards() {
+        let status = status(
+            "",
+            &[
+                "com.apple",
+                "com.apple/250.ApplicationFirewall",
+                "com.apple/200.AirDrop",
+                "com.apple/200.AirDrop/Bonjour",
+                "corp",
+            ],
+        );
+        assert_eq!(
+            resolve_anchor(&status, None, "com.apple/*"),
+            ["com.apple/200.AirDrop", "com.apple/250.ApplicationFirewall"]
+        );
+        assert_eq!(
+            resolve_anchor(&status, Some("com.apple/200.AirDrop"), "Bonjour"),
+            ["com.apple/200.AirDrop/Bonjour"]
+        );
+        assert_eq!(resolve_anchor(&status, None, "corp"), ["corp"]);
+        assert!(resolve_anchor(&status, None, "missing").is_empty());
+    }
+}
@@ -0,0 +1,786 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::network::get_network_services;
+use super::proxy::{get_proxy_settings, ProxySettings};
+
+/// Local clock differences beyond this break certificate validation and TOTP
+const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+const CURL_TIMINGS: &str = "%{time_namelookup} %{time_connect} %{time_appconnect} \
+                            %{time_starttransfer} %{time_total} %{http_code} \
+                            %{ssl_verify_result} %{remote_ip}";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ProxyMode {
+    /// Use the proxy configured for the service in System Settings
+    System,
+    /// Connect directly even if a proxy is configured
+    Bypass,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CertificateInfo {
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub not_before: Option<String>,
+    pub not_after: Option<String>,
+    pub hostname_match: Option<bool>,
+    pub expired: bool,
+    pub not_yet_valid: bool,
+}
+
+/// Values printed by `curl -w`, in seconds since the start of the request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CurlTimings {
+    pub namelookup: f64,
+    pub connect: f64,
+    pub appconnect: f64,
+    pub starttransfer: f64,
+    pub total: f64,
+    pub http_code: u16,
+    pub ssl_verify_result: i64,
+    pub remote_ip: Option<String>,
+}
+
+/// TLS details from the `curl -v` trace
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TlsTrace {
+    pub tls_version: Option<String>,
+    pub certificate: Option<CertificateInfo>,
+    pub verify_error: Option<String>,
+    pub server_date: Option<String>,
+    pub connection_reset: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpsCheckResult {
+    pub url: String,
+    pub proxy: Option<String>,
+    pub remote_ip: Option<String>,
+    pub http_status: Option<u16>,
+    pub dns_ms: Option<f64>,
+    pub connect_ms: Option<f64>,
+    pub tls_ms: Option<f64>,
+    pub first_byte_ms: Option<f64>,
+    pub total_ms: Option<f64>,
+    pub tls_version: Option<String>,
+    pub certificate: Option<CertificateInfo>,
+    pub certificate_valid: bool,
+    pub verify_error: Option<String>,
+    /// Server clock minus local clock, from the HTTP Date header
+    pub clock_skew_secs: Option<i64>,
+    pub issues: Vec<String>,
+}
+
+fn month_number(name: &str) -> Option<u32> {
+    let months = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let lower = name.to_ascii_lowercase();
+    months
+        .iter()
+        .position(|m| *m == lower)
+        .map(|i| i as u32 + 1)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parse a GMT date as printed by curl for certificates ("Jan  1 00:00:00 2024 GMT")
+/// or by servers in the Date header ("Mon, 01 Jan 2024 00:00:00 GMT") to Unix seconds
+pub fn parse_gmt_date(value: &str) -> Option<i64> {
+    let tokens: Vec<&str> = value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let (day, month, year, time) = match tokens.as_slice() {
+        [month, day, time, year, "GMT"] => (*day, month_number(month)?, *year, *time),
+        [_, day, month, year, time, "GMT"] => (*day, month_number(month)?, *year, *time),
+        _ => return None,
+    };
+
+    let mut clock = time.split(':').map(|part| part.parse::<i64>().ok());
+    let (hours, minutes, seconds) = (clock.next()??, clock.next()??, clock.next()??);
+    let days = days_from_civil(year.parse().ok()?, month, day.parse().ok()?);
+
+    Some(days * 86_400 + hours * 3_600 + minutes * 60 + seconds)
+}
+
+pub fn parse_curl_timings(output: &str) -> Option<CurlTimings> {
+    let tokens: Vec<&str> = output.lines().last()?.split_whitespace().collect();
+    let number = |index: usize| tokens.get(index).and_then(|t| t.parse::<f64>().ok());
+
+    Some(CurlTimings {
+        namelookup: number(0)?,
+        connect: number(1)?,
+        appconnect: number(2)?,
+        starttransfer: number(3)?,
+        total: number(4)?,
+        http_code: tokens.get(5)?.parse().ok()?,
+        ssl_verify_result: tokens.get(6)?.parse().ok()?,
+        remote_ip: tokens.get(7).map(|s| s.to_string()),
+    })
+}
+
+/// Parse the `*` and `<` lines of a `curl -v` trace
+pub fn parse_curl_trace(stderr: &str) -> TlsTrace {
+    let mut trace = TlsTrace::default();
+    let mut certificate: Option<CertificateInfo> = None;
+
+    for line in stderr.lines() {
+        if let Some(header) = line.strip_prefix("< ") {
+            if let Some((name, value)) = header.split_once(':') {
+                if name.eq_ignore_ascii_case("date") {
+                    trace.server_date = Some(value.trim().to_string());
+                }
+            }
+            continue;
+        }
+
+        let Some(info) = line.strip_prefix('*') else {
+            continue;
+        };
+        let info = info.trim();
+        let lower = info.to_ascii_lowercase();
+
+        if let Some(rest) = info.strip_prefix("SSL connection using ") {
+            trace.tls_version = rest.split(" / ").next().map(|v| v.trim().to_string());
+        } else if info == "Server certificate:" {
+            certificate = Some(CertificateInfo::default());
+        } else if lower.contains("certificate problem")
+            || lower.contains("ssl: no alternative")
+            || lower.contains("certificate verify result")
+        {
+            trace.verify_error = Some(info.to_string());
+        } else if lower.contains("connection reset by peer") {
+            trace.connection_reset = true;
+        } else if let Some(cert) = certificate.as_mut() {
+            let (key, value) = match info.split_once(':') {
+                Some((key, value)) => (key.trim(), value.trim().to_string()),
+                None => continue,
+            };
+            match key {
+                "subject" => cert.subject = Some(value),
+                "issuer" => cert.issuer = Some(value),
+                "start date" => cert.not_before = Some(value),
+                "expire date" => cert.not_after = Some(value),
+                "subjectAltName" => {
+                    cert.hostname_match = Some(value.contains("matched"));
+                }
+                _ => {}
+            }
+        }
+        if lower.contains("subjectaltname does not match") {
+            if let Some(cert) = certificate.as_mut() {
+                cert.hostname_match = Some(false);
+            }
+        }
+    }
+
+    trace.certificate = certificate;
+    trace
+}
+
+/// OpenSSL verification codes reported by `%{ssl_verify_result}`
+fn verify_code_message(code: i64) -> String {
+    match code {
+        9 => "certificate is not yet valid".to_string(),
+        10 => "certificate has expired".to_string(),
+        18 => "self-signed certificate".to_string(),
+        19 => "self-signed certificate in certificate chain".to_string(),
+        20 => "unable to get local issuer certificate".to_string(),
+        21 => "unable to verify the first certificate".to_string(),
+        62 => "hostname mismatch".to_string(),
+        code => format!("verification error {}", code),
+    }
+}
+
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or(ipv6),
+        None => authority.split(':').next().unwrap_or(authority),
+    }
+}
+
+/// Match a certificate name against a host, allowing a leading "*." wildcard
+fn name_matches(pattern: &str, host: &str) -> bool {
+    let (pattern, host) = (pattern.to_ascii_lowercase(), host.to_ascii_lowercase());
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+        None => pattern == host,
+    }
+}
+
+/// Combine the timings and trace into a result with human readable issues.
+/// `now` is the local clock in Unix seconds.
+pub fn assess_https(
+    url: &str,
+    proxy: Option<String>,
+    timings: Option<&CurlTimings>,
+    trace: TlsTrace,
+    exit_code: Option<i32>,
+    now: i64,
+) -> HttpsCheckResult {
+    let is_https = url.starts_with("https://");
+    let ms = |seconds: f64| (seconds * 1000.0 * 10.0).round() / 10.0;
+    let reached = |seconds: f64| seconds > 0.0;
+
+    let mut issues = Vec::new();
+    let mut certificate = trace.certificate;
+    if let Some(cert) = certificate.as_mut() {
+        let not_before = cert.not_before.as_deref().and_then(parse_gmt_date);
+        let not_after = cert.not_after.as_deref().and_then(parse_gmt_date);
+        cert.not_yet_valid = not_before.is_some_and(|start| now < start);
+        cert.expired = not_after.is_some_and(|end| now > end);
+
+        if cert.not_yet_valid {
+            issues.push("Certificate is not valid yet, the local clock may be behind".to_string());
+        }
+        if cert.expired {
+            issues.push("Certificate has expired, or the local clock is ahead".to_string());
+        }
+        let name_error = trace.verify_error.as_deref().is_some_and(|error| {
+            let lower = error.to_ascii_lowercase();
+            lower.contains("no alternative certificate subject name")
+                || lower.contains("does not match")
+        });
+        if name_error {
+            cert.hostname_match = Some(false);
+        }
+        // --insecure skips curl's name check, fall back to the subject common name.
+        // IP hosts are only listed in subjectAltName, so the CN says nothing about them.
+        let host = url_host(url);
+        if cert.hostname_match.is_none() && host.parse::<std::net::IpAddr>().is_err() {
+            cert.hostname_match = cert.subject.as_deref().and_then(|subject| {
+                subject
+                    .split(['/', ';', ','])
+                    .find_map(|part| part.trim().strip_prefix("CN="))
+                    .map(|cn| name_matches(cn.trim(), host))
+            });
+        }
+        if cert.hostname_match == Some(false) {
+            issues.push("Certificate does not match the host name".to_string());
+        }
+    }
+
+    let clock_skew_secs = trace
+        .server_date
+        .as_deref()
+        .and_then(parse_gmt_date)
+        .map(|server| server - now);
+    if let Some(skew) = clock_skew_secs.filter(|skew| skew.abs() > MAX_CLOCK_SKEW_SECS) {
+        issues.push(format!(
+            "Local clock differs from the server by {} seconds",
+            skew
+        ));
+    }
+
+    let mut verify_error = trace.verify_error;
+    if let Some(t) = timings.filter(|t| is_https && t.ssl_verify_result != 0) {
+        verify_error.get_or_insert_with(|| verify_code_message(t.ssl_verify_result));
+    }
+    let certificate_valid =
+        is_https && timings.is_some_and(|t| reached(t.appconnect)) && verify_error.is_none();
+    if let Some(error) = &verify_error {
+        let lower = error.to_ascii_lowercase();
+        // OpenSSL 3 writes "self-signed", older releases and LibreSSL "self signed"
+        if lower.contains("self-signed")
+            || lower.contains("self signed")
+            || lower.contains("local issuer")
+        {
+            issues.push(format!(
+                "Certificate is not trusted ({}), traffic may be intercepted by a proxy \
+                 or security software",
+                error
+            ));
+        } else {
+            issues.push(error.clone());
+        }
+    }
+
+    if let Some(t) = timings {
+        if !reached(t.namelookup) && !reached(t.connect) && exit_code == Some(6) {
+            issues.push("Host name could not be resolved".to_string());
+        } else if reached(t.namelookup) && !reached(t.connect) {
+            issues.push("TCP connection failed".to_string());
+        } else if is_https && reached(t.connect) && !reached(t.appconnect) {
+            issues.push(if trace.connection_reset {
+                "TLS handshake was reset, the host name may be filtered (SNI blocking)".to_string()
+            } else {
+                "TLS handshake failed".to_string()
+            });
+        }
+    } else {
+        issues.push(format!(
+            "curl failed with exit code {}",
+            exit_code.unwrap_or(-1)
+        ));
+    }
+
+    let timing = |value: fn(&CurlTimings) -> Option<f64>| timings.and_then(value).map(ms);
+    HttpsCheckResult {
+        url: url.to_string(),
+        proxy,
+        remote_ip: timings
+            .and_then(|t| t.remote_ip.clone())
+            .filter(|ip| !ip.is_empty()),
+        http_status: timings.map(|t| t.http_code).filter(|code| *code != 0),
+        dns_ms: timing(|t| Some(t.namelookup)),
+        connect_ms: timing(|t| (t.connect > 0.0).then_some(t.connect - t.namelookup)),
+        tls_ms: timing(|t| (t.appconnect > 0.0).then_some(t.appconnect - t.connect)),
+        first_byte_ms: timing(|t| (t.starttransfer > 0.0).then_some(t.starttransfer)),
+        total_ms: timing(|t| Some(t.total)),
+        tls_version: trace.tls_version,
+        certificate,
+        certificate_valid,
+        verify_error,
+        clock_skew_secs,
+        issues,
+    }
+}
+
+/// curl `--proxy` value for the system proxy that applies to `url`
+pub fn proxy_for_url(settings: &ProxySettings, url: &str) -> Option<String> {
+    let web = if url.starts_with("https://") {
+        &settings.https_proxy
+    } else {
+        &settings.http_proxy
+    };
+
+    if web.enabled && !web.server.is_empty() {
+        Some(format!("http://{}:{}", web.server, web.port))
+    } else if settings.socks_proxy.enabled && !settings.socks_proxy.server.is_empty() {
+        Some(format!(
+            "socks5h://{}:{}",
+            settings.socks_proxy.server, settings.socks_proxy.port
+        ))
+    } else {
+        None
+    }
+}
+
+fn system_proxy(service_name: Option<String>, url: &str) -> Result<Option<String>, String> {
+    let service_name = match service_name.filter(|s| !s.is_empty()) {
+        Some(name) => name,
+        None => match get_network_services()?.into_iter().find(|s| s.is_default) {
+            Some(service) => service.name,
+            None => return Ok(None),
+        },
+    };
+
+    Ok(proxy_for_url(&get_proxy_settings(service_name)?, url))
+}
+
+fn run_curl(
+    url: &str,
+    proxy: Option<&str>,
+    ca_file: Option<&str>,
+    timeout_secs: u64,
+    insecure: bool,
+) -> Result<(Option<CurlTimings>, TlsTrace, Option<i32>), String> {
+    let mut command = Command::new("curl");
+    command.args([
+        "-v",
+        "-s",
+        "-o",
+        "/dev/null",
+        "--max-time",
+        &timeout_secs.to_string(),
+        "-w",
+        &format!("\n{}", CURL_TIMINGS),
+    ]);
+    match proxy {
+        Some(proxy) => command.args(["--proxy", proxy]),
+        None => command.args(["--noproxy", "*"]),
+    };
+    if let Some(ca_file) = ca_file {
+        command.args(["--cacert", ca_file]);
+    }
+    if insecure {
+        command.arg("--insecure");
+    }
+
+    let output = command
+        .arg(url)
+        .output()
+        .map_err(|e| format!("Failed to execute curl: {}", e))?;
+
+    Ok((
+        parse_curl_timings(&String::from_utf8_lossy(&output.stdout)),
+        parse_curl_trace(&String::from_utf8_lossy(&output.stderr)),
+        output.status.code(),
+    ))
+}
+
+/// Check an HTTP(S) endpoint step by step: DNS, TCP, TLS and the HTTP response.
+///
+/// The certificate is inspected even when it fails verification, `ca_file` sets the
+/// trusted CAs (e.g. a company root or a test CA), and `proxy_mode` decides whether the
+/// service's system proxy is used.
+#[tauri::command(async)]
+pub fn check_https(
+    url: String,
+    proxy_mode: Option<ProxyMode>,
+    service_name: Option<String>,
+    ca_file: Option<String>,
+    timeout_secs: Option<u64>,
+) -> Result<HttpsCheckResult, String> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(format!("'{}' is not an http(s) URL", url));
+    }
+
+    let proxy = match proxy_mode.unwrap_or(ProxyMode::System) {
+        ProxyMode::System => system_proxy(service_name, &url)?,
+        ProxyMode::Bypass => None,
+    };
+
+    let timeout = timeout_secs.unwrap_or(10).clamp(1, 60);
+    let ca_file = ca_file.filter(|f| !f.is_empty());
+    let run = |insecure| {
+        run_curl(
+            &url,
+            proxy.as_deref(),
+            ca_file.as_deref(),
+            timeout,
+            insecure,
+        )
+    };
+
+    let (mut timings, mut trace, mut exit_code) = run(false)?;
+    // Verification failed: repeat without verification so the certificate, timings
+    // and HTTP status can still be reported, keeping the original error
+    if url.starts_with("https://") && matches!(exit_code, Some(51 | 60)) {
+        let verify_error = trace.verify_error.take();
+        (timings, trace, exit_code) = run(true)?;
+        trace.verify_error = verify_error.or(trace.verify_error);
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+
+    Ok(assess_https(
+        &url,
+        proxy,
+        timings.as_ref(),
+        trace,
+        exit_code,
+        now,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::net::{TcpListener, TcpStream};
+    use std::path::PathBuf;
+    use std::process::{Child, Stdio};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const NEW_KEY: &str = "-newkey ec -pkeyopt ec_paramgen_curve:prime256v1 -nodes";
+
+    /// Certificates issued by a throwaway CA, created with the openssl command line tool
+    struct Pki {
+        dir: PathBuf,
+    }
+
+    impl Pki {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "https-{}-{}-{:?}",
+                name,
+                std::process::id(),
+                std::thread::current().id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let pki = Pki { dir };
+            pki.openssl(&format!(
+                "req -x509 {} -keyout ca.key -out ca.pem -days 2 -subj /CN=test-ca",
+                NEW_KEY
+            ));
+            pki
+        }
+
+        /// Run openssl with whitespace-separated `args` in the PKI directory
+        fn openssl(&self, args: &str) {
+            let output = Command::new("openssl")
+                .args(args.split_whitespace())
+                .current_dir(&self.dir)
+                .output()
+                .expect("openssl is required for the TLS tests");
+            assert!(
+                output.status.success(),
+                "openssl {}: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        fn path(&self, name: &str) -> String {
+            self.dir.join(name).to_string_lossy().to_string()
+        }
+
+        /// Issue `name`.pem with `name`.key for `san`
+        fn issue(&self, name: &str, san: &str, kind: Issue) {
+            fs::write(
+                self.dir.join(format!("{}.ext", name)),
+                format!("subjectAltName={}\n", san),
+            )
+            .unwrap();
+            self.openssl(&format!(
+                "req {} -keyout {name}.key -out {name}.csr -subj /CN=leaf",
+                NEW_KEY
+            ));
+
+            if kind == Issue::SelfSigned {
+                self.openssl(&format!(
+                    "x509 -req -in {name}.csr -signkey {name}.key -out {name}.pem -days 1 \
+                     -extfile {name}.ext"
+                ));
+                return;
+            }
+            // `openssl ca` is the portable way to pick arbitrary validity dates
+            fs::write(
+                self.dir.join("ca.cnf"),
+                "[ca]\ndefault_ca = test\n[test]\ndatabase = index.txt\nnew_certs_dir = .\n\
+                 serial = serial\ndefault_md = sha256\npolicy = any\n[any]\n\
+                 commonName = supplied\n",
+            )
+            .unwrap();
+            fs::write(self.dir.join("index.txt"), "").unwrap();
+            fs::write(self.dir.join("serial"), "01\n").unwrap();
+            let end = match kind {
+                Issue::Expired => "20200102000000Z",
+                _ => "20991231000000Z",
+            };
+            self.openssl(&format!(
+                "ca -batch -notext -config ca.cnf -cert ca.pem -keyfile ca.key -in {name}.csr \
+                 -out {name}.pem -startdate 20200101000000Z -enddate {end} -extfile {name}.ext"
+            ));
+        }
+
+        /// Serve `name`.pem with `openssl s_server -www`, which answers HTTP 200
+        fn serve(&self, name: &str) -> TlsServer {
+            let port = {
+                let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+                listener.local_addr().unwrap().port()
+            };
+            let child = Command::new("openssl")
+                .args(["s_server", "-quiet", "-www", "-accept"])
+                .arg(port.to_string())
+                .args(["-cert", &format!("{}.pem", name)])
+                .args(["-key", &format!("{}.key", name)])
+                .current_dir(&self.dir)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap();
+            let server = TlsServer { child, port };
+
+            let started = Instant::now();
+            while TcpStream::connect(("127.0.0.1", port)).is_err() {
+                assert!(
+                    started.elapsed() < Duration::from_secs(10),
+                    "s_server did not start"
+                );
+                thread::sleep(Duration::from_millis(20));
+            }
+            server
+        }
+    }
+
+    impl Drop for Pki {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[derive(PartialEq)]
+    enum Issue {
+        Valid,
+        Expired,
+        SelfSigned,
+    }
+
+    struct TlsServer {
+        child: Child,
+        port: u16,
+    }
+
+    impl TlsServer {
+        fn check(&self, ca_file: Option<String>) -> HttpsCheckResult {
+            check_https(
+                format!("https://127.0.0.1:{}/", self.port),
+                Some(ProxyMode::Bypass),
+                None,
+                ca_file,
+                Some(10),
+            )
+            .unwrap()
+        }
+    }
+
+    impl Drop for TlsServer {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    #[test]
+    fn valid_certificate() {
+        let pki = Pki::new("valid");
+        pki.issue("leaf", "IP:127.0.0.1,DNS:localhost", Issue::Valid);
+        let server = pki.serve("leaf");
+
+        let result = server.check(Some(pki.path("ca.pem")));
+        assert!(result.certificate_valid, "{:?}", result);
+        assert_eq!(result.verify_error, None);
+        assert_eq!(result.http_status, Some(200));
+        assert!(result.issues.is_empty(), "{:?}", result.issues);
+        assert!(result.tls_version.is_some());
+        assert!(result.tls_ms.is_some());
+        let cert = result.certificate.unwrap();
+        assert!(!cert.expired);
+        assert_ne!(cert.hostname_match, Some(false));
+    }
+
+    #[test]
+    fn expired_certificate() {
+        let pki = Pki::new("expired");
+        pki.issue("leaf", "IP:127.0.0.1", Issue::Expired);
+        let server = pki.serve("leaf");
+
+        let result = server.check(Some(pki.path("ca.pem")));
+        assert!(!result.certificate_valid);
+        assert!(result.verify_error.unwrap().contains("expired"));
+        // The certificate is still read through the unverified retry
+        assert_eq!(result.http_status, Some(200));
+        assert!(result.certificate.unwrap().expired);
+        assert!(result
+            .issues
+            .iter()
+            .any(|issue| issue.starts_with("Certificate has expired")));
+    }
+
+    #[test]
+    fn self_signed_certificate() {
+        let pki = Pki::new("self-signed");
+        pki.issue("leaf", "IP:127.0.0.1", Issue::SelfSigned);
+        let server = pki.serve("leaf");
+
+        let result = server.check(None);
+        assert!(!result.certificate_valid);
+        assert_eq!(result.http_status, Some(200));
+        assert!(
+            result
+                .issues
+                .iter()
+                .any(|issue| issue.starts_with("Certificate is not trusted")),
+            "{:?}",
+            result.issues
+        );
+    }
+
+    #[test]
+    fn certificate_for_another_host() {
+        let pki = Pki::new("wrong-host");
+        pki.issue("leaf", "DNS:other.example", Issue::Valid);
+        let server = pki.serve("leaf");
+
+        let result = server.check(Some(pki.path("ca.pem")));
+        assert!(!result.certificate_valid);
+        assert_eq!(result.certificate.unwrap().hostname_match, Some(false));
+        assert!(result
+            .issues
+            .contains(&"Certificate does not match the host name".to_string()));
+    }
+
+    #[test]
+    fn verify_codes_without_trace_are_explained() {
+        let timings = CurlTimings {
+            namelookup: 0.001,
+            connect: 0.01,
+            appconnect: 0.05,
+            starttransfer: 0.06,
+            total: 0.06,
+            http_code: 200,
+            ssl_verify_result: 18,
+            remote_ip: Some("127.0.0.1".to_string()),
+        };
+        let result = assess_https(
+            "https://example.com/",
+            None,
+            Some(&timings),
+            TlsTrace::default(),
+            Some(0),
+            0,
+        );
+        assert_eq!(
+            result.verify_error.as_deref(),
+            Some("self-signed certificate")
+        );
+        assert_eq!(
+            result.issues,
+            [
+                "Certificate is not trusted (self-signed certificate), traffic may be \
+              intercepted by a proxy or security software"
+            ]
+        );
+
+        let legacy = TlsTrace {
+            verify_error: Some("SSL certificate problem: self signed certificate".to_string()),
+            ..TlsTrace::default()
+        };
+        let result = assess_https(
+            "https://example.com/",
+            None,
+            Some(&timings),
+            legacy,
+            Some(0),
+            0,
+        );
+        assert!(result.issues[0].starts_with("Certificate is not trusted"));
+    }
+
+    #[test]
+    fn parses_dates_and_timings() {
+        assert_eq!(
+            parse_gmt_date("Jan  1 00:00:00 2024 GMT"),
+            Some(1_704_067_200)
+        );
+        assert_eq!(
+            parse_gmt_date("Mon, 01 Jan 2024 00:00:00 GMT"),
+            Some(1_704_067_200)
+        );
+        assert_eq!(parse_gmt_date("2024-01-01"), None);
+
+        let timings =
+            parse_curl_timings("<html>\n0.001 0.010 0.050 0.060 0.061 301 0 1.2.3.4").unwrap();
+        assert_eq!(timings.http_code, 301);
+        assert_eq!(timings.remote_ip.as_deref(), Some("1.2.3.4"));
+        assert_eq!(parse_curl_timings("garbage"), None);
+    }
+}
@@ -0,0 +1,431 @@
+use serde::{Deserialize, Serialize};
+
+use super::network::InterfaceStatus;
+use super::runner::system_runner;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Ipv4Address {
+    pub address: String,
+    pub prefix_len: Option<u32>,
+    pub broadcast: Option<String>,
+    /// Peer address of point-to-point links such as utun
+    pub destination: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Ipv6Address {
+    pub address: String,
+    pub prefix_len: Option<u32>,
+    pub scope_id: Option<String>,
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct InterfaceDetails {
+    pub name: String,
+    pub flags: Vec<String>,
+    pub mtu: Option<u32>,
+    pub mac_address: Option<String>,
+    pub ipv4: Vec<Ipv4Address>,
+    pub ipv6: Vec<Ipv6Address>,
+    pub media: Option<String>,
+    pub duplex: Option<String>,
+    pub status: Option<String>,
+    pub nd6_options: Vec<String>,
+    /// Member interfaces of a bridge
+    pub members: Vec<String>,
+}
+
+impl InterfaceDetails {
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    /// Connection state, falling back to address checks for interfaces without a
+    /// `status:` line such as utun
+    pub fn link_status(&self) -> InterfaceStatus {
+        match self.status.as_deref() {
+            Some("active") => InterfaceStatus::Connected,
+            Some(_) => InterfaceStatus::Disconnected,
+            None => {
+                let has_address = self
+                    .ipv4
+                    .iter()
+                    .any(|addr| !addr.address.starts_with("127."));
+                if self.has_flag("UP") && has_address {
+                    InterfaceStatus::Connected
+                } else {
+                    InterfaceStatus::Disconnected
+                }
+            }
+        }
+    }
+}
+
+/// Items between angle brackets, e.g. "8863<UP,BROADCAST>" -> ["UP", "BROADCAST"]
+fn bracket_list(value: &str) -> Vec<String> {
+    value
+        .split_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(inner, _)| {
+            inner
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Prefix length from a hex netmask such as "0xffffff00"
+fn hex_mask_prefix(mask: &str) -> Option<u32> {
+    u32::from_str_radix(mask.trim_start_matches("0x"), 16)
+        .ok()
+        .map(|bits| bits.count_ones())
+}
+
+/// Value following `key` in a whitespace separated token list
+fn token_after<'a>(tokens: &[&'a str], key: &str) -> Option<&'a str> {
+    tokens
+        .iter()
+        .position(|t| *t == key)
+        .and_then(|i| tokens.get(i + 1).copied())
+}
+
+fn parse_inet(tokens: &[&str]) -> Option<Ipv4Address> {
+    Some(Ipv4Address {
+        address: tokens.get(1)?.to_string(),
+        prefix_len: token_after(tokens, "netmask").and_then(hex_mask_prefix),
+        broadcast: token_after(tokens, "broadcast").map(|s| s.to_string()),
+        destination: token_after(tokens, "-->").map(|s| s.to_string()),
+    })
+}
+
+fn parse_inet6(tokens: &[&str]) -> Option<Ipv6Address> {
+    let raw = tokens.get(1)?;
+    let (address, zone) = match raw.split_once('%') {
+        Some((address, zone)) => (address, Some(zone.to_string())),
+        None => (*raw, None),
+    };
+
+    let mut flags = Vec::new();
+    let mut skip_next = false;
+    for token in tokens.iter().skip(2) {
+        if skip_next {
+            skip_next = false;
+        } else if matches!(*token, "prefixlen" | "scopeid" | "-->") {
+            skip_next = true;
+        } else {
+            flags.push(token.to_string());
+        }
+    }
+
+    Some(Ipv6Address {
+        address: address.to_string(),
+        prefix_len: token_after(tokens, "prefixlen").and_then(|p| p.parse().ok()),
+        scope_id: zone.or_else(|| token_after(tokens, "scopeid").map(|s| s.to_string())),
+        flags,
+    })
+}
+
+/// Parse "autoselect (1000baseT <full-duplex>)" into media and duplex
+fn parse_media(value: &str) -> (Option<String>, Option<String>) {
+    let value = value.trim();
+    let duplex = bracket_list(value)
+        .into_iter()
+        .find(|option| option.ends_with("duplex"));
+    (Some(value.to_string()), duplex)
+}
+
+/// Parse `ifconfig` or `ifconfig -a` output into one entry per interface
+pub fn parse_ifconfig(output: &str) -> Vec<InterfaceDetails> {
+    let mut interfaces: Vec<InterfaceDetails> = Vec::new();
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Interface headers start at column 0: "en0: flags=8863<UP,...> mtu 1500"
+        if !line.starts_with(char::is_whitespace) {
+            if let Some((name, rest)) = line.split_once(": ") {
+                let tokens: Vec<&str> = rest.split_whitespace().collect();
+                interfaces.push(InterfaceDetails {
+                    name: name.to_string(),
+                    flags: tokens
+                        .iter()
+                        .find(|t| t.starts_with("flags="))
+                        .map(|t| bracket_list(t))
+                        .unwrap_or_default(),
+                    mtu: token_after(&tokens, "mtu").and_then(|m| m.parse().ok()),
+                    ..Default::default()
+                });
+            }
+            continue;
+        }
+
+        let Some(current) = interfaces.last_mut() else {
+            continue;
+        };
+        let trimmed = line.trim();
+        let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+
+        match tokens.first().copied() {
+            Some("ether") => current.mac_address = tokens.get(1).map(|s| s.to_string()),
+            Some("inet") => current.ipv4.extend(parse_inet(&tokens)),
+            Some("inet6") => current.ipv6.extend(parse_inet6(&tokens)),
+            Some("nd6") => current.nd6_options = bracket_list(trimmed),
+            Some("member:") => current.members.extend(tokens.get(1).map(|s| s.to_string())),
+            Some("media:") => {
+                let (media, duplex) = parse_media(trimmed.trim_start_matches("media:"));
+                current.media = media;
+                current.duplex = duplex;
+            }
+            Some("status:") => current.status = tokens.get(1).map(|s| s.to_string()),
+            _ => {}
+        }
+    }
+
+    interfaces
+}
+
+#[tauri::command]
+pub fn get_interface_details(device: String) -> Result<InterfaceDetails, String> {
+    if device.is_empty() {
+        return Err("Device cannot be empty".to_string());
+    }
+
+    let stdout = system_runner().run("ifconfig", &[&device])?;
+
+    parse_ifconfig(&stdout)
+        .into_iter()
+        .find(|details| details.name == device)
+        .ok_or_else(|| format!("Interface {} does not exist", device))
+}
+
+#[tauri::command]
+pub fn list_interfaces() -> Result<Vec<InterfaceDetails>, String> {
+    let stdout = system_runner().run("ifconfig", &["-a"])?;
+
+    Ok(parse_ifconfig(&stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IFCONFIG: &str = "\
+lo0: flags=8049<UP,LOOPBACK,RUNNING,MULTICAST> mtu 16384
+	options=1203<RXCSUM,TXCSUM,TXSTATUS,SW_TIMESTAMP>
+	inet 127.0.0.1 netmask 0xff000000
+	inet6 ::1 prefixlen 128 
+	inet6 fe80::1%lo0 prefixlen 64 scopeid 0x1 
+	nd6 options=201<PERFORMNUD,DAD>
+en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+	options=6460<TSO4,TSO6,CHANNEL_IO,PARTIAL_CSUM,ZEROINVERT_CSUM>
+	ether a4:83:e7:12:34:56
+	inet6 fe80::1c8a:5f2e:9b3d:7a41%en0 prefixlen 64 secured scopeid 0xe 
+	inet 192.168.1.20 netmask 0xffffff00 broadcast 192.168.1.255
+	inet6 2001:db8:1::1a2b prefixlen 64 autoconf secured 
+	inet6 2001:db8:1::9c4d prefixlen 64 autoconf temporary 
+	nd6 options=201<PERFORMNUD,DAD>
+	media: autoselect
+	status: active
+en5: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+	options=404<VLAN_MTU,CHANNEL_IO>
+	ether 00:e0:4c:68:01:02
+	inet 10.0.0.5 netmask 0xffffff00 broadcast 10.0.0.255
+	nd6 options=201<PERFORMNUD,DAD>
+	media: autoselect (1000baseT <full-duplex>)
+	status: active
+en6: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+	options=400<CHANNEL_IO>
+	ether 00:e0:4c:68:0a:0b
+	nd6 options=201<PERFORMNUD,DAD>
+	media: autoselect (none)
+	status: inactive
+bridge0: flags=8822<BROADCAST,SMART,SIMPLEX,MULTICAST> mtu 1500
+	options=63<RXCSUM,TXCSUM,TSO4,TSO6>
+	ether 36:a1:f2:00:00:01
+	Configuration:
+		id 0:0:0:0:0:0 priority 0 hellotime 0 fwddelay 0
+		maxage 0 holdcnt 0 proto stp maxaddr 100 timeout 1200
+	member: en1 flags=3<LEARNING,DISCOVER>
+	        ifmaxaddr 0 port 8 priority 0 path cost 0
+	member: en2 flags=3<LEARNING,DISCOVER>
+	        ifmaxaddr 0 port 9 priority 0 path cost 0
+	nd6 options=201<PERFORMNUD,DAD>
+	media: <unknown type>
+	status: inactive
+awdl0: flags=8943<UP,BROADCAST,RUNNING,PROMISC,SIMPLEX,MULTICAST> mtu 1500
+	options=6460<TSO4,TSO6,CHANNEL_IO,PARTIAL_CSUM,ZEROINVERT_CSUM>
+	ether 8e:2f:a1:0b:3c:4d
+	inet6 fe80::8c2f:a1ff:fe0b:3c4d%awdl0 prefixlen 64 scopeid 0x10 
+	nd6 options=201<PERFORMNUD,DAD>
+	media: autoselect
+	status: active
+utun4: flags=8051<UP,POINTOPOINT,RUNNING,MULTICAST> mtu 1380
+	inet 10.8.0.2 --> 10.8.0.1 netmask 0xffffffff
+	inet6 fe80::8f3a:2b1c:4d5e:6f70%utun4 prefixlen 64 scopeid 0x14 
+	nd6 options=201<PERFORMNUD,DAD>
+utun0: flags=8051<UP,POINTOPOINT,RUNNING,MULTICAST> mtu 1380
+	inet6 fe80::a1b2:c3d4:e5f6:789%utun0 prefixlen 64 scopeid 0xf 
+	nd6 options=201<PERFORMNUD,DAD>
+";
+
+    fn interface(name: &str) -> InterfaceDetails {
+        parse_ifconfig(IFCONFIG)
+            .into_iter()
+            .find(|i| i.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_every_interface() {
+        let names: Vec<String> = parse_ifconfig(IFCONFIG)
+            .into_iter()
+            .map(|i| i.name)
+            .collect();
+        assert_eq!(
+            names,
+            ["lo0", "en0", "en5", "en6", "bridge0", "awdl0", "utun4", "utun0"]
+        );
+        assert!(parse_ifconfig("").is_empty());
+    }
+
+    #[test]
+    fn wifi_interface() {
+        let en0 = interface("en0");
+
+        assert_eq!(
+            en0.flags,
+            [
+                "UP",
+                "BROADCAST",
+                "SMART",
+                "RUNNING",
+                "SIMPLEX",
+                "MULTICAST"
+            ]
+        );
+        assert_eq!(en0.mtu, Some(1500));
+        assert_eq!(en0.mac_address.as_deref(), Some("a4:83:e7:12:34:56"));
+        assert_eq!(
+            en0.ipv4,
+            [Ipv4Address {
+                address: "192.168.1.20".to_string(),
+                prefix_len: Some(24),
+                broadcast: Some("192.168.1.255".to_string()),
+                destination: None,
+            }]
+        );
+        assert_eq!(en0.ipv6.len(), 3);
+        assert_eq!(
+            en0.ipv6[0],
+            Ipv6Address {
+                address: "fe80::1c8a:5f2e:9b3d:7a41".to_string(),
+                prefix_len: Some(64),
+                scope_id: Some("en0".to_string()),
+                flags: vec!["secured".to_string()],
+            }
+        );
+        assert_eq!(en0.ipv6[2].flags, ["autoconf", "temporary"]);
+        assert_eq!(en0.ipv6[2].scope_id, None);
+        assert_eq!(en0.nd6_options, ["PERFORMNUD", "DAD"]);
+        assert_eq!(en0.media.as_deref(), Some("autoselect"));
+        assert_eq!(en0.duplex, None);
+        assert_eq!(en0.status.as_deref(), Some("active"));
+        assert_eq!(en0.link_status(), InterfaceStatus::Connected);
+    }
+
+    #[test]
+    fn ethernet_interface() {
+        let en5 = interface("en5");
+
+        assert_eq!(
+            en5.media.as_deref(),
+            Some("autoselect (1000baseT <full-duplex>)")
+        );
+        assert_eq!(en5.duplex.as_deref(), Some("full-duplex"));
+        assert_eq!(en5.ipv4[0].address, "10.0.0.5");
+        assert!(en5.ipv6.is_empty());
+        assert_eq!(en5.link_status(), InterfaceStatus::Connected);
+    }
+
+    #[test]
+    fn inactive_interface() {
+        let en6 = interface("en6");
+
+        assert_eq!(en6.status.as_deref(), Some("inactive"));
+        assert_eq!(en6.media.as_deref(), Some("autoselect (none)"));
+        assert!(en6.ipv4.is_empty());
+        // UP but unplugged
+        assert!(en6.has_flag("UP"));
+        assert_eq!(en6.link_status(), InterfaceStatus::Disconnected);
+    }
+
+    #[test]
+    fn bridge_interface() {
+        let bridge = interface("bridge0");
+
+        assert_eq!(bridge.members, ["en1", "en2"]);
+        assert!(!bridge.has_flag("UP"));
+        assert_eq!(bridge.mac_address.as_deref(), Some("36:a1:f2:00:00:01"));
+        assert_eq!(bridge.media.as_deref(), Some("<unknown type>"));
+        assert_eq!(bridge.link_status(), InterfaceStatus::Disconnected);
+    }
+
+    #[test]
+    fn awdl_interface() {
+        let awdl = interface("awdl0");
+
+        assert!(awdl.has_flag("PROMISC"));
+        assert!(awdl.has_flag("UP"));
+        assert_eq!(awdl.mac_address.as_deref(), Some("8e:2f:a1:0b:3c:4d"));
+        assert!(awdl.ipv4.is_empty());
+        assert_eq!(
+            awdl.ipv6,
+            [Ipv6Address {
+                address: "fe80::8c2f:a1ff:fe0b:3c4d".to_string(),
+                prefix_len: Some(64),
+                scope_id: Some("awdl0".to_string()),
+                flags: Vec::new(),
+            }]
+        );
+        // AirDrop keeps the link active, but it carries no IPv4 traffic
+        assert_eq!(awdl.status.as_deref(), Some("active"));
+        assert_eq!(awdl.link_status(), InterfaceStatus::Connected);
+    }
+
+    #[test]
+    fn tunnel_interfaces() {
+        let utun4 = interface("utun4");
+
+        assert_eq!(utun4.mtu, Some(1380));
+        assert_eq!(utun4.mac_address, None);
+        assert_eq!(
+            utun4.ipv4,
+            [Ipv4Address {
+                address: "10.8.0.2".to_string(),
+                prefix_len: Some(32),
+                broadcast: None,
+                destination: Some("10.8.0.1".to_string()),
+            }]
+        );
+        assert_eq!(utun4.ipv6[0].scope_id.as_deref(), Some("utun4"));
+        assert_eq!(utun4.status, None);
+        // No status line: UP with an IPv4 address counts as connected
+        assert_eq!(utun4.link_status(), InterfaceStatus::Connected);
+
+        // Tunnels iCloud Private Relay and friends keep around with link-local only
+        assert_eq!(
+            interface("utun0").link_status(),
+            InterfaceStatus::Disconnected
+        );
+        // Loopback addresses do not count
+        assert_eq!(
+            interface("lo0").link_status(),
+            InterfaceStatus::Disconnected
+        );
+    }
+}
@@ -0,0 +1,691 @@
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::process::Command;
+
+use super::network::{run_with_admin_privileges, shell_quote};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum IpMode {
+    Dhcp,
+    ManualWithDhcpRouter,
+    Manual,
+    Bootp,
+    Off,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum Ipv6Mode {
+    Automatic,
+    LinkLocal,
+    Manual,
+    Off,
+    #[default]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IpConfig {
+    pub service_name: String,
+    pub mode: IpMode,
+    pub ip_address: Option<String>,
+    pub subnet_mask: Option<String>,
+    pub router: Option<String>,
+    // Defaults keep snapshots taken before these fields existed readable
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub ipv6_mode: Ipv6Mode,
+    #[serde(default)]
+    pub ipv6_addresses: Vec<String>,
+    #[serde(default)]
+    pub ipv6_prefix_length: Option<u8>,
+    #[serde(default)]
+    pub ipv6_router: Option<String>,
+}
+
+impl IpConfig {
+    /// Whether two configurations would produce the same `networksetup` setting.
+    ///
+    /// Addresses handed out by DHCP/BOOTP are ignored since they come from the lease.
+    pub fn same_settings(&self, other: &IpConfig) -> bool {
+        if self.mode != other.mode {
+            return false;
+        }
+        match self.mode {
+            IpMode::Manual => {
+                self.ip_address == other.ip_address
+                    && self.subnet_mask == other.subnet_mask
+                    && self.router == other.router
+            }
+            IpMode::ManualWithDhcpRouter => self.ip_address == other.ip_address,
+            IpMode::Dhcp => self.client_id == other.client_id,
+            _ => true,
+        }
+    }
+}
+
+/// `networksetup` prints "none" or nothing for unset values
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+fn parse_ipv6_mode(value: &str) -> Ipv6Mode {
+    match value
+        .trim()
+        .to_ascii_lowercase()
+        .replace(['-', ' '], "")
+        .as_str()
+    {
+        "automatic" => Ipv6Mode::Automatic,
+        "linklocal" | "linklocalonly" => Ipv6Mode::LinkLocal,
+        "manual" => Ipv6Mode::Manual,
+        "off" => Ipv6Mode::Off,
+        _ => Ipv6Mode::Unknown,
+    }
+}
+
+/// Parse `networksetup -getinfo <service>` output
+pub fn parse_get_info(service_name: &str, output: &str) -> IpConfig {
+    let mut config = IpConfig {
+        service_name: service_name.to_string(),
+        mode: IpMode::Unknown,
+        ip_address: None,
+        subnet_mask: None,
+        router: None,
+        client_id: None,
+        ipv6_mode: Ipv6Mode::Unknown,
+        ipv6_addresses: Vec::new(),
+        ipv6_prefix_length: None,
+        ipv6_router: None,
+    };
+
+    for line in output.lines() {
+        let line = line.trim();
+        match line {
+            "DHCP Configuration" => config.mode = IpMode::Dhcp,
+            "Manually Using DHCP Router Configuration" => {
+                config.mode = IpMode::ManualWithDhcpRouter
+            }
+            "Manual Configuration" => config.mode = IpMode::Manual,
+            "BOOTP Configuration" => config.mode = IpMode::Bootp,
+            _ => {
+                if let Some((key, value)) = line.split_once(':') {
+                    match key.trim() {
+                        "IP address" => config.ip_address = non_empty(value),
+                        "Subnet mask" => config.subnet_mask = non_empty(value),
+                        "Router" => config.router = non_empty(value),
+                        "Client ID" => config.client_id = non_empty(value),
+                        "IPv6" => config.ipv6_mode = parse_ipv6_mode(value),
+                        "IPv6 IP address" => config.ipv6_addresses.extend(non_empty(value)),
+                        "IPv6 Prefix Length" => {
+                            config.ipv6_prefix_length = value.trim().parse().ok()
+                        }
+                        "IPv6 Router" => config.ipv6_router = non_empty(value),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    config
+}
+
+pub(crate) fn parse_ipv4(value: &str, what: &str) -> Result<Ipv4Addr, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("{} '{}' is not a valid IPv4 address", what, value))
+}
+
+/// Validate a dotted subnet mask and return its prefix length
+pub fn validate_subnet_mask(mask: &str) -> Result<u32, String> {
+    let bits = u32::from(parse_ipv4(mask, "Subnet mask")?);
+    let prefix = bits.leading_ones();
+    // A contiguous mask has no set bits after its leading ones
+    if bits.checked_shl(prefix).unwrap_or(0) != 0 {
+        return Err(format!("Subnet mask '{}' is not contiguous", mask));
+    }
+    if prefix == 0 {
+        return Err(format!("Subnet mask '{}' is empty", mask));
+    }
+    Ok(prefix)
+}
+
+/// Reject addresses no interface can use: unspecified, broadcast, multicast, loopback
+/// and the reserved 240.0.0.0/4 block
+fn validate_host_address(ip: Ipv4Addr) -> Result<(), String> {
+    if ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_loopback()
+        || ip.octets()[0] >= 240
+    {
+        return Err(format!("IP address '{}' cannot be assigned to a host", ip));
+    }
+    Ok(())
+}
+
+/// Validate the address for "manual with DHCP router". The subnet comes from DHCP,
+/// so the network and broadcast addresses are only rejected when `subnet_mask`, the
+/// service's current mask, is known.
+pub fn validate_dhcp_router_address(
+    ip_address: &str,
+    subnet_mask: Option<&str>,
+) -> Result<Ipv4Addr, String> {
+    let ip = parse_ipv4(ip_address, "IP address")?;
+    validate_host_address(ip)?;
+    if ip.is_link_local() {
+        return Err(format!(
+            "IP address '{}' is in the self-assigned range 169.254.0.0/16",
+            ip
+        ));
+    }
+
+    if let Some(prefix) = subnet_mask.and_then(|mask| validate_subnet_mask(mask).ok()) {
+        let mask = u32::MAX << (32 - prefix);
+        let network = u32::from(ip) & mask;
+        if prefix < 31 && (u32::from(ip) == network || u32::from(ip) == network | !mask) {
+            return Err(format!(
+                "IP address '{}' is the network or broadcast address of its subnet",
+                ip
+            ));
+        }
+    }
+
+    Ok(ip)
+}
+
+/// Validate a manual IPv4 configuration: the address must be a usable host and the
+/// router must sit inside the same subnet
+pub fn validate_manual_config(
+    ip_address: &str,
+    subnet_mask: &str,
+    router: &str,
+) -> Result<(), String> {
+    let ip = parse_ipv4(ip_address, "IP address")?;
+    let prefix = validate_subnet_mask(subnet_mask)?;
+    let router_ip = parse_ipv4(router, "Router")?;
+
+    validate_host_address(ip)?;
+
+    let mask = u32::from(parse_ipv4(subnet_mask, "Subnet mask")?);
+    let network = u32::from(ip) & mask;
+    let broadcast = network | !mask;
+    // /31 and /32 have no network or broadcast address to avoid
+    if prefix < 31 && (u32::from(ip) == network || u32::from(ip) == broadcast) {
+        return Err(format!(
+            "IP address '{}' is the network or broadcast address of its subnet",
+            ip
+        ));
+    }
+    if u32::from(router_ip) & mask != network {
+        return Err(format!(
+            "Router '{}' is not in the subnet {}/{}",
+            router_ip,
+            Ipv4Addr::from(network),
+            prefix
+        ));
+    }
+    if prefix < 31 && (u32::from(router_ip) == network || u32::from(router_ip) == broadcast) {
+        return Err(format!(
+            "Router '{}' is the network or broadcast address of its subnet",
+            router_ip
+        ));
+    }
+    if router_ip == ip {
+        return Err("Router cannot be the same as the IP address".to_string());
+    }
+
+    Ok(())
+}
+
+pub(crate) fn get_ip_config_internal(service_name: &str) -> Result<IpConfig, String> {
+    let output = Command::new("networksetup")
+        .args(["-getinfo", service_name])
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.contains("is not a recognized network service") {
+        return Err(format!("Network service '{}' does not exist", service_name));
+    }
+
+    Ok(parse_get_info(service_name, &stdout))
+}
+
+/// The root `networksetup` command for an IP configuration, every value single-quoted
+pub fn ip_config_command(config: &IpConfig) -> Result<String, String> {
+    if config.service_name.is_empty() {
+        return Err("Service name cannot be empty".to_string());
+    }
+
+    let required = |value: &Option<String>, what: &str| {
+        value
+            .clone()
+            .ok_or_else(|| format!("{} is required for {:?}", what, config.mode))
+    };
+    let service = shell_quote(&config.service_name);
+
+    Ok(match config.mode {
+        IpMode::Dhcp => match config.client_id.as_deref() {
+            Some(client_id) => format!(
+                "networksetup -setdhcp {} {}",
+                service,
+                shell_quote(client_id)
+            ),
+            None => format!("networksetup -setdhcp {}", service),
+        },
+        IpMode::Bootp => format!("networksetup -setbootp {}", service),
+        IpMode::Manual => {
+            let ip = required(&config.ip_address, "IP address")?;
+            let mask = required(&config.subnet_mask, "Subnet mask")?;
+            let router = required(&config.router, "Router")?;
+            validate_manual_config(&ip, &mask, &router)?;
+            format!(
+                "networksetup -setmanual {} {} {} {}",
+                service,
+                shell_quote(ip.trim()),
+                shell_quote(mask.trim()),
+                shell_quote(router.trim())
+            )
+        }
+        IpMode::ManualWithDhcpRouter => {
+            let ip = validate_dhcp_router_address(
+                &required(&config.ip_address, "IP address")?,
+                config.subnet_mask.as_deref(),
+            )?;
+            format!(
+                "networksetup -setmanualwithdhcprouter {} {}",
+                service,
+                shell_quote(&ip.to_string())
+            )
+        }
+        IpMode::Off => format!("networksetup -setv4off {}", service),
+        IpMode::Unknown => {
+            return Err(format!("Cannot apply IP mode {:?}", config.mode));
+        }
+    })
+}
+
+/// Apply an IP configuration with the matching `networksetup` verb
+pub(crate) fn apply_ip_config(config: &IpConfig) -> Result<String, String> {
+    run_with_admin_privileges(&ip_config_command(config)?)?;
+
+    Ok(format!(
+        "IP configuration set to {:?} for {}",
+        config.mode, config.service_name
+    ))
+}
+
+/// Build an IPv4-only configuration for the setter commands
+fn ipv4_config(service_name: String, mode: IpMode) -> IpConfig {
+    IpConfig {
+        service_name,
+        mode,
+        ip_address: None,
+        subnet_mask: None,
+        router: None,
+        client_id: None,
+        ipv6_mode: Ipv6Mode::Unknown,
+        ipv6_addresses: Vec::new(),
+        ipv6_prefix_length: None,
+        ipv6_router: None,
+    }
+}
+
+#[tauri::command]
+pub fn get_ip_config(service_name: String) -> Result<IpConfig, String> {
+    get_ip_config_internal(&service_name)
+}
+
+#[tauri::command]
+pub fn set_dhcp(service_name: String, client_id: Option<String>) -> Result<String, String> {
+    let mut config = ipv4_config(service_name, IpMode::Dhcp);
+    config.client_id = client_id.filter(|id| !id.is_empty());
+    apply_ip_config(&config)
+}
+
+#[tauri::command]
+pub fn set_bootp(service_name: String) -> Result<String, String> {
+    apply_ip_config(&ipv4_config(service_name, IpMode::Bootp))
+}
+
+#[tauri::command]
+pub fn set_manual_ip(
+    service_name: String,
+    ip_address: String,
+    subnet_mask: String,
+    router: String,
+) -> Result<String, String> {
+    let mut config = ipv4_config(service_name, IpMode::Manual);
+    config.ip_address = Some(ip_address);
+    config.subnet_mask = Some(subnet_mask);
+    config.router = Some(router);
+    apply_ip_config(&config)
+}
+
+#[tauri::command]
+pub fn set_manual_with_dhcp_router(
+    service_name: String,
+    ip_address: String,
+) -> Result<String, String> {
+    let mut config = ipv4_config(service_name, IpMode::ManualWithDhcpRouter);
+    config.ip_address = Some(ip_address);
+    // The current mask, if any, lets the address be checked against its subnet
+    config.subnet_mask = get_ip_config_internal(&config.service_name)
+        .ok()
+        .and_then(|current| current.subnet_mask);
+    apply_ip_config(&config)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Ipv6Config {
+    pub service_name: String,
+    pub mode: Ipv6Mode,
+    pub addresses: Vec<String>,
+    pub prefix_length: Option<u8>,
+    pub router: Option<String>,
+}
+
+#[tauri::command]
+pub fn get_ipv6_config(service_name: String) -> Result<Ipv6Config, String> {
+    let config = get_ip_config_internal(&service_name)?;
+
+    Ok(Ipv6Config {
+        service_name,
+        mode: config.ipv6_mode,
+        addresses: config.ipv6_addresses,
+        prefix_length: config.ipv6_prefix_length,
+        router: config.ipv6_router,
+    })
+}
+
+#[tauri::command]
+pub fn set_ipv6_mode(
+    service_name: String,
+    mode: Ipv6Mode,
+    address: Option<String>,
+    prefix_length: Option<u8>,
+    router: Option<String>,
+) -> Result<String, String> {
+    if service_name.is_empty() {
+        return Err("Service name cannot be empty".to_string());
+    }
+
+    let service = shell_quote(&service_name);
+    let command = match mode {
+        Ipv6Mode::Automatic => format!("networksetup -setv6automatic {}", service),
+        Ipv6Mode::LinkLocal => format!("networksetup -setv6linklocal {}", service),
+        Ipv6Mode::Off => format!("networksetup -setv6off {}", service),
+        Ipv6Mode::Manual => {
+            let address: Ipv6Addr = address
+                .as_deref()
+                .ok_or("IPv6 address is required for manual mode")?
+                .trim()
+                .parse()
+                .map_err(|_| "IPv6 address is not valid".to_string())?;
+            let prefix_length = prefix_length.unwrap_or(64);
+            if !(1..=128).contains(&prefix_length) {
+                return Err(format!(
+                    "Prefix length {} must be between 1 and 128",
+                    prefix_length
+                ));
+            }
+            let router: Ipv6Addr = router
+                .as_deref()
+                .ok_or("IPv6 router is required for manual mode")?
+                .trim()
+                .parse()
+                .map_err(|_| "IPv6 router is not valid".to_string())?;
+            format!(
+                "networksetup -setv6manual {} {} {} {}",
+                service,
+                shell_quote(&address.to_string()),
+                prefix_length,
+                shell_quote(&router.to_string())
+            )
+        }
+        Ipv6Mode::Unknown => return Err("Cannot apply IPv6 mode Unknown".to_string()),
+    };
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!("IPv6 set to {:?} for {}", mode, service_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contiguous_masks() {
+        assert_eq!(validate_subnet_mask("255.255.255.0"), Ok(24));
+        assert_eq!(validate_subnet_mask(" 255.255.240.0 "), Ok(20));
+        assert_eq!(validate_subnet_mask("255.255.255.254"), Ok(31));
+        assert_eq!(validate_subnet_mask("255.255.255.255"), Ok(32));
+        assert_eq!(validate_subnet_mask("128.0.0.0"), Ok(1));
+    }
+
+    #[test]
+    fn non_contiguous_and_invalid_masks() {
+        for mask in [
+            "255.0.255.0",
+            "255.255.255.1",
+            "0.255.255.255",
+            "255.255.254.128",
+        ] {
+            assert_eq!(
+                validate_subnet_mask(mask),
+                Err(format!("Subnet mask '{}' is not contiguous", mask))
+            );
+        }
+        assert_eq!(
+            validate_subnet_mask("0.0.0.0"),
+            Err("Subnet mask '0.0.0.0' is empty".to_string())
+        );
+        assert_eq!(
+            validate_subnet_mask("255.255.255"),
+            Err("Subnet mask '255.255.255' is not a valid IPv4 address".to_string())
+        );
+    }
+
+    #[test]
+    fn valid_manual_configs() {
+        assert_eq!(
+            validate_manual_config("192.168.1.20", "255.255.255.0", "192.168.1.1"),
+            Ok(())
+        );
+        assert_eq!(
+            validate_manual_config("10.0.3.7", "255.255.0.0", "10.0.255.254"),
+            Ok(())
+        );
+        // Point-to-point /31 links use both addresses
+        assert_eq!(
+            validate_manual_config("10.0.0.0", "255.255.255.254", "10.0.0.1"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn gateway_outside_the_subnet() {
+        assert_eq!(
+            validate_manual_config("192.168.1.20", "255.255.255.0", "192.168.2.1"),
+            Err("Router '192.168.2.1' is not in the subnet 192.168.1.0/24".to_string())
+        );
+        assert_eq!(
+            validate_manual_config("10.0.0.10", "255.255.255.248", "10.0.0.17"),
+            Err("Router '10.0.0.17' is not in the subnet 10.0.0.8/29".to_string())
+        );
+        assert!(validate_manual_config("10.0.0.1", "255.255.255.255", "10.0.0.2").is_err());
+    }
+
+    #[test]
+    fn network_and_broadcast_addresses() {
+        for ip in ["192.168.1.0", "192.168.1.255"] {
+            assert_eq!(
+                validate_manual_config(ip, "255.255.255.0", "192.168.1.1"),
+                Err(format!(
+                    "IP address '{}' is the network or broadcast address of its subnet",
+                    ip
+                ))
+            );
+        }
+        for router in ["192.168.1.0", "192.168.1.255"] {
+            assert_eq!(
+                validate_manual_config("192.168.1.20", "255.255.255.0", router),
+                Err(format!(
+                    "Router '{}' is the network or broadcast address of its subnet",
+                    router
+                ))
+            );
+        }
+        // 172.16.0.255 is an ordinary host in a /16
+        assert_eq!(
+            validate_manual_config("172.16.0.255", "255.255.0.0", "172.16.0.1"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn addresses_that_cannot_be_assigned() {
+        for ip in ["0.0.0.0", "255.255.255.255", "224.0.0.251", "127.0.0.1"] {
+            assert_eq!(
+                validate_manual_config(ip, "255.0.0.0", "10.0.0.1"),
+                Err(format!("IP address '{}' cannot be assigned to a host", ip))
+            );
+        }
+        assert_eq!(
+            validate_manual_config("192.168.1.1", "255.255.255.0", "192.168.1.1"),
+            Err("Router cannot be the same as the IP address".to_string())
+        );
+        assert_eq!(
+            validate_manual_config("192.168.1.300", "255.255.255.0", "192.168.1.1"),
+            Err("IP address '192.168.1.300' is not a valid IPv4 address".to_string())
+        );
+        assert_eq!(
+            validate_manual_config("192.168.1.20", "255.255.0.255", "192.168.1.1"),
+            Err("Subnet mask '255.255.0.255' is not contiguous".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_getinfo_output() {
+        let config = parse_get_info(
+            "Ethernet",
+            "Manual Configuration\n\
+             IP address: 10.0.0.5\n\
+             Subnet mask: 255.255.255.0\n\
+             Router: 10.0.0.1\n\
+             IPv6: Automatic\n\
+             IPv6 IP address: none\n\
+             IPv6 Router: none\n\
+             Ethernet Address: 00:e0:4c:68:01:02\n",
+        );
+        assert_eq!(config.mode, IpMode::Manual);
+        assert_eq!(config.ip_address.as_deref(), Some("10.0.0.5"));
+        assert_eq!(config.router.as_deref(), Some("10.0.0.1"));
+        assert_eq!(config.ipv6_mode, Ipv6Mode::Automatic);
+        assert!(config.ipv6_addresses.is_empty());
+        assert_eq!(config.ipv6_router, None);
+
+        let dhcp = parse_get_info(
+            "Wi-Fi",
+            "DHCP Configuration\nClient ID: \nIP address: 192.168.1.20\n",
+        );
+        assert_eq!(dhcp.mode, IpMode::Dhcp);
+        assert_eq!(dhcp.client_id, None);
+        assert!(dhcp.same_settings(&IpConfig {
+            ip_address: Some("192.168.7.9".to_string()),
+            ..dhcp.clone()
+        }));
+    }
+
+    #[test]
+    fn parses_bootp_and_dhcp_router_getinfo_output() {
+        let bootp = parse_get_info(
+            "Ethernet",
+            "BOOTP Configuration\n\
+             IP address: 10.1.2.30\n\
+             Subnet mask: 255.255.0.0\n\
+             Router: 10.1.0.1\n\
+             IPv6: Off\n\
+             Ethernet Address: 00:e0:4c:68:01:02\n",
+        );
+        assert_eq!(bootp.mode, IpMode::Bootp);
+        assert_eq!(bootp.ip_address.as_deref(), Some("10.1.2.30"));
+        assert_eq!(bootp.subnet_mask.as_deref(), Some("255.255.0.0"));
+        assert_eq!(bootp.ipv6_mode, Ipv6Mode::Off);
+
+        let manual_router = parse_get_info(
+            "Wi-Fi",
+            "Manually Using DHCP Router Configuration\n\
+             IP address: 192.168.1.50\n\
+             Subnet mask: 255.255.255.0\n\
+             Router: 192.168.1.1\n\
+             IPv6: Automatic\n\
+             IPv6 IP address: none\n\
+             IPv6 Router: none\n\
+             Wi-Fi ID: a4:83:e7:11:22:33\n",
+        );
+        assert_eq!(manual_router.mode, IpMode::ManualWithDhcpRouter);
+        assert_eq!(manual_router.ip_address.as_deref(), Some("192.168.1.50"));
+        assert_eq!(manual_router.router.as_deref(), Some("192.168.1.1"));
+    }
+
+    #[test]
+    fn dhcp_router_address_must_be_a_host() {
+        for ip in [
+            "0.0.0.0",
+            "255.255.255.255",
+            "224.0.0.1",
+            "127.0.0.1",
+            "240.0.0.1",
+        ] {
+            assert_eq!(
+                validate_dhcp_router_address(ip, None),
+                Err(format!("IP address '{}' cannot be assigned to a host", ip))
+            );
+        }
+        assert!(validate_dhcp_router_address("169.254.10.1", None).is_err());
+        assert!(validate_dhcp_router_address("192.168.1.0", Some("255.255.255.0")).is_err());
+        assert!(validate_dhcp_router_address("192.168.1.255", Some("255.255.255.0")).is_err());
+        // Without a known mask only the address itself can be checked
+        assert!(validate_dhcp_router_address("192.168.1.255", None).is_ok());
+        assert_eq!(
+            validate_dhcp_router_address("192.168.1.50", Some("255.255.255.0")),
+            Ok("192.168.1.50".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn ip_config_command_quotes_every_value() {
+        let mut config = ipv4_config("Wi-Fi $(touch /tmp/x)".to_string(), IpMode::Dhcp);
+        config.client_id = Some("`id`'s box".to_string());
+        assert_eq!(
+            ip_config_command(&config),
+            Ok("networksetup -setdhcp 'Wi-Fi $(touch /tmp/x)' '`id`'\\''s box'".to_string())
+        );
+
+        config.mode = IpMode::Manual;
+        config.ip_address = Some("10.0.0.5".to_string());
+        config.subnet_mask = Some("255.255.255.0".to_string());
+        config.router = Some("10.0.0.1".to_string());
+        assert_eq!(
+            ip_config_command(&config),
+            Ok("networksetup -setmanual 'Wi-Fi $(touch /tmp/x)' \
+                '10.0.0.5' '255.255.255.0' '10.0.0.1'"
+                .to_string())
+        );
+
+        config.mode = IpMode::ManualWithDhcpRouter;
+        config.ip_address = Some("10.0.0.0".to_string());
+        assert!(ip_config_command(&config).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use super::network::{run_with_admin_privileges, shell_quote};
+use super::runner::{system_runner, CommandRunner};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NetworkLocation {
+    pub name: String,
+    pub is_current: bool,
+}
+
+/// Parse `networksetup -listlocations` output, one location per line
+pub fn parse_list_locations(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Parse `networksetup -getcurrentlocation` output
+pub fn parse_current_location(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .map(|line| line.to_string())
+}
+
+fn list_locations_internal() -> Result<Vec<String>, String> {
+    let output = Command::new("networksetup")
+        .arg("-listlocations")
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    Ok(parse_list_locations(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Name of the active location, used to tag results of service commands
+pub(crate) fn current_location() -> Option<String> {
+    current_location_with(system_runner())
+}
+
+pub(crate) fn current_location_with(runner: &dyn CommandRunner) -> Option<String> {
+    let stdout = runner.run("networksetup", &["-getcurrentlocation"]).ok()?;
+
+    parse_current_location(&stdout)
+}
+
+#[tauri::command]
+pub fn list_locations() -> Result<Vec<NetworkLocation>, String> {
+    let current = current_location();
+
+    Ok(list_locations_internal()?
+        .into_iter()
+        .map(|name| NetworkLocation {
+            is_current: current.as_ref() == Some(&name),
+            name,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn get_current_location() -> Result<String, String> {
+    current_location().ok_or_else(|| "Failed to read the current location".to_string())
+}
+
+#[tauri::command]
+pub fn switch_location(name: String) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("Location name cannot be empty".to_string());
+    }
+
+    let existing = list_locations_internal()?;
+    if !existing.iter().any(|l| l == &name) {
+        return Err(format!("Location '{}' does not exist", name));
+    }
+
+    let command = format!("networksetup -switchtolocation {}", shell_quote(&name));
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!("Switched to location '{}'", name))
+}
+
+/// Create a location; `populate` fills it with the default services
+#[tauri::command]
+pub fn create_location(name: String, populate: Option<bool>) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("Location name cannot be empty".to_string());
+    }
+
+    if let Ok(existing) = list_locations_internal() {
+        if existing.iter().any(|l| l == &name) {
+            return Err("Location name already exists".to_string());
+        }
+    }
+
+    let mut command = format!("networksetup -createlocation {}", shell_quote(&name));
+    if populate.unwrap_or(true) {
+        command.push_str(" populate");
+    }
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!("Location '{}' created successfully", name))
+}
+
+#[tauri::command]
+pub fn delete_location(name: String) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("Location name cannot be empty".to_string());
+    }
+
+    if let Ok(existing) = list_locations_internal() {
+        if !existing.iter().any(|l| l == &name) {
+            return Err(format!("Location '{}' does not exist", name));
+        }
+        if existing.len() <= 1 {
+            return Err("Cannot delete the only location".to_string());
+        }
+    }
+    if current_location().as_ref() == Some(&name) {
+        return Err("Cannot delete the current location, switch to another one first".to_string());
+    }
+
+    let command = format!("networksetup -deletelocation {}", shell_quote(&name));
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!("Location '{}' deleted successfully", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Networksetup(Result<&'static str, &'static str>);
+
+    impl CommandRunner for Networksetup {
+        fn run(&self, program: &str, args: &[&str]) -> Result<String, String> {
+            assert_eq!(
+                (program, args),
+                ("networksetup", &["-getcurrentlocation"][..])
+            );
+            self.0.map(str::to_string).map_err(str::to_string)
+        }
+    }
+
+    #[test]
+    fn parses_location_list() {
+        assert_eq!(
+            parse_list_locations("Automatic\nOffice VPN\n  Café  \n\nHome (Ethernet)\n"),
+            ["Automatic", "Office VPN", "Café", "Home (Ethernet)"]
+        );
+        assert_eq!(
+            parse_list_locations("Automatic\r\nHome\r\n"),
+            ["Automatic", "Home"]
+        );
+        assert!(parse_list_locations("").is_empty());
+        assert!(parse_list_locations("\n  \n").is_empty());
+    }
+
+    #[test]
+    fn parses_current_location() {
+        assert_eq!(
+            parse_current_location("Office VPN\n").as_deref(),
+            Some("Office VPN")
+        );
+        assert_eq!(
+            parse_current_location("\n  Automatic  \n").as_deref(),
+            Some("Automatic")
+        );
+        assert_eq!(parse_current_location(""), None);
+        assert_eq!(parse_current_location(" \n"), None);
+    }
+
+    #[test]
+    fn reads_current_location_through_the_runner() {
+        assert_eq!(
+            current_location_with(&Networksetup(Ok("Home\n"))).as_deref(),
+            Some("Home")
+        );
+        assert_eq!(current_location_with(&Networksetup(Ok(""))), None);
+        assert_eq!(current_location_with(&Networksetup(Err("not found"))), None);
+    }
+
+    #[test]
+    fn empty_names_are_rejected_before_running_anything() {
+        assert_eq!(
+            switch_location(String::new()).unwrap_err(),
+            "Location name cannot be empty"
+        );
+        assert_eq!(
+            create_location(String::new(), None).unwrap_err(),
+            "Location name cannot be empty"
+        );
+        assert_eq!(
+            delete_location(String::new()).unwrap_err(),
+            "Location name cannot be empty"
+        );
+    }
+}
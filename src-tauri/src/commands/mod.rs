@@ -1,4 +1,26 @@
+pub mod captive;
+pub mod deep_reset;
+pub mod dhcp;
 pub mod diagnostics;
 pub mod dns;
+pub mod doctor;
+pub mod firewall;
+pub mod https;
+pub mod interface;
+pub mod ip;
+pub mod location;
+pub mod mtu;
+pub mod neighbor;
 pub mod network;
+pub mod ports;
 pub mod proxy;
+pub mod reset;
+pub mod route;
+pub mod runner;
+pub mod snapshot;
+#[cfg(test)]
+mod test_http;
+pub mod throughput;
+pub mod vpn;
+pub mod wifi;
+pub mod wifi_analysis;
@@ -0,0 +1,322 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use super::network::{run_with_admin_privileges, shell_quote};
+
+/// IPv4 header (20 bytes) plus ICMP echo header (8 bytes)
+const ICMP_OVERHEAD: u32 = 28;
+/// Smallest payload used to check the target is reachable at all
+const MIN_PROBE_PAYLOAD: u32 = 64;
+/// Largest payload that fits a standard 1500 byte Ethernet frame
+const DEFAULT_MAX_PAYLOAD: u32 = 1500 - ICMP_OVERHEAD;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MtuInfo {
+    pub hardware_port: String,
+    pub active: u32,
+    pub current_setting: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MtuRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MtuProbe {
+    pub payload_size: u32,
+    pub success: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PathMtuResult {
+    pub target: String,
+    pub reachable: bool,
+    pub path_mtu: Option<u32>,
+    pub probes: Vec<MtuProbe>,
+}
+
+/// Parse `networksetup -getMTU` output, e.g. "Active MTU: 1500 (Current Setting: 1500)"
+pub fn parse_get_mtu(hardware_port: &str, output: &str) -> Option<MtuInfo> {
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Active MTU:") {
+            let (active, setting) = match rest.split_once('(') {
+                Some((active, setting)) => (active, Some(setting)),
+                None => (rest, None),
+            };
+            let active = active.trim().parse().ok()?;
+            let current_setting = setting.and_then(|s| {
+                s.trim_end_matches(')')
+                    .trim()
+                    .trim_start_matches("Current Setting:")
+                    .trim()
+                    .parse()
+                    .ok()
+            });
+
+            return Some(MtuInfo {
+                hardware_port: hardware_port.to_string(),
+                active,
+                current_setting,
+            });
+        }
+    }
+
+    None
+}
+
+/// Parse `networksetup -listValidMTURange` output, e.g. "Valid MTU Range: 1280-9000"
+pub fn parse_valid_mtu_range(output: &str) -> Option<MtuRange> {
+    for line in output.lines() {
+        if let Some(rest) = line.trim().strip_prefix("Valid MTU Range:") {
+            let (min, max) = rest.trim().split_once('-')?;
+            return Some(MtuRange {
+                min: min.trim().parse().ok()?,
+                max: max.trim().parse().ok()?,
+            });
+        }
+    }
+
+    None
+}
+
+/// Binary search for the largest payload that passes with the don't-fragment bit set.
+///
+/// `probe` sends one packet with the given payload size and reports whether a reply came
+/// back. The first probe uses a small payload to make sure the target answers at all,
+/// then sizes shrink from `max_payload` until the largest passing size is found.
+pub fn search_path_mtu<F>(max_payload: u32, mut probe: F) -> (Option<u32>, Vec<MtuProbe>)
+where
+    F: FnMut(u32) -> bool,
+{
+    let mut probes = Vec::new();
+    let mut send = |size: u32, probes: &mut Vec<MtuProbe>| {
+        let success = probe(size);
+        probes.push(MtuProbe {
+            payload_size: size,
+            success,
+        });
+        success
+    };
+
+    // The reachability probe never exceeds the limit, so it is the answer for tiny limits
+    if !send(MIN_PROBE_PAYLOAD.min(max_payload), &mut probes) {
+        return (None, probes);
+    }
+    if max_payload <= MIN_PROBE_PAYLOAD || send(max_payload, &mut probes) {
+        return (Some(max_payload + ICMP_OVERHEAD), probes);
+    }
+
+    // Invariant: `good` passes, `bad` fails
+    let mut good = MIN_PROBE_PAYLOAD;
+    let mut bad = max_payload;
+    while bad - good > 1 {
+        let mid = good + (bad - good) / 2;
+        if send(mid, &mut probes) {
+            good = mid;
+        } else {
+            bad = mid;
+        }
+    }
+
+    (Some(good + ICMP_OVERHEAD), probes)
+}
+
+/// Send a single don't-fragment ICMP echo with the given payload size
+fn ping_with_df(target: &str, payload_size: u32) -> bool {
+    Command::new("ping")
+        .args([
+            "-D",
+            "-c",
+            "1",
+            "-t",
+            "2",
+            "-s",
+            &payload_size.to_string(),
+            target,
+        ])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+#[tauri::command]
+pub fn get_mtu(hardware_port: String) -> Result<MtuInfo, String> {
+    let output = Command::new("networksetup")
+        .args(["-getMTU", &hardware_port])
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_get_mtu(&hardware_port, &stdout).ok_or_else(|| {
+        format!(
+            "Failed to read MTU for {}: {}",
+            hardware_port,
+            stdout.trim()
+        )
+    })
+}
+
+#[tauri::command]
+pub fn get_valid_mtu_range(hardware_port: String) -> Result<MtuRange, String> {
+    let output = Command::new("networksetup")
+        .args(["-listValidMTURange", &hardware_port])
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_valid_mtu_range(&stdout).ok_or_else(|| {
+        format!(
+            "Failed to read valid MTU range for {}: {}",
+            hardware_port,
+            stdout.trim()
+        )
+    })
+}
+
+#[tauri::command]
+pub fn set_mtu(hardware_port: String, mtu: u32) -> Result<String, String> {
+    if hardware_port.is_empty() {
+        return Err("Hardware port cannot be empty".to_string());
+    }
+
+    let range = get_valid_mtu_range(hardware_port.clone())?;
+    if mtu < range.min || mtu > range.max {
+        return Err(format!(
+            "MTU {} is outside the valid range {}-{} for {}",
+            mtu, range.min, range.max, hardware_port
+        ));
+    }
+
+    let command = format!(
+        "networksetup -setMTU {} {}",
+        shell_quote(&hardware_port),
+        mtu
+    );
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!("MTU set to {} for {}", mtu, hardware_port))
+}
+
+/// Probing waits up to two seconds per packet, so it runs off the main thread
+#[tauri::command(async)]
+pub fn discover_path_mtu(target: String, max_mtu: Option<u32>) -> Result<PathMtuResult, String> {
+    if target.is_empty() {
+        return Err("Target cannot be empty".to_string());
+    }
+
+    let max_payload = max_mtu
+        .map(|mtu| mtu.saturating_sub(ICMP_OVERHEAD))
+        .unwrap_or(DEFAULT_MAX_PAYLOAD);
+
+    let (path_mtu, probes) = search_path_mtu(max_payload, |size| ping_with_df(&target, size));
+
+    Ok(PathMtuResult {
+        target,
+        reachable: path_mtu.is_some(),
+        path_mtu,
+        probes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A path that passes payloads up to `limit`, with every probe size recorded
+    fn search(max_payload: u32, limit: Option<u32>) -> (Option<u32>, Vec<u32>) {
+        let mut sent = Vec::new();
+        let (mtu, probes) = search_path_mtu(max_payload, |size| {
+            sent.push(size);
+            limit.is_some_and(|limit| size <= limit)
+        });
+        assert_eq!(
+            probes.iter().map(|p| p.payload_size).collect::<Vec<_>>(),
+            sent
+        );
+        (mtu, sent)
+    }
+
+    #[test]
+    fn full_size_path_needs_two_probes() {
+        assert_eq!(
+            search(DEFAULT_MAX_PAYLOAD, Some(9000)),
+            (Some(1500), vec![MIN_PROBE_PAYLOAD, DEFAULT_MAX_PAYLOAD])
+        );
+    }
+
+    #[test]
+    fn finds_the_largest_passing_payload() {
+        // PPPoE (1492), a WireGuard tunnel (1420) and a path that barely passes the
+        // reachability probe
+        for mtu in [1492, 1420, 1280, 576, MIN_PROBE_PAYLOAD + ICMP_OVERHEAD + 1] {
+            let (found, sent) = search(DEFAULT_MAX_PAYLOAD, Some(mtu - ICMP_OVERHEAD));
+            assert_eq!(found, Some(mtu));
+            // Binary search over at most 1408 sizes
+            assert!(sent.len() <= 2 + 11, "{} probes for {}", sent.len(), mtu);
+        }
+        let (found, _) = search(DEFAULT_MAX_PAYLOAD, Some(MIN_PROBE_PAYLOAD));
+        assert_eq!(found, Some(MIN_PROBE_PAYLOAD + ICMP_OVERHEAD));
+    }
+
+    #[test]
+    fn respects_a_custom_maximum() {
+        assert_eq!(search(9000 - ICMP_OVERHEAD, Some(9000)).0, Some(9000));
+        assert_eq!(search(1400, Some(1472)).0, Some(1428));
+        assert_eq!(search(1400, Some(1000)).0, Some(1028));
+    }
+
+    #[test]
+    fn small_maximum_is_not_exceeded() {
+        for max_payload in [MIN_PROBE_PAYLOAD, 40, 0] {
+            let (found, sent) = search(max_payload, Some(1472));
+            assert_eq!(found, Some(max_payload + ICMP_OVERHEAD));
+            assert_eq!(sent, [max_payload]);
+        }
+    }
+
+    #[test]
+    fn unreachable_target() {
+        assert_eq!(
+            search(DEFAULT_MAX_PAYLOAD, None),
+            (None, vec![MIN_PROBE_PAYLOAD])
+        );
+        assert_eq!(search(40, None), (None, vec![40]));
+    }
+
+    #[test]
+    fn parses_networksetup_output() {
+        assert_eq!(
+            parse_get_mtu("en0", "Active MTU: 1500 (Current Setting: 1400)\n"),
+            Some(MtuInfo {
+                hardware_port: "en0".to_string(),
+                active: 1500,
+                current_setting: Some(1400),
+            })
+        );
+        assert_eq!(
+            parse_get_mtu("utun3", "Active MTU: 1380\n").map(|m| (m.active, m.current_setting)),
+            Some((1380, None))
+        );
+        assert_eq!(
+            parse_get_mtu(
+                "Wi-Fi",
+                "Could not find hardware port or device named Wi-Fi.\n"
+            ),
+            None
+        );
+
+        assert_eq!(
+            parse_valid_mtu_range("Valid MTU Range: 1280-9000\n"),
+            Some(MtuRange {
+                min: 1280,
+                max: 9000
+            })
+        );
+        assert_eq!(parse_valid_mtu_range("Valid MTU Range: 1500\n"), None);
+        assert_eq!(parse_valid_mtu_range(""), None);
+    }
+}
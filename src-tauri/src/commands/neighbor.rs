@@ -0,0 +1,440 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+use std::thread::sleep;
+use std::time::Duration;
+
+use super::network::{get_default_route, run_with_admin_privileges};
+use super::runner::{system_runner, CommandRunner, SystemRunner};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum NeighborProtocol {
+    Arp,
+    Ndp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NeighborEntry {
+    pub protocol: NeighborProtocol,
+    pub ip_address: String,
+    /// Link-layer address, `None` while resolution is incomplete
+    pub mac_address: Option<String>,
+    pub interface: Option<String>,
+    pub expire: Option<String>,
+    pub permanent: bool,
+    /// NDP reachability state such as R (reachable) or S (stale)
+    pub state: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SpoofKind {
+    /// One IP address answered from several MAC addresses
+    DuplicateIp,
+    /// The default gateway's MAC address changed between samples
+    GatewayMacChanged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpoofAlert {
+    pub kind: SpoofKind,
+    pub ip_address: String,
+    pub interface: Option<String>,
+    pub mac_addresses: Vec<String>,
+    pub message: String,
+}
+
+/// Lowercase, zero-padded MAC so "0:1a:2b:3c:4d:5e" matches "00:1a:2b:3c:4d:5e"
+pub fn normalize_mac(mac: &str) -> String {
+    mac.split(':')
+        .map(|octet| format!("{:0>2}", octet.to_ascii_lowercase()))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn link_address(value: &str) -> Option<String> {
+    (value.contains(':') && !value.starts_with('(')).then(|| normalize_mac(value))
+}
+
+/// Parse `arp -an` output, e.g.
+/// "? (192.168.1.1) at a0:b1:c2:d3:e4:f5 on en0 ifscope [ethernet]"
+pub fn parse_arp(output: &str) -> Vec<NeighborEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let ip_address = tokens
+                .iter()
+                .find(|t| t.starts_with('(') && t.ends_with(')'))?
+                .trim_matches(|c| c == '(' || c == ')')
+                .to_string();
+            let after = |key: &str| {
+                tokens
+                    .iter()
+                    .position(|t| *t == key)
+                    .and_then(|i| tokens.get(i + 1).copied())
+            };
+
+            Some(NeighborEntry {
+                protocol: NeighborProtocol::Arp,
+                ip_address,
+                mac_address: after("at").and_then(link_address),
+                interface: after("on").map(|s| s.to_string()),
+                expire: after("in").map(|seconds| format!("{}s", seconds)),
+                permanent: tokens.contains(&"permanent"),
+                state: None,
+            })
+        })
+        .collect()
+}
+
+/// Parse `ndp -an` output, a table of
+/// "Neighbor  Linklayer Address  Netif  Expire  St  Flgs  Prbs"
+pub fn parse_ndp(output: &str) -> Vec<NeighborEntry> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with("Neighbor"))
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens.len() < 4 {
+                return None;
+            }
+            let ip_address = tokens[0].split('%').next().unwrap_or(tokens[0]);
+            ip_address.parse::<IpAddr>().ok()?;
+
+            Some(NeighborEntry {
+                protocol: NeighborProtocol::Ndp,
+                ip_address: ip_address.to_string(),
+                mac_address: link_address(tokens[1]),
+                interface: Some(tokens[2].to_string()),
+                expire: Some(tokens[3].to_string()).filter(|e| e != "permanent"),
+                permanent: tokens[3] == "permanent",
+                state: tokens.get(4).map(|s| s.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Look for spoofing across successive neighbour table samples: an IP seen with
+/// several MAC addresses, or a gateway whose MAC changes from one sample to the next
+pub fn detect_spoofing(samples: &[Vec<NeighborEntry>], gateway: Option<&str>) -> Vec<SpoofAlert> {
+    let mut alerts = Vec::new();
+    let mut macs_by_ip: BTreeMap<(String, Option<String>), BTreeSet<String>> = BTreeMap::new();
+    let mut gateway_macs: Vec<(Option<String>, String)> = Vec::new();
+
+    for sample in samples {
+        for entry in sample {
+            let Some(mac) = entry.mac_address.as_ref() else {
+                continue;
+            };
+            if entry.permanent || mac == "ff:ff:ff:ff:ff:ff" {
+                continue;
+            }
+
+            if Some(entry.ip_address.as_str()) == gateway {
+                if gateway_macs.last().map(|(_, last)| last) != Some(mac) {
+                    gateway_macs.push((entry.interface.clone(), mac.clone()));
+                }
+            } else {
+                macs_by_ip
+                    .entry((entry.ip_address.clone(), entry.interface.clone()))
+                    .or_default()
+                    .insert(mac.clone());
+            }
+        }
+    }
+
+    if let (Some(gateway), Some((interface, _))) = (gateway, gateway_macs.first()) {
+        if gateway_macs.len() > 1 {
+            let mac_addresses: Vec<String> = gateway_macs.iter().map(|(_, m)| m.clone()).collect();
+            alerts.push(SpoofAlert {
+                kind: SpoofKind::GatewayMacChanged,
+                ip_address: gateway.to_string(),
+                interface: interface.clone(),
+                message: format!(
+                    "Gateway {} changed MAC address {} time(s): {}",
+                    gateway,
+                    mac_addresses.len() - 1,
+                    mac_addresses.join(" -> ")
+                ),
+                mac_addresses,
+            });
+        }
+    }
+
+    for ((ip_address, interface), macs) in macs_by_ip {
+        if macs.len() > 1 {
+            let mac_addresses: Vec<String> = macs.into_iter().collect();
+            alerts.push(SpoofAlert {
+                kind: SpoofKind::DuplicateIp,
+                message: format!(
+                    "{} is claimed by {} MAC addresses: {}",
+                    ip_address,
+                    mac_addresses.len(),
+                    mac_addresses.join(", ")
+                ),
+                ip_address,
+                interface,
+                mac_addresses,
+            });
+        }
+    }
+
+    alerts
+}
+
+fn read_neighbors(runner: &dyn CommandRunner) -> Result<Vec<NeighborEntry>, String> {
+    let mut entries = parse_arp(&runner.run("arp", &["-an"])?);
+    // ndp is missing on systems without IPv6, the ARP table is still useful
+    if let Ok(output) = runner.run("ndp", &["-an"]) {
+        entries.extend(parse_ndp(&output));
+    }
+
+    Ok(entries)
+}
+
+#[tauri::command]
+pub fn list_neighbors() -> Result<Vec<NeighborEntry>, String> {
+    read_neighbors(system_runner())
+}
+
+/// Remove one entry from the ARP or NDP table instead of flushing everything
+#[tauri::command]
+pub fn delete_neighbor(ip_address: String, interface: Option<String>) -> Result<String, String> {
+    let ip: IpAddr = ip_address
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid IP address", ip_address))?;
+    let interface = interface.filter(|i| !i.is_empty());
+    if let Some(interface) = &interface {
+        if !interface.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("Invalid interface name '{}'", interface));
+        }
+    }
+
+    let command = match (ip, &interface) {
+        (IpAddr::V4(ip), Some(interface)) => format!("arp -d {} ifscope {}", ip, interface),
+        (IpAddr::V4(ip), None) => format!("arp -d {}", ip),
+        // Link-local neighbours are only unique together with their zone
+        (IpAddr::V6(ip), Some(interface)) if ip.segments()[0] & 0xffc0 == 0xfe80 => {
+            format!("ndp -d {}%{}", ip, interface)
+        }
+        (IpAddr::V6(ip), _) => format!("ndp -d {}", ip),
+    };
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!("Neighbor entry {} deleted", ip))
+}
+
+/// Read the neighbour tables `count` times, `interval` apart
+fn sample_neighbors(
+    runner: &dyn CommandRunner,
+    count: u32,
+    interval: Duration,
+) -> Result<Vec<Vec<NeighborEntry>>, String> {
+    let mut tables = Vec::new();
+    for index in 0..count {
+        if index > 0 {
+            sleep(interval);
+        }
+        tables.push(read_neighbors(runner)?);
+    }
+
+    Ok(tables)
+}
+
+/// Sample the neighbour tables several times and report duplicate IPs or a
+/// changing gateway MAC. Async so the sleeps between samples run off the main thread.
+#[tauri::command(async)]
+pub fn detect_arp_spoofing(
+    samples: Option<u32>,
+    interval_ms: Option<u64>,
+) -> Result<Vec<SpoofAlert>, String> {
+    let count = samples.unwrap_or(3).clamp(1, 20);
+    let interval = Duration::from_millis(interval_ms.unwrap_or(1000).min(10_000));
+    let gateway = get_default_route().and_then(|route| route.gateway);
+
+    // Sample without the shared cache, every sample must be a fresh read
+    let tables = sample_neighbors(&SystemRunner, count, interval)?;
+
+    Ok(detect_spoofing(&tables, gateway.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    const ARP: &str = "\
+? (192.168.1.1) at a0:b1:c2:d3:e4:f5 on en0 ifscope [ethernet]
+? (192.168.1.23) at 0:1a:2b:3c:4d:5e on en0 ifscope [ethernet]
+? (192.168.1.40) at (incomplete) on en0 ifscope [ethernet]
+? (192.168.1.255) at ff:ff:ff:ff:ff:ff on en0 ifscope [ethernet]
+? (224.0.0.251) at 1:0:5e:0:0:fb on en0 ifscope permanent [ethernet]
+? (10.8.0.1) at 52:54:0:12:34:56 on en7 expires in 1170 seconds [ethernet]
+";
+
+    const NDP: &str = "\
+Neighbor                             Linklayer Address  Netif Expire    St Flgs Prbs
+fe80::1%en0                          a0:b1:c2:d3:e4:f5    en0 23h59m58s S  R
+fe80::aede:48ff:fe00:1122%en0        ac:de:48:0:11:22     en0 permanent R
+2001:db8::1                          (incomplete)         en0 expired   I
+";
+
+    fn entry(ip: &str, mac: &str) -> NeighborEntry {
+        NeighborEntry {
+            protocol: NeighborProtocol::Arp,
+            ip_address: ip.to_string(),
+            mac_address: Some(mac.to_string()),
+            interface: Some("en0".to_string()),
+            expire: None,
+            permanent: false,
+            state: None,
+        }
+    }
+
+    /// Hands out one queued `arp -an` table per call, with `ndp` unavailable
+    struct Tables(Mutex<VecDeque<&'static str>>);
+
+    impl CommandRunner for Tables {
+        fn run(&self, program: &str, _args: &[&str]) -> Result<String, String> {
+            match program {
+                "arp" => self
+                    .0
+                    .lock()
+                    .unwrap()
+                    .pop_front()
+                    .map(str::to_string)
+                    .ok_or("no more tables".to_string()),
+                _ => Err("ndp: command not found".to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn parses_arp_table() {
+        let entries = parse_arp(ARP);
+        assert_eq!(entries.len(), 6);
+
+        assert_eq!(entries[0], entry("192.168.1.1", "a0:b1:c2:d3:e4:f5"));
+        assert_eq!(entries[1].mac_address.as_deref(), Some("00:1a:2b:3c:4d:5e"));
+        assert_eq!(entries[2].mac_address, None);
+        assert!(entries[4].permanent);
+        assert_eq!(entries[4].mac_address.as_deref(), Some("01:00:5e:00:00:fb"));
+        assert_eq!(entries[5].interface.as_deref(), Some("en7"));
+        assert_eq!(entries[5].expire.as_deref(), Some("1170s"));
+        assert!(parse_arp("arp: no entries\n").is_empty());
+    }
+
+    #[test]
+    fn parses_ndp_table() {
+        let entries = parse_ndp(NDP);
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].protocol, NeighborProtocol::Ndp);
+        assert_eq!(entries[0].ip_address, "fe80::1");
+        assert_eq!(entries[0].mac_address.as_deref(), Some("a0:b1:c2:d3:e4:f5"));
+        assert_eq!(entries[0].expire.as_deref(), Some("23h59m58s"));
+        assert_eq!(entries[0].state.as_deref(), Some("S"));
+        assert!(entries[1].permanent);
+        assert_eq!(entries[1].expire, None);
+        assert_eq!(entries[1].mac_address.as_deref(), Some("ac:de:48:00:11:22"));
+        assert_eq!(entries[2].mac_address, None);
+        assert_eq!(entries[2].state.as_deref(), Some("I"));
+    }
+
+    #[test]
+    fn stable_tables_raise_no_alert() {
+        let sample = parse_arp(ARP);
+        let alerts = detect_spoofing(&[sample.clone(), sample], Some("192.168.1.1"));
+        assert!(alerts.is_empty());
+    }
+
+    #[test]
+    fn gateway_mac_change_is_reported() {
+        let samples = [
+            vec![entry("192.168.1.1", "a0:b1:c2:d3:e4:f5")],
+            vec![entry("192.168.1.1", "de:ad:be:ef:00:01")],
+            vec![entry("192.168.1.1", "de:ad:be:ef:00:01")],
+        ];
+        let alerts = detect_spoofing(&samples, Some("192.168.1.1"));
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, SpoofKind::GatewayMacChanged);
+        assert_eq!(
+            alerts[0].mac_addresses,
+            ["a0:b1:c2:d3:e4:f5", "de:ad:be:ef:00:01"]
+        );
+        assert_eq!(
+            alerts[0].message,
+            "Gateway 192.168.1.1 changed MAC address 1 time(s): \
+             a0:b1:c2:d3:e4:f5 -> de:ad:be:ef:00:01"
+        );
+        // Without a known gateway the same change is a duplicate IP
+        let alerts = detect_spoofing(&samples, None);
+        assert_eq!(alerts[0].kind, SpoofKind::DuplicateIp);
+    }
+
+    #[test]
+    fn duplicate_ip_is_reported_per_interface() {
+        let mut other_interface = entry("192.168.1.23", "22:22:22:22:22:22");
+        other_interface.interface = Some("en1".to_string());
+        let samples = [
+            vec![entry("192.168.1.23", "11:11:11:11:11:11"), other_interface],
+            vec![entry("192.168.1.23", "33:33:33:33:33:33")],
+        ];
+        let alerts = detect_spoofing(&samples, Some("192.168.1.1"));
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, SpoofKind::DuplicateIp);
+        assert_eq!(alerts[0].interface.as_deref(), Some("en0"));
+        assert_eq!(
+            alerts[0].mac_addresses,
+            ["11:11:11:11:11:11", "33:33:33:33:33:33"]
+        );
+    }
+
+    #[test]
+    fn permanent_broadcast_and_incomplete_entries_are_ignored() {
+        let mut permanent = entry("192.168.1.50", "44:44:44:44:44:44");
+        permanent.permanent = true;
+        let mut incomplete = entry("192.168.1.50", "");
+        incomplete.mac_address = None;
+        let samples = [
+            vec![
+                entry("192.168.1.50", "55:55:55:55:55:55"),
+                entry("192.168.1.255", "ff:ff:ff:ff:ff:ff"),
+            ],
+            vec![
+                permanent,
+                incomplete,
+                entry("192.168.1.255", "ff:ff:ff:ff:ff:ff"),
+            ],
+        ];
+        assert!(detect_spoofing(&samples, None).is_empty());
+    }
+
+    #[test]
+    fn samples_are_spaced_by_the_interval() {
+        let runner = Tables(Mutex::new(VecDeque::from([
+            "? (192.168.1.1) at a0:b1:c2:d3:e4:f5 on en0 ifscope [ethernet]\n",
+            "? (192.168.1.1) at de:ad:be:ef:00:01 on en0 ifscope [ethernet]\n",
+        ])));
+        let started = Instant::now();
+        let tables = sample_neighbors(&runner, 2, Duration::from_millis(50)).unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert_eq!(tables.len(), 2);
+        let alerts = detect_spoofing(&tables, Some("192.168.1.1"));
+        assert_eq!(alerts[0].kind, SpoofKind::GatewayMacChanged);
+
+        // A failed read aborts the sampling
+        let runner = Tables(Mutex::new(VecDeque::from([ARP])));
+        assert_eq!(
+            sample_neighbors(&runner, 2, Duration::ZERO).unwrap_err(),
+            "no more tables"
+        );
+    }
+}
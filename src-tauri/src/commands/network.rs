@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::process::Command;
 
+use super::interface::{get_interface_details, parse_ifconfig, InterfaceDetails};
+use super::location::current_location_with;
+use super::runner::{invalidate_command_cache, system_runner, CommandRunner};
+use super::vpn::is_vpn_port;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum InterfaceStatus {
     Connected,
@@ -17,8 +22,21 @@ pub struct NetworkService {
     pub device: String,
     pub enabled: bool,
     pub is_default: bool,
+    /// The service's device carries the routing table's default route
+    pub is_primary: bool,
+    /// Why this service was passed over for a lower-ordered default service
+    pub skip_reason: Option<String>,
+    pub is_vpn: bool,
     pub order: i32,
     pub status: InterfaceStatus,
+    pub location: Option<String>,
+    pub details: Option<InterfaceDetails>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DefaultRoute {
+    pub gateway: Option<String>,
+    pub interface: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,8 +46,14 @@ pub struct HardwarePort {
     pub in_use: bool,
 }
 
+/// Quote a value for `sh` in single quotes, so `$`, backticks and quotes in names
+/// reach the command unchanged
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
 /// Execute a shell command with administrator privileges using AppleScript
-fn run_with_admin_privileges(command: &str) -> Result<String, String> {
+pub(crate) fn run_with_admin_privileges(command: &str) -> Result<String, String> {
     // Create a temporary script file
     let temp_dir = std::env::temp_dir();
     let script_path = temp_dir.join("mac_net_repair_admin.scpt");
@@ -76,6 +100,9 @@ end try"#,
     // Clean up script file
     let _ = std::fs::remove_file(&script_path);
 
+    // The command may have changed the configuration even if it reported an error
+    invalidate_command_cache();
+
     if output.status.success() {
         // Read result from file
         let result = std::fs::read_to_string(&result_path)
@@ -98,56 +125,69 @@ end try"#,
     }
 }
 
+/// Parse `route -n get default` output into the gateway and outgoing interface
+pub fn parse_default_route(output: &str) -> Option<DefaultRoute> {
+    let mut gateway = None;
+    let mut interface = None;
+
+    for line in output.lines() {
+        if let Some((key, value)) = line.trim().split_once(':') {
+            let value = value.trim().to_string();
+            match key.trim() {
+                "gateway" if !value.is_empty() => gateway = Some(value),
+                "interface" if !value.is_empty() => interface = Some(value),
+                _ => {}
+            }
+        }
+    }
+
+    interface.map(|interface| DefaultRoute { gateway, interface })
+}
+
+/// Look up the current IPv4 default route
+pub(crate) fn get_default_route() -> Option<DefaultRoute> {
+    default_route_with(system_runner())
+}
+
+fn default_route_with(runner: &dyn CommandRunner) -> Option<DefaultRoute> {
+    let stdout = runner.run("route", &["-n", "get", "default"]).ok()?;
+
+    parse_default_route(&stdout)
+}
+
 /// Get the status of a network interface using ifconfig
-fn get_interface_status(device: &str) -> InterfaceStatus {
+pub(crate) fn get_interface_status(device: &str) -> InterfaceStatus {
     if device.is_empty() {
         return InterfaceStatus::Unavailable;
     }
 
-    let output = Command::new("ifconfig").arg(device).output();
-
-    match output {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            if stdout.contains("status: active") {
-                InterfaceStatus::Connected
-            } else if stdout.contains("status: inactive") {
-                InterfaceStatus::Disconnected
-            } else if stdout.contains("flags=") {
-                // Interface exists but status unknown, check if it has an IP
-                if stdout.contains("inet ") && !stdout.contains("inet 127.") {
-                    InterfaceStatus::Connected
-                } else {
-                    InterfaceStatus::Disconnected
-                }
-            } else {
-                InterfaceStatus::Unavailable
-            }
-        }
+    match get_interface_details(device.to_string()) {
+        Ok(details) => details.link_status(),
         Err(_) => InterfaceStatus::Unavailable,
     }
 }
 
-#[tauri::command]
-pub fn get_network_services() -> Result<Vec<NetworkService>, String> {
-    // Get actual network services from listnetworkserviceorder
-    // This returns only configured services, not all hardware ports
-    let output = Command::new("networksetup")
-        .arg("-listnetworkserviceorder")
-        .output()
-        .map_err(|e| format!("Failed to execute command: {}", e))?;
+/// A service entry from `networksetup -listnetworkserviceorder`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceOrderEntry {
+    pub name: String,
+    pub enabled: bool,
+    pub hardware_port: String,
+    pub device: String,
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut services = Vec::new();
+/// Parse `networksetup -listnetworkserviceorder` output into services with their ports
+pub fn parse_service_order(output: &str) -> Vec<ServiceOrderEntry> {
+    let mut entries = Vec::new();
     let mut current_service: Option<(String, bool)> = None;
 
-    for line in stdout.lines() {
+    for line in output.lines() {
         let line = line.trim();
 
         // Check for hardware port line first: "(Hardware Port: Wi-Fi, Device: en1)"
         // Must check this before service line since both start with '('
         if line.starts_with("(Hardware Port:") && current_service.is_some() {
-            let (service_name, enabled) = current_service.take().unwrap();
+            let (name, enabled) = current_service.take().unwrap();
 
             // Parse hardware port and device
             let inner = line
@@ -156,18 +196,11 @@ pub fn get_network_services() -> Result<Vec<NetworkService>, String> {
             let parts: Vec<&str> = inner.split(", Device:").collect();
 
             if parts.len() == 2 {
-                let hardware_port = parts[0].trim().to_string();
-                let device = parts[1].trim().to_string();
-                let status = get_interface_status(&device);
-
-                services.push(NetworkService {
-                    name: service_name,
-                    hardware_port,
-                    device,
+                entries.push(ServiceOrderEntry {
+                    name,
                     enabled,
-                    is_default: services.is_empty(), // First service is default
-                    order: services.len() as i32,
-                    status,
+                    hardware_port: parts[0].trim().to_string(),
+                    device: parts[1].trim().to_string(),
                 });
             }
         }
@@ -186,16 +219,138 @@ pub fn get_network_services() -> Result<Vec<NetworkService>, String> {
         }
     }
 
+    entries
+}
+
+/// Why a service cannot carry default traffic, if anything
+fn unusable_reason(service: &NetworkService) -> Option<String> {
+    if !service.enabled {
+        return Some("Service is disabled".to_string());
+    }
+    if service.is_vpn {
+        return Some("Service is a VPN".to_string());
+    }
+    if service.device.is_empty() {
+        return Some("Service has no hardware device".to_string());
+    }
+
+    match service.status {
+        InterfaceStatus::Unavailable => Some(format!("Device {} is not present", service.device)),
+        InterfaceStatus::Disconnected => {
+            Some(format!("Device {} is not connected", service.device))
+        }
+        InterfaceStatus::Connected => {
+            let has_ipv4 = service
+                .details
+                .as_ref()
+                .is_some_and(|details| !details.ipv4.is_empty());
+            if has_ipv4 {
+                None
+            } else {
+                Some(format!("Device {} has no IPv4 address", service.device))
+            }
+        }
+    }
+}
+
+/// Mark the primary and default services.
+///
+/// The primary service owns `primary_interface`, the outgoing interface of the
+/// default route. The default is the primary service, or the first usable service
+/// in the service order when the route goes elsewhere (e.g. a VPN tunnel). Services
+/// ordered above the default record why they were skipped.
+pub fn assign_default_service(services: &mut [NetworkService], primary_interface: Option<&str>) {
+    let primary = primary_interface.and_then(|interface| {
+        services
+            .iter()
+            .position(|s| !s.device.is_empty() && s.device == interface)
+    });
+    let default = primary.or_else(|| services.iter().position(|s| unusable_reason(s).is_none()));
+
+    for (index, service) in services.iter_mut().enumerate() {
+        service.is_primary = Some(index) == primary;
+        service.is_default = Some(index) == default;
+        service.skip_reason = match default {
+            Some(default) if index >= default => None,
+            Some(_) => Some(unusable_reason(service).unwrap_or_else(|| {
+                format!(
+                    "Default route uses {}",
+                    primary_interface.unwrap_or("another interface")
+                )
+            })),
+            None => unusable_reason(service),
+        };
+    }
+}
+
+/// Gather the service order, `ifconfig -a`, the location and the default route
+/// concurrently and merge them by device, so the number of commands does not grow
+/// with the number of services
+pub fn collect_network_services(runner: &dyn CommandRunner) -> Result<Vec<NetworkService>, String> {
+    let (order, interfaces, location, route) = std::thread::scope(|scope| {
+        let order = scope.spawn(|| runner.run("networksetup", &["-listnetworkserviceorder"]));
+        let interfaces = scope.spawn(|| runner.run("ifconfig", &["-a"]));
+        let location = scope.spawn(|| current_location_with(runner));
+        let route = scope.spawn(|| default_route_with(runner));
+        (
+            order
+                .join()
+                .unwrap_or_else(|_| Err("Service probe panicked".to_string())),
+            interfaces
+                .join()
+                .unwrap_or_else(|_| Err("Interface probe panicked".to_string())),
+            location.join().unwrap_or(None),
+            route.join().unwrap_or(None),
+        )
+    });
+
+    // This returns only configured services, not all hardware ports
+    let entries = parse_service_order(&order?);
+    let interfaces: HashMap<String, InterfaceDetails> = parse_ifconfig(&interfaces?)
+        .into_iter()
+        .map(|details| (details.name.clone(), details))
+        .collect();
+
+    let mut services: Vec<NetworkService> = entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let details = interfaces.get(&entry.device).cloned();
+            let status = details
+                .as_ref()
+                .map(|d| d.link_status())
+                .unwrap_or(InterfaceStatus::Unavailable);
+            let is_vpn = is_vpn_port(&entry.hardware_port, &entry.device);
+
+            NetworkService {
+                name: entry.name,
+                hardware_port: entry.hardware_port,
+                device: entry.device,
+                enabled: entry.enabled,
+                is_default: false,
+                is_primary: false,
+                skip_reason: None,
+                is_vpn,
+                order: index as i32,
+                status,
+                location: location.clone(),
+                details,
+            }
+        })
+        .collect();
+
+    assign_default_service(&mut services, route.as_ref().map(|r| r.interface.as_str()));
+
     Ok(services)
 }
 
-fn get_service_order_internal() -> Result<Vec<String>, String> {
-    let output = Command::new("networksetup")
-        .arg("-listnetworkserviceorder")
-        .output()
-        .map_err(|e| format!("Failed to execute command: {}", e))?;
+#[tauri::command]
+pub fn get_network_services() -> Result<Vec<NetworkService>, String> {
+    collect_network_services(system_runner())
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+pub(crate) fn get_service_order_internal() -> Result<Vec<String>, String> {
+    let stdout = system_runner().run("networksetup", &["-listnetworkserviceorder"])?;
     let mut services = Vec::new();
 
     for line in stdout.lines() {
@@ -218,24 +373,22 @@ pub fn get_service_order() -> Result<Vec<String>, String> {
     get_service_order_internal()
 }
 
+/// `networksetup -ordernetworkservices` with every service name single-quoted
+fn service_order_command(services: &[String]) -> String {
+    let service_args: Vec<String> = services.iter().map(|s| shell_quote(s)).collect();
+    format!(
+        "networksetup -ordernetworkservices {}",
+        service_args.join(" ")
+    )
+}
+
 #[tauri::command]
 pub fn set_service_order(services: Vec<String>) -> Result<String, String> {
     if services.is_empty() {
         return Err("Service list cannot be empty".to_string());
     }
 
-    // Build the command with properly escaped service names
-    let service_args: Vec<String> = services
-        .iter()
-        .map(|s| format!("\"{}\"", s.replace("\"", "\\\"")))
-        .collect();
-
-    let command = format!(
-        "networksetup -ordernetworkservices {}",
-        service_args.join(" ")
-    );
-
-    run_with_admin_privileges(&command)?;
+    run_with_admin_privileges(&service_order_command(&services))?;
 
     Ok("Service order updated successfully".to_string())
 }
@@ -341,3 +494,435 @@ pub fn remove_network_service(name: String) -> Result<String, String> {
 
     Ok(format!("Network service '{}' removed successfully", name))
 }
+
+#[tauri::command]
+pub fn set_service_enabled(name: String, enabled: bool) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("Service name cannot be empty".to_string());
+    }
+
+    if let Ok(existing) = get_service_order_internal() {
+        if !existing.iter().any(|s| s == &name) {
+            return Err(format!("Network service '{}' does not exist", name));
+        }
+    }
+
+    let command = format!(
+        "networksetup -setnetworkserviceenabled {} {}",
+        shell_quote(&name),
+        if enabled { "on" } else { "off" }
+    );
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!(
+        "Network service '{}' {}",
+        name,
+        if enabled { "enabled" } else { "disabled" }
+    ))
+}
+
+/// Disable and re-enable a service to unstick a wedged interface
+#[tauri::command(async)]
+pub fn bounce_network_service(name: String, delay_ms: Option<u64>) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("Service name cannot be empty".to_string());
+    }
+
+    if let Ok(existing) = get_service_order_internal() {
+        if !existing.iter().any(|s| s == &name) {
+            return Err(format!("Network service '{}' does not exist", name));
+        }
+    }
+
+    // One privileged script so the user only authorizes once
+    let delay = delay_ms.unwrap_or(2000) as f64 / 1000.0;
+    let toggle = |state: &str| {
+        format!(
+            "networksetup -setnetworkserviceenabled {} {}",
+            shell_quote(&name),
+            state
+        )
+    };
+    let command = format!("{} && sleep {} && {}", toggle("off"), delay, toggle("on"));
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!("Network service '{}' restarted", name))
+}
+
+/// Rename a service, then restore the service order so the renamed service keeps
+/// its current position
+fn rename_service_command(existing: &[String], name: &str, new_name: &str) -> String {
+    let order: Vec<String> = existing
+        .iter()
+        .map(|s| {
+            if s == name {
+                new_name.to_string()
+            } else {
+                s.clone()
+            }
+        })
+        .collect();
+
+    format!(
+        "networksetup -renamenetworkservice {} {} && {}",
+        shell_quote(name),
+        shell_quote(new_name),
+        service_order_command(&order)
+    )
+}
+
+#[tauri::command]
+pub fn rename_network_service(name: String, new_name: String) -> Result<String, String> {
+    if name.is_empty() || new_name.is_empty() {
+        return Err("Service name cannot be empty".to_string());
+    }
+    if name == new_name {
+        return Ok(format!("Network service '{}' unchanged", name));
+    }
+
+    let existing = get_service_order_internal()?;
+    if !existing.iter().any(|s| s == &name) {
+        return Err(format!("Network service '{}' does not exist", name));
+    }
+    if existing.iter().any(|s| s == &new_name) {
+        return Err("Service name already exists".to_string());
+    }
+
+    run_with_admin_privileges(&rename_service_command(&existing, &name, &new_name))?;
+
+    Ok(format!(
+        "Network service '{}' renamed to '{}'",
+        name, new_name
+    ))
+}
+
+#[tauri::command]
+pub fn duplicate_network_service(name: String, new_name: String) -> Result<String, String> {
+    if name.is_empty() || new_name.is_empty() {
+        return Err("Service name cannot be empty".to_string());
+    }
+
+    if let Ok(existing) = get_service_order_internal() {
+        if !existing.iter().any(|s| s == &name) {
+            return Err(format!("Network service '{}' does not exist", name));
+        }
+        if existing.iter().any(|s| s == &new_name) {
+            return Err("Service name already exists".to_string());
+        }
+    }
+
+    let command = format!(
+        "networksetup -duplicatenetworkservice {} {}",
+        shell_quote(&name),
+        shell_quote(&new_name)
+    );
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!(
+        "Network service '{}' duplicated as '{}'",
+        name, new_name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::interface::Ipv4Address;
+    use super::*;
+
+    const SERVICE_ORDER: &str = "\
+An asterisk (*) denotes that a network service is disabled.
+(1) USB 10/100/1000 LAN
+(Hardware Port: USB 10/100/1000 LAN, Device: en7)
+
+(2) Wi-Fi
+(Hardware Port: Wi-Fi, Device: en0)
+
+(3) Corp VPN
+(Hardware Port: com.wireguard.macos, Device: )
+
+(*) Thunderbolt Bridge
+(Hardware Port: Thunderbolt Bridge, Device: bridge0)
+";
+
+    const IFCONFIG: &str = "\
+en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+\tether a4:83:e7:00:00:01
+\tinet 192.168.1.20 netmask 0xffffff00 broadcast 192.168.1.255
+\tstatus: active
+en7: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+\tether 00:e0:4c:00:00:02
+\tstatus: inactive
+";
+
+    struct Probes {
+        route: Option<&'static str>,
+    }
+
+    impl CommandRunner for Probes {
+        fn run(&self, program: &str, args: &[&str]) -> Result<String, String> {
+            match (program, args) {
+                ("networksetup", ["-listnetworkserviceorder"]) => Ok(SERVICE_ORDER.to_string()),
+                ("networksetup", ["-getcurrentlocation"]) => Ok("Office\n".to_string()),
+                ("ifconfig", ["-a"]) => Ok(IFCONFIG.to_string()),
+                ("route", ["-n", "get", "default"]) => self
+                    .route
+                    .map(|interface| format!("gateway: 192.168.1.1\ninterface: {}\n", interface))
+                    .ok_or_else(|| "route: writing to routing socket: not in table".to_string()),
+                _ => Err(format!(
+                    "Unexpected command: {} {}",
+                    program,
+                    args.join(" ")
+                )),
+            }
+        }
+    }
+
+    fn service(
+        name: &str,
+        device: &str,
+        status: InterfaceStatus,
+        ipv4: Option<&str>,
+    ) -> NetworkService {
+        NetworkService {
+            name: name.to_string(),
+            hardware_port: name.to_string(),
+            device: device.to_string(),
+            enabled: true,
+            is_default: false,
+            is_primary: false,
+            skip_reason: None,
+            is_vpn: false,
+            order: 0,
+            status,
+            location: None,
+            details: Some(InterfaceDetails {
+                name: device.to_string(),
+                ipv4: ipv4
+                    .into_iter()
+                    .map(|address| Ipv4Address {
+                        address: address.to_string(),
+                        prefix_len: Some(24),
+                        broadcast: None,
+                        destination: None,
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn connected(name: &str, device: &str) -> NetworkService {
+        service(
+            name,
+            device,
+            InterfaceStatus::Connected,
+            Some("192.168.1.20"),
+        )
+    }
+
+    #[test]
+    fn explains_unusable_services() {
+        assert_eq!(unusable_reason(&connected("Wi-Fi", "en0")), None);
+
+        let mut disabled = connected("Wi-Fi", "en0");
+        disabled.enabled = false;
+        assert_eq!(
+            unusable_reason(&disabled).as_deref(),
+            Some("Service is disabled")
+        );
+
+        let mut vpn = connected("Corp VPN", "utun4");
+        vpn.is_vpn = true;
+        assert_eq!(unusable_reason(&vpn).as_deref(), Some("Service is a VPN"));
+
+        assert_eq!(
+            unusable_reason(&connected("Bluetooth PAN", "")).as_deref(),
+            Some("Service has no hardware device")
+        );
+        assert_eq!(
+            unusable_reason(&service("Dock", "en9", InterfaceStatus::Unavailable, None)).as_deref(),
+            Some("Device en9 is not present")
+        );
+        assert_eq!(
+            unusable_reason(&service("LAN", "en7", InterfaceStatus::Disconnected, None)).as_deref(),
+            Some("Device en7 is not connected")
+        );
+        assert_eq!(
+            unusable_reason(&service("LAN", "en7", InterfaceStatus::Connected, None)).as_deref(),
+            Some("Device en7 has no IPv4 address")
+        );
+
+        let mut without_details = connected("LAN", "en7");
+        without_details.details = None;
+        assert_eq!(
+            unusable_reason(&without_details).as_deref(),
+            Some("Device en7 has no IPv4 address")
+        );
+    }
+
+    #[test]
+    fn awdl_is_not_a_usable_service() {
+        let awdl = "\
+awdl0: flags=8943<UP,BROADCAST,RUNNING,PROMISC,SIMPLEX,MULTICAST> mtu 1500
+\tether 8e:2f:a1:0b:3c:4d
+\tinet6 fe80::8c2f:a1ff:fe0b:3c4d%awdl0 prefixlen 64 scopeid 0x10
+\tstatus: active
+";
+        let details = parse_ifconfig(awdl).pop().unwrap();
+        let mut service = service("AirDrop", "awdl0", details.link_status(), None);
+        service.details = Some(details);
+
+        assert_eq!(service.status, InterfaceStatus::Connected);
+        assert_eq!(
+            unusable_reason(&service).as_deref(),
+            Some("Device awdl0 has no IPv4 address")
+        );
+    }
+
+    #[test]
+    fn default_is_the_service_carrying_the_route() {
+        let mut services = [
+            service("LAN", "en7", InterfaceStatus::Disconnected, None),
+            connected("Wi-Fi", "en0"),
+            connected("iPhone USB", "en8"),
+        ];
+        assign_default_service(&mut services, Some("en0"));
+
+        assert!(services[1].is_primary && services[1].is_default);
+        assert!(!services[0].is_default && !services[2].is_default);
+        assert_eq!(
+            services[0].skip_reason.as_deref(),
+            Some("Device en7 is not connected")
+        );
+        assert_eq!(services[1].skip_reason, None);
+        // Services below the default are not skipped, just lower in the order
+        assert_eq!(services[2].skip_reason, None);
+    }
+
+    #[test]
+    fn usable_service_above_the_route_is_explained() {
+        let mut services = [connected("LAN", "en7"), connected("Wi-Fi", "en0")];
+        assign_default_service(&mut services, Some("en0"));
+
+        assert!(services[1].is_default);
+        assert_eq!(
+            services[0].skip_reason.as_deref(),
+            Some("Default route uses en0")
+        );
+    }
+
+    #[test]
+    fn route_through_a_tunnel_falls_back_to_the_service_order() {
+        let mut vpn = connected("Corp VPN", "utun4");
+        vpn.is_vpn = true;
+        let mut services = [
+            service("LAN", "en7", InterfaceStatus::Disconnected, None),
+            connected("Wi-Fi", "en0"),
+            vpn,
+        ];
+
+        // A VPN service that owns the route is both primary and default
+        assign_default_service(&mut services, Some("utun4"));
+        assert!(services[2].is_primary);
+        assert!(services[2].is_default);
+
+        // A tunnel without a service falls back to the first usable service
+        assign_default_service(&mut services, Some("utun9"));
+        assert!(services.iter().all(|s| !s.is_primary));
+        assert!(services[1].is_default);
+        assert_eq!(
+            services[0].skip_reason.as_deref(),
+            Some("Device en7 is not connected")
+        );
+
+        assign_default_service(&mut services, None);
+        assert!(services[1].is_default);
+    }
+
+    #[test]
+    fn no_usable_service_has_no_default() {
+        let mut disabled = connected("Wi-Fi", "en0");
+        disabled.enabled = false;
+        let mut services = [
+            service("LAN", "en7", InterfaceStatus::Unavailable, None),
+            disabled,
+        ];
+        assign_default_service(&mut services, None);
+
+        assert!(services.iter().all(|s| !s.is_default && !s.is_primary));
+        assert_eq!(
+            services[0].skip_reason.as_deref(),
+            Some("Device en7 is not present")
+        );
+        assert_eq!(
+            services[1].skip_reason.as_deref(),
+            Some("Service is disabled")
+        );
+    }
+
+    #[test]
+    fn collects_services_from_merged_probes() {
+        let services = collect_network_services(&Probes { route: Some("en0") }).unwrap();
+
+        let names: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "USB 10/100/1000 LAN",
+                "Wi-Fi",
+                "Corp VPN",
+                "Thunderbolt Bridge"
+            ]
+        );
+        assert_eq!(services[0].status, InterfaceStatus::Disconnected);
+        assert_eq!(
+            services[0].skip_reason.as_deref(),
+            Some("Device en7 is not connected")
+        );
+        assert_eq!(services[1].status, InterfaceStatus::Connected);
+        assert!(services[1].is_default && services[1].is_primary);
+        assert_eq!(
+            services[1].details.as_ref().unwrap().ipv4[0].address,
+            "192.168.1.20"
+        );
+        assert!(services[2].is_vpn);
+        assert_eq!(services[2].status, InterfaceStatus::Unavailable);
+        assert!(!services[3].enabled);
+        assert!(services
+            .iter()
+            .all(|s| s.location.as_deref() == Some("Office")));
+        assert_eq!(
+            services.iter().map(|s| s.order).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+
+        // Without a default route the first usable service is the default
+        let services = collect_network_services(&Probes { route: None }).unwrap();
+        assert!(services[1].is_default && !services[1].is_primary);
+    }
+
+    #[test]
+    fn rename_keeps_the_service_position() {
+        let existing: Vec<String> = ["Ethernet", "Wi-Fi", "Corp VPN"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            rename_service_command(&existing, "Wi-Fi", "Home $(reboot)"),
+            "networksetup -renamenetworkservice 'Wi-Fi' 'Home $(reboot)' && \
+             networksetup -ordernetworkservices 'Ethernet' 'Home $(reboot)' 'Corp VPN'"
+        );
+    }
+
+    #[test]
+    fn service_order_quotes_every_name() {
+        let services = vec!["Bob's iPhone".to_string(), "`id`".to_string()];
+        assert_eq!(
+            service_order_command(&services),
+            "networksetup -ordernetworkservices 'Bob'\\''s iPhone' '`id`'"
+        );
+    }
+}
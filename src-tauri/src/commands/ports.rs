@@ -0,0 +1,484 @@
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+use super::proxy::{get_proxy_settings, ProxySettings};
+use super::runner::{system_runner, CommandRunner};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ListenerFamily {
+    Ipv4,
+    Ipv6,
+    /// An IPv6 socket that also accepts IPv4 ("tcp46")
+    Dual,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Listener {
+    pub process: Option<String>,
+    pub pid: Option<u32>,
+    /// Only known for sockets lsof can see, i.e. the user's own processes
+    pub user: Option<String>,
+    pub family: ListenerFamily,
+    /// "*" for every address
+    pub address: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyListenerCheck {
+    /// "HTTP", "HTTPS" or "SOCKS"
+    pub kind: String,
+    pub server: String,
+    pub port: u16,
+    /// Whether the proxy runs on this Mac; only those can be checked
+    pub is_local: bool,
+    /// `None` for remote proxies
+    pub listening: Option<bool>,
+    pub listeners: Vec<Listener>,
+}
+
+/// "127.0.0.1:7890", "*:49152" or "[::1]:8080" -> (address, port)
+fn split_address(value: &str, separator: char) -> Option<(String, u16)> {
+    let (address, port) = value.rsplit_once(separator)?;
+    let address = address.trim_start_matches('[').trim_end_matches(']');
+
+    Some((address.to_string(), port.parse().ok()?))
+}
+
+/// Parse `lsof -nP -iTCP -sTCP:LISTEN`:
+///
+/// ```text
+/// COMMAND   PID USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+/// ClashX   1234 me     10u  IPv4 0x8f0e3c2d5a1b7c01      0t0  TCP 127.0.0.1:7890 (LISTEN)
+/// ```
+pub fn parse_lsof_listeners(output: &str) -> Vec<Listener> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens.len() < 9 || tokens.last() != Some(&"(LISTEN)") {
+                return None;
+            }
+            let (address, port) = split_address(tokens[tokens.len() - 2], ':')?;
+            let family = match tokens[4] {
+                "IPv4" => ListenerFamily::Ipv4,
+                "IPv6" => ListenerFamily::Ipv6,
+                _ => return None,
+            };
+
+            Some(Listener {
+                // lsof escapes spaces in command names
+                process: Some(tokens[0].replace("\\x20", " ")),
+                pid: tokens[1].parse().ok(),
+                user: Some(tokens[2].to_string()),
+                family,
+                address,
+                port,
+            })
+        })
+        .collect()
+}
+
+/// Parse the LISTEN sockets of `netstat -anv`. The pid column is "pid" on older
+/// releases and "process:pid" on newer ones, so it is located through the header.
+/// Process names may contain spaces, so the column is bounded by its position
+/// from both the start and the end of the row.
+pub fn parse_netstat_listeners(output: &str) -> Vec<Listener> {
+    // (columns before the pid column, columns after it)
+    let mut pid_column: Option<(usize, usize)> = None;
+    let mut listeners = Vec::new();
+
+    for line in output.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.first() == Some(&"Proto") {
+            // "Local Address" and "Foreign Address" are one column each in the rows
+            pid_column = tokens
+                .iter()
+                .position(|t| *t == "pid" || *t == "process:pid")
+                .and_then(|i| Some((i.checked_sub(2)?, tokens.len() - 1 - i)));
+            continue;
+        }
+        if tokens.len() < 6 || tokens[5] != "LISTEN" {
+            continue;
+        }
+
+        let family = match tokens[0] {
+            "tcp4" => ListenerFamily::Ipv4,
+            "tcp6" => ListenerFamily::Ipv6,
+            "tcp46" => ListenerFamily::Dual,
+            _ => continue,
+        };
+        let Some((address, port)) = split_address(tokens[3], '.') else {
+            continue;
+        };
+        let owner = pid_column
+            .and_then(|(before, after)| tokens.get(before..tokens.len().checked_sub(after)?))
+            .filter(|owner| !owner.is_empty())
+            .map(|owner| owner.join(" "));
+        let (process, pid) = match owner
+            .as_deref()
+            .map(|o| o.rsplit_once(':').unwrap_or(("", o)))
+        {
+            Some((name, pid)) => (
+                Some(name.to_string()).filter(|n| !n.is_empty()),
+                pid.parse().ok().filter(|pid| *pid != 0),
+            ),
+            None => (None, None),
+        };
+
+        listeners.push(Listener {
+            process,
+            pid,
+            user: None,
+            family,
+            address,
+            port,
+        });
+    }
+
+    listeners
+}
+
+/// Combine both sources. lsof names the process and user but only sees the user's
+/// own sockets; netstat sees every socket.
+pub fn merge_listeners(lsof: Vec<Listener>, netstat: Vec<Listener>) -> Vec<Listener> {
+    let mut merged: Vec<Listener> = Vec::new();
+
+    for listener in lsof.into_iter().chain(netstat) {
+        let existing = merged.iter_mut().find(|known| {
+            known.port == listener.port
+                && known.address == listener.address
+                && (known.pid == listener.pid || known.pid.is_none() || listener.pid.is_none())
+        });
+        match existing {
+            Some(known) => {
+                if known.pid.is_none() {
+                    known.pid = listener.pid;
+                }
+                if known.process.is_none() {
+                    known.process = listener.process;
+                }
+                if listener.family == ListenerFamily::Dual {
+                    known.family = ListenerFamily::Dual;
+                }
+            }
+            None => merged.push(listener),
+        }
+    }
+
+    merged.sort_by(|a, b| (a.port, &a.address).cmp(&(b.port, &b.address)));
+    merged
+}
+
+pub fn is_loopback_host(host: &str) -> bool {
+    host == "localhost" || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Whether a connection to `host` reaches `listener`. An IPv4 address only
+/// reaches IPv4 and dual-stack sockets, an IPv6 address only IPv6 and dual-stack
+/// ones; "localhost" may resolve to either.
+pub fn accepts_connections_to(listener: &Listener, host: &str) -> bool {
+    let family = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(_)) => listener.family != ListenerFamily::Ipv6,
+        Ok(IpAddr::V6(_)) => listener.family != ListenerFamily::Ipv4,
+        Err(_) => true,
+    };
+    if !family {
+        return false;
+    }
+
+    match listener.address.as_str() {
+        "*" | "0.0.0.0" | "::" => true,
+        address if host == "localhost" => address == "127.0.0.1" || address == "::1",
+        address => address == host,
+    }
+}
+
+/// Which of the enabled proxies on this Mac have a process listening on their port
+pub fn check_proxy_listeners(
+    settings: &ProxySettings,
+    listeners: &[Listener],
+) -> Vec<ProxyListenerCheck> {
+    [
+        ("HTTP", &settings.http_proxy),
+        ("HTTPS", &settings.https_proxy),
+        ("SOCKS", &settings.socks_proxy),
+    ]
+    .into_iter()
+    .filter(|(_, config)| config.enabled)
+    .map(|(kind, config)| {
+        let is_local = is_loopback_host(&config.server);
+        let matching: Vec<Listener> = listeners
+            .iter()
+            .filter(|l| is_local && l.port == config.port)
+            .filter(|l| accepts_connections_to(l, &config.server))
+            .cloned()
+            .collect();
+
+        ProxyListenerCheck {
+            kind: kind.to_string(),
+            server: config.server.clone(),
+            port: config.port,
+            is_local,
+            listening: is_local.then_some(!matching.is_empty()),
+            listeners: matching,
+        }
+    })
+    .collect()
+}
+
+pub(crate) fn collect_listeners(runner: &dyn CommandRunner) -> Result<Vec<Listener>, String> {
+    let lsof = runner.run("lsof", &["-nP", "-iTCP", "-sTCP:LISTEN"]);
+    let netstat = runner.run("netstat", &["-anv", "-p", "tcp"]);
+    if let (Err(e), Err(_)) = (&lsof, &netstat) {
+        return Err(e.clone());
+    }
+
+    Ok(merge_listeners(
+        lsof.map(|out| parse_lsof_listeners(&out))
+            .unwrap_or_default(),
+        netstat
+            .map(|out| parse_netstat_listeners(&out))
+            .unwrap_or_default(),
+    ))
+}
+
+/// TCP sockets in LISTEN state with their owning process
+#[tauri::command]
+pub fn list_listeners() -> Result<Vec<Listener>, String> {
+    collect_listeners(system_runner())
+}
+
+/// Processes listening on `port`, empty when the port is free
+#[tauri::command]
+pub fn who_owns_port(port: u16) -> Result<Vec<Listener>, String> {
+    Ok(list_listeners()?
+        .into_iter()
+        .filter(|listener| listener.port == port)
+        .collect())
+}
+
+/// Check that the local proxies configured for a service are actually running
+#[tauri::command]
+pub fn check_proxy_listener(service_name: String) -> Result<Vec<ProxyListenerCheck>, String> {
+    let settings = get_proxy_settings(service_name)?;
+    let listeners = list_listeners()?;
+
+    Ok(check_proxy_listeners(&settings, &listeners))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::proxy::ProxyConfig;
+    use super::*;
+
+    const LSOF: &str = "\
+COMMAND     PID USER   FD   TYPE             DEVICE SIZE/OFF NODE NAME
+ClashX\\x20 1234 me     10u  IPv4 0x8f0e3c2d5a1b7c01      0t0  TCP 127.0.0.1:7890 (LISTEN)
+ClashX\\x20 1234 me     11u  IPv6 0x8f0e3c2d5a1b7c02      0t0  TCP [::1]:7890 (LISTEN)
+node       4321 me     23u  IPv6 0x8f0e3c2d5a1b7c03      0t0  TCP *:3000 (LISTEN)
+rapportd    612 me      8u  IPv4 0x8f0e3c2d5a1b7c04      0t0  TCP 192.168.1.20:49152->192.168.1.7:52000 (ESTABLISHED)
+";
+
+    /// macOS 12 and earlier
+    const NETSTAT_PID: &str = "\
+Active Internet connections (including servers)
+Proto Recv-Q Send-Q  Local Address          Foreign Address        (state)     rhiwat shiwat    pid   epid  state    options
+tcp4       0      0  127.0.0.1.7890         *.*                    LISTEN      131072 131072   1234      0 0x0080 0x00000006
+tcp46      0      0  *.3000                 *.*                    LISTEN      131072 131072   4321      0 0x0080 0x00000006
+tcp4       0      0  *.22                   *.*                    LISTEN      131072 131072      0      0 0x0080 0x00000006
+tcp4       0      0  192.168.1.20.49152     192.168.1.7.52000      ESTABLISHED 131072 131768    612      0 0x0102 0x00000008
+";
+
+    /// macOS 13 and later; names may contain spaces
+    const NETSTAT_PROCESS_PID: &str = "\
+Active Internet connections (including servers)
+Proto Recv-Q Send-Q  Local Address          Foreign Address        (state)          rxbytes      txbytes  rhiwat  shiwat    process:pid      state   options           gencnt    flags   flags1 usscnt rtncnt fltrs
+tcp4       0      0  127.0.0.1.7890         *.*                    LISTEN                 0            0  131072  131072  ClashX Meta:1234    00000 00000006 0000000000001a2b 00000000 00000800      1      0 000001
+tcp6       0      0  ::1.631                *.*                    LISTEN                 0            0  131072  131072      cupsd:498    00000 00000006 0000000000001a2c 00000000 00000800      1      0 000001
+tcp4       0      0  *.445                  *.*                    LISTEN                 0            0  131072  131072      launchd:1    00000 00000006 0000000000001a2d 00000000 00000800      1      0 000001
+udp4       0      0  *.5353                 *.*                                           0            0  786896    9216  mDNSResponder:401 00000 00000000 0000000000001a2e 00000000 00000000      1      0 000001
+";
+
+    fn listener(family: ListenerFamily, address: &str, port: u16) -> Listener {
+        Listener {
+            process: None,
+            pid: None,
+            user: None,
+            family,
+            address: address.to_string(),
+            port,
+        }
+    }
+
+    fn proxies(server: &str, port: u16) -> ProxySettings {
+        ProxySettings {
+            http_proxy: ProxyConfig {
+                enabled: true,
+                server: server.to_string(),
+                port,
+            },
+            ..ProxySettings::default()
+        }
+    }
+
+    #[test]
+    fn parses_lsof() {
+        let listeners = parse_lsof_listeners(LSOF);
+        assert_eq!(listeners.len(), 3);
+        assert_eq!(listeners[0].process.as_deref(), Some("ClashX "));
+        assert_eq!(listeners[0].pid, Some(1234));
+        assert_eq!(listeners[0].user.as_deref(), Some("me"));
+        assert_eq!(listeners[0].family, ListenerFamily::Ipv4);
+        assert_eq!(listeners[0].address, "127.0.0.1");
+        assert_eq!(listeners[0].port, 7890);
+        assert_eq!(listeners[1].family, ListenerFamily::Ipv6);
+        assert_eq!(listeners[1].address, "::1");
+        assert_eq!(listeners[2].address, "*");
+        assert_eq!(listeners[2].port, 3000);
+
+        assert!(parse_lsof_listeners("").is_empty());
+    }
+
+    #[test]
+    fn parses_netstat_with_a_pid_column() {
+        let listeners = parse_netstat_listeners(NETSTAT_PID);
+        assert_eq!(listeners.len(), 3);
+        assert_eq!(
+            listeners[0],
+            Listener {
+                process: None,
+                pid: Some(1234),
+                user: None,
+                family: ListenerFamily::Ipv4,
+                address: "127.0.0.1".to_string(),
+                port: 7890,
+            }
+        );
+        assert_eq!(listeners[1].family, ListenerFamily::Dual);
+        assert_eq!(listeners[1].address, "*");
+        // Kernel sockets report pid 0
+        assert_eq!(listeners[2].pid, None);
+        assert_eq!(listeners[2].port, 22);
+    }
+
+    #[test]
+    fn parses_netstat_with_a_process_pid_column() {
+        let listeners = parse_netstat_listeners(NETSTAT_PROCESS_PID);
+        assert_eq!(listeners.len(), 3);
+        assert_eq!(listeners[0].process.as_deref(), Some("ClashX Meta"));
+        assert_eq!(listeners[0].pid, Some(1234));
+        assert_eq!(listeners[0].port, 7890);
+        assert_eq!(listeners[1].process.as_deref(), Some("cupsd"));
+        assert_eq!(listeners[1].pid, Some(498));
+        assert_eq!(listeners[1].family, ListenerFamily::Ipv6);
+        assert_eq!(listeners[1].address, "::1");
+        assert_eq!(listeners[1].port, 631);
+        assert_eq!(listeners[2].process.as_deref(), Some("launchd"));
+        assert_eq!(listeners[2].pid, Some(1));
+    }
+
+    #[test]
+    fn netstat_without_a_header_has_no_owners() {
+        let rows: String = NETSTAT_PID
+            .lines()
+            .skip(2)
+            .map(|l| format!("{}\n", l))
+            .collect();
+        let listeners = parse_netstat_listeners(&rows);
+        assert_eq!(listeners.len(), 3);
+        assert!(listeners
+            .iter()
+            .all(|l| l.pid.is_none() && l.process.is_none()));
+    }
+
+    #[test]
+    fn merges_lsof_and_netstat() {
+        let merged = merge_listeners(
+            parse_lsof_listeners(LSOF),
+            parse_netstat_listeners(NETSTAT_PID),
+        );
+        let ports: Vec<(u16, &str)> = merged
+            .iter()
+            .map(|l| (l.port, l.address.as_str()))
+            .collect();
+        assert_eq!(
+            ports,
+            [(22, "*"), (3000, "*"), (7890, "127.0.0.1"), (7890, "::1")]
+        );
+
+        // lsof's names and users are kept, netstat adds the dual-stack family
+        let node = &merged[1];
+        assert_eq!(node.process.as_deref(), Some("node"));
+        assert_eq!(node.user.as_deref(), Some("me"));
+        assert_eq!(node.family, ListenerFamily::Dual);
+        // Sockets of other users only come from netstat
+        assert_eq!(merged[0].user, None);
+    }
+
+    #[test]
+    fn merge_fills_in_missing_owners() {
+        let mut named = listener(ListenerFamily::Ipv4, "*", 8080);
+        named.process = Some("nginx".to_string());
+        let mut with_pid = listener(ListenerFamily::Ipv4, "*", 8080);
+        with_pid.pid = Some(77);
+
+        let merged = merge_listeners(vec![named], vec![with_pid]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].process.as_deref(), Some("nginx"));
+        assert_eq!(merged[0].pid, Some(77));
+
+        // Different processes on the same port stay separate
+        let mut first = listener(ListenerFamily::Ipv4, "*", 8080);
+        first.pid = Some(1);
+        let mut second = listener(ListenerFamily::Ipv4, "*", 8080);
+        second.pid = Some(2);
+        assert_eq!(merge_listeners(vec![first], vec![second]).len(), 2);
+    }
+
+    #[test]
+    fn connections_need_a_matching_family_and_address() {
+        let v4_any = listener(ListenerFamily::Ipv4, "*", 7890);
+        let v6_any = listener(ListenerFamily::Ipv6, "*", 7890);
+        let dual = listener(ListenerFamily::Dual, "*", 7890);
+        let v4_loopback = listener(ListenerFamily::Ipv4, "127.0.0.1", 7890);
+        let v6_loopback = listener(ListenerFamily::Ipv6, "::1", 7890);
+
+        assert!(accepts_connections_to(&v4_any, "127.0.0.1"));
+        assert!(!accepts_connections_to(&v6_any, "127.0.0.1"));
+        assert!(accepts_connections_to(&dual, "127.0.0.1"));
+        assert!(accepts_connections_to(&v4_loopback, "127.0.0.1"));
+        assert!(!accepts_connections_to(&v6_loopback, "127.0.0.1"));
+
+        assert!(!accepts_connections_to(&v4_any, "::1"));
+        assert!(accepts_connections_to(&v6_any, "::1"));
+        assert!(accepts_connections_to(&dual, "::1"));
+        assert!(accepts_connections_to(&v6_loopback, "::1"));
+
+        for listener in [&v4_any, &v6_any, &dual, &v4_loopback, &v6_loopback] {
+            assert!(accepts_connections_to(listener, "localhost"));
+        }
+        assert!(!accepts_connections_to(&v4_loopback, "192.168.1.20"));
+    }
+
+    #[test]
+    fn checks_local_proxies() {
+        let listeners = [listener(ListenerFamily::Ipv6, "*", 7890)];
+        let checks = check_proxy_listeners(&proxies("127.0.0.1", 7890), &listeners);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].kind, "HTTP");
+        assert!(checks[0].is_local);
+        assert_eq!(checks[0].listening, Some(false));
+
+        let checks = check_proxy_listeners(&proxies("localhost", 7890), &listeners);
+        assert_eq!(checks[0].listening, Some(true));
+        assert_eq!(checks[0].listeners.len(), 1);
+
+        let checks = check_proxy_listeners(&proxies("proxy.corp.example", 7890), &listeners);
+        assert!(!checks[0].is_local);
+        assert_eq!(checks[0].listening, None);
+        assert!(checks[0].listeners.is_empty());
+
+        assert!(check_proxy_listeners(&ProxySettings::default(), &listeners).is_empty());
+    }
+}
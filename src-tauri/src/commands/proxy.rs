@@ -1,19 +1,23 @@
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+use super::location::current_location;
+use super::runner::invalidate_command_cache;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct ProxyConfig {
     pub enabled: bool,
     pub server: String,
     pub port: u16,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct ProxySettings {
     pub http_proxy: ProxyConfig,
     pub https_proxy: ProxyConfig,
     pub socks_proxy: ProxyConfig,
     pub service_name: String,
+    pub location: Option<String>,
 }
 
 fn parse_proxy_output(output: &str) -> ProxyConfig {
@@ -39,6 +43,7 @@ fn parse_proxy_output(output: &str) -> ProxyConfig {
 pub fn get_proxy_settings(service_name: String) -> Result<ProxySettings, String> {
     let mut settings = ProxySettings {
         service_name: service_name.clone(),
+        location: current_location(),
         ..Default::default()
     };
 
@@ -90,6 +95,7 @@ pub fn set_http_proxy(
             .output()
             .map_err(|e| format!("Failed to disable HTTP proxy: {}", e))?;
     }
+    invalidate_command_cache();
 
     Ok(format!("HTTP proxy updated for {}", service_name))
 }
@@ -117,6 +123,7 @@ pub fn set_https_proxy(
             .output()
             .map_err(|e| format!("Failed to disable HTTPS proxy: {}", e))?;
     }
+    invalidate_command_cache();
 
     Ok(format!("HTTPS proxy updated for {}", service_name))
 }
@@ -144,6 +151,7 @@ pub fn set_socks_proxy(
             .output()
             .map_err(|e| format!("Failed to disable SOCKS proxy: {}", e))?;
     }
+    invalidate_command_cache();
 
     Ok(format!("SOCKS proxy updated for {}", service_name))
 }
@@ -173,6 +181,7 @@ pub fn clear_all_proxies(service_name: String) -> Result<String, String> {
         .args(["-setautoproxystate", &service_name, "off"])
         .output()
         .map_err(|e| format!("Failed to disable auto proxy: {}", e))?;
+    invalidate_command_cache();
 
     Ok(format!("All proxies cleared for {}", service_name))
 }
@@ -0,0 +1,411 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::time::Instant;
+
+use super::network::{get_default_route, run_with_admin_privileges};
+use super::runner::invalidate_command_cache;
+use super::vpn::is_tunnel_interface;
+
+/// Frames each privileged step's output in the combined admin script
+const STEP_MARKER: &str = "@@reset-step";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ResetStep {
+    FlushDnsCache,
+    RestartMdnsResponder,
+    FlushArpCache,
+    RenewDhcp,
+}
+
+impl ResetStep {
+    /// Every step, in the order they are executed
+    pub const ALL: [ResetStep; 4] = [
+        ResetStep::FlushDnsCache,
+        ResetStep::RestartMdnsResponder,
+        ResetStep::FlushArpCache,
+        ResetStep::RenewDhcp,
+    ];
+
+    fn description(self) -> &'static str {
+        match self {
+            ResetStep::FlushDnsCache => "Flush the DNS cache",
+            ResetStep::RestartMdnsResponder => "Restart mDNSResponder",
+            ResetStep::FlushArpCache => "Clear the ARP cache",
+            ResetStep::RenewDhcp => "Renew the DHCP lease of the primary interface",
+        }
+    }
+
+    fn requires_admin(self) -> bool {
+        // mDNSResponder runs as its own user, ARP and interface changes need root
+        !matches!(self, ResetStep::FlushDnsCache)
+    }
+
+    /// Shell command for this step, or the reason it cannot run
+    fn command(self, default_interface: Option<&str>) -> Result<String, String> {
+        match self {
+            ResetStep::FlushDnsCache => Ok("dscacheutil -flushcache".to_string()),
+            ResetStep::RestartMdnsResponder => Ok("killall -HUP mDNSResponder".to_string()),
+            ResetStep::FlushArpCache => Ok("arp -d -a".to_string()),
+            ResetStep::RenewDhcp => match default_interface {
+                None => Err("No default route, primary interface is unknown".to_string()),
+                Some(interface) if is_tunnel_interface(interface) => Err(format!(
+                    "The default route goes through the VPN tunnel {}, which has no DHCP lease",
+                    interface
+                )),
+                Some(interface) => Ok(format!("ipconfig set {} DHCP", interface)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlannedStep {
+    pub step: ResetStep,
+    pub description: String,
+    pub command: Option<String>,
+    pub requires_admin: bool,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum StepStatus {
+    Success,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StepResult {
+    pub step: ResetStep,
+    pub command: Option<String>,
+    pub requires_admin: bool,
+    pub status: StepStatus,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Privileged steps share one script and report its total duration
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetReport {
+    pub success: bool,
+    pub steps: Vec<StepResult>,
+}
+
+/// Deduplicate the requested steps and put them in execution order
+fn normalize_steps(steps: Option<Vec<ResetStep>>) -> Vec<ResetStep> {
+    let mut steps = steps.unwrap_or_else(|| ResetStep::ALL.to_vec());
+    steps.sort();
+    steps.dedup();
+    steps
+}
+
+fn default_interface() -> Option<String> {
+    get_default_route().map(|route| route.interface)
+}
+
+/// Resolve the command of each step against the current default interface
+fn plan_steps(steps: Vec<ResetStep>, default_interface: Option<&str>) -> Vec<PlannedStep> {
+    steps
+        .into_iter()
+        .map(|step| {
+            let (command, note) = match step.command(default_interface) {
+                Ok(command) => (Some(command), None),
+                Err(reason) => (None, Some(reason)),
+            };
+            PlannedStep {
+                step,
+                description: step.description().to_string(),
+                command,
+                requires_admin: step.requires_admin(),
+                note,
+            }
+        })
+        .collect()
+}
+
+fn skipped_step(planned: &PlannedStep) -> StepResult {
+    StepResult {
+        step: planned.step,
+        command: None,
+        requires_admin: planned.requires_admin,
+        status: StepStatus::Skipped,
+        exit_code: None,
+        stdout: String::new(),
+        stderr: planned.note.clone().unwrap_or_default(),
+        duration_ms: 0,
+    }
+}
+
+fn run_local_step(step: ResetStep, command: &str) -> StepResult {
+    let start = Instant::now();
+    let result = Command::new("sh").args(["-c", command]).output();
+    invalidate_command_cache();
+    let (status, exit_code, stdout, stderr) = match result {
+        Ok(output) => (
+            if output.status.success() {
+                StepStatus::Success
+            } else {
+                StepStatus::Failed
+            },
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ),
+        Err(e) => (
+            StepStatus::Failed,
+            None,
+            String::new(),
+            format!("Failed to execute command: {}", e),
+        ),
+    };
+
+    StepResult {
+        step,
+        command: Some(command.to_string()),
+        requires_admin: false,
+        status,
+        exit_code,
+        stdout,
+        stderr,
+        duration_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+/// One privileged script so the user only authorizes once. Every step runs even if
+/// an earlier one fails, and its output is framed by markers carrying its exit code.
+fn admin_script(commands: &[&str]) -> String {
+    commands
+        .iter()
+        .enumerate()
+        .map(|(index, command)| {
+            format!(
+                "echo '{marker} start {index}'; {command} 2>&1; echo \"{marker} exit {index} $?\"",
+                marker = STEP_MARKER,
+                index = index,
+                command = command
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Split the admin script output back into the exit code and output of each step.
+/// `do shell script` returns lines separated by `\r`, so both line endings are accepted.
+fn split_admin_output(output: &str, count: usize) -> Vec<Option<(i32, String)>> {
+    let mut results = vec![None; count];
+    let mut current: Option<(usize, Vec<&str>)> = None;
+
+    for line in output.split(['\n', '\r']).filter(|line| !line.is_empty()) {
+        let Some(marker) = line.strip_prefix(STEP_MARKER) else {
+            if let Some((_, lines)) = current.as_mut() {
+                lines.push(line);
+            }
+            continue;
+        };
+        match marker.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["start", index] => current = index.parse().ok().map(|index| (index, Vec::new())),
+            ["exit", index, code] => {
+                if let (Some((started, lines)), Ok(code)) = (current.take(), code.parse()) {
+                    if index.parse() == Ok(started) && started < count {
+                        results[started] = Some((code, lines.join("\n")));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    results
+}
+
+fn run_admin_steps(steps: &[(ResetStep, &str)]) -> Vec<StepResult> {
+    if steps.is_empty() {
+        return Vec::new();
+    }
+
+    let commands: Vec<&str> = steps.iter().map(|(_, command)| *command).collect();
+    let start = Instant::now();
+    let output = run_with_admin_privileges(&admin_script(&commands));
+    let duration_ms = start.elapsed().as_millis() as u64;
+    let outputs = match &output {
+        Ok(stdout) => split_admin_output(stdout, steps.len()),
+        Err(_) => vec![None; steps.len()],
+    };
+
+    steps
+        .iter()
+        .zip(outputs)
+        .map(|((step, command), result)| {
+            let (status, exit_code, stdout, stderr) = match (result, &output) {
+                (Some((0, stdout)), _) => (StepStatus::Success, Some(0), stdout, String::new()),
+                (Some((code, stdout)), _) => {
+                    (StepStatus::Failed, Some(code), stdout, String::new())
+                }
+                (None, Err(e)) => (StepStatus::Failed, None, String::new(), e.clone()),
+                (None, Ok(_)) => (
+                    StepStatus::Failed,
+                    None,
+                    String::new(),
+                    "The step did not report a result".to_string(),
+                ),
+            };
+            StepResult {
+                step: *step,
+                command: Some(command.to_string()),
+                requires_admin: true,
+                status,
+                exit_code,
+                stdout,
+                stderr,
+                duration_ms,
+            }
+        })
+        .collect()
+}
+
+/// Show what `run_reset_plan` would do without running anything
+#[tauri::command]
+pub fn preview_reset_plan(steps: Option<Vec<ResetStep>>) -> Result<Vec<PlannedStep>, String> {
+    Ok(plan_steps(
+        normalize_steps(steps),
+        default_interface().as_deref(),
+    ))
+}
+
+#[tauri::command(async)]
+pub fn run_reset_plan(steps: Option<Vec<ResetStep>>) -> Result<ResetReport, String> {
+    let steps = normalize_steps(steps);
+    if steps.is_empty() {
+        return Err("No reset steps selected".to_string());
+    }
+    let plan = plan_steps(steps, default_interface().as_deref());
+
+    // Unprivileged steps run on their own, privileged ones in one batch afterwards
+    let local: Vec<Option<StepResult>> = plan
+        .iter()
+        .map(|planned| match &planned.command {
+            None => Some(skipped_step(planned)),
+            Some(command) if !planned.requires_admin => Some(run_local_step(planned.step, command)),
+            Some(_) => None,
+        })
+        .collect();
+    let admin: Vec<(ResetStep, &str)> = plan
+        .iter()
+        .zip(&local)
+        .filter(|(_, result)| result.is_none())
+        .filter_map(|(planned, _)| Some((planned.step, planned.command.as_deref()?)))
+        .collect();
+    let mut admin_results = run_admin_steps(&admin).into_iter();
+
+    let results: Vec<StepResult> = local
+        .into_iter()
+        .filter_map(|result| result.or_else(|| admin_results.next()))
+        .collect();
+    let success = results.iter().all(|r| r.status != StepStatus::Failed);
+
+    Ok(ResetReport {
+        success,
+        steps: results,
+    })
+}
+
+#[tauri::command]
+pub fn reset_network() -> Result<String, String> {
+    let report = run_reset_plan(None)?;
+
+    let summary: Vec<String> = report
+        .steps
+        .iter()
+        .map(|r| format!("{}: {:?}", r.step.description(), r.status))
+        .collect();
+
+    if report.success {
+        Ok(format!("Network reset completed: {}", summary.join(", ")))
+    } else {
+        Err(format!("Network reset incomplete: {}", summary.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_requested_steps() {
+        assert_eq!(normalize_steps(None), ResetStep::ALL);
+        assert_eq!(
+            normalize_steps(Some(vec![
+                ResetStep::RenewDhcp,
+                ResetStep::FlushDnsCache,
+                ResetStep::RenewDhcp,
+            ])),
+            [ResetStep::FlushDnsCache, ResetStep::RenewDhcp]
+        );
+        assert!(normalize_steps(Some(Vec::new())).is_empty());
+    }
+
+    #[test]
+    fn plans_every_step() {
+        let plan = plan_steps(ResetStep::ALL.to_vec(), Some("en0"));
+        let commands: Vec<Option<&str>> = plan.iter().map(|p| p.command.as_deref()).collect();
+        assert_eq!(
+            commands,
+            [
+                Some("dscacheutil -flushcache"),
+                Some("killall -HUP mDNSResponder"),
+                Some("arp -d -a"),
+                Some("ipconfig set en0 DHCP"),
+            ]
+        );
+        let admin: Vec<bool> = plan.iter().map(|p| p.requires_admin).collect();
+        assert_eq!(admin, [false, true, true, true]);
+        assert!(plan.iter().all(|p| p.note.is_none()));
+    }
+
+    #[test]
+    fn dhcp_renewal_needs_a_physical_default_interface() {
+        let plan = plan_steps(vec![ResetStep::RenewDhcp], Some("utun4"));
+        assert_eq!(plan[0].command, None);
+        assert_eq!(
+            plan[0].note.as_deref(),
+            Some("The default route goes through the VPN tunnel utun4, which has no DHCP lease")
+        );
+
+        let plan = plan_steps(vec![ResetStep::RenewDhcp], None);
+        assert_eq!(plan[0].command, None);
+        assert_eq!(
+            plan[0].note.as_deref(),
+            Some("No default route, primary interface is unknown")
+        );
+        assert_eq!(skipped_step(&plan[0]).status, StepStatus::Skipped);
+    }
+
+    #[test]
+    fn batches_admin_steps_into_one_script() {
+        assert_eq!(
+            admin_script(&["killall -HUP mDNSResponder", "arp -d -a"]),
+            "echo '@@reset-step start 0'; killall -HUP mDNSResponder 2>&1; \
+             echo \"@@reset-step exit 0 $?\"; \
+             echo '@@reset-step start 1'; arp -d -a 2>&1; echo \"@@reset-step exit 1 $?\""
+        );
+    }
+
+    #[test]
+    fn splits_admin_output_per_step() {
+        // do shell script separates lines with carriage returns
+        let output = "@@reset-step start 0\r@@reset-step exit 0 0\r\
+                      @@reset-step start 1\rdelete: cannot locate 192.168.1.9\r\
+                      @@reset-step exit 1 1\r@@reset-step start 2\r";
+        assert_eq!(
+            split_admin_output(output, 3),
+            [
+                Some((0, String::new())),
+                Some((1, "delete: cannot locate 192.168.1.9".to_string())),
+                None,
+            ]
+        );
+        assert_eq!(split_admin_output("", 2), [None, None]);
+    }
+}
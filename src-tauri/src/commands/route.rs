@@ -0,0 +1,422 @@
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use super::ip::{parse_ipv4, validate_subnet_mask};
+use super::network::{get_service_order_internal, run_with_admin_privileges, shell_quote};
+use super::runner::system_runner;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum RouteFamily {
+    Ipv4,
+    Ipv6,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RouteEntry {
+    pub family: RouteFamily,
+    pub destination: String,
+    pub gateway: String,
+    pub flags: String,
+    pub netif: Option<String>,
+    pub expire: Option<String>,
+}
+
+impl RouteEntry {
+    /// Bound to its interface (`I` flag) and only used for scoped lookups
+    pub fn is_scoped(&self) -> bool {
+        self.flags.contains('I')
+    }
+}
+
+/// A persistent route from `networksetup -getadditionalroutes`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StaticRoute {
+    pub destination: String,
+    pub subnet_mask: String,
+    pub gateway: String,
+}
+
+/// Parse `netstat -rn` output. Entries are tagged with the family of the section
+/// ("Internet:" / "Internet6:") they appear in.
+pub fn parse_netstat_routes(output: &str) -> Vec<RouteEntry> {
+    let mut routes = Vec::new();
+    let mut family = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        match trimmed {
+            "Internet:" => family = Some(RouteFamily::Ipv4),
+            "Internet6:" => family = Some(RouteFamily::Ipv6),
+            _ => {}
+        }
+
+        let Some(family) = family else {
+            continue;
+        };
+        let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+        if tokens.len() < 3 || tokens[0] == "Destination" || trimmed.ends_with(':') {
+            continue;
+        }
+
+        routes.push(RouteEntry {
+            family,
+            destination: tokens[0].to_string(),
+            gateway: tokens[1].to_string(),
+            flags: tokens[2].to_string(),
+            netif: tokens.get(3).map(|s| s.to_string()),
+            expire: tokens.get(4).map(|s| s.to_string()),
+        });
+    }
+
+    routes
+}
+
+/// Network address and prefix length of a `netstat -rn` destination.
+///
+/// IPv4 networks are printed without their trailing zero octets ("192.168.1" is
+/// 192.168.1.0/24) and IPv6 destinations may carry a zone ("fe80::%lo0/64").
+pub fn parse_destination(family: RouteFamily, destination: &str) -> Option<(IpAddr, u32)> {
+    if destination == "default" {
+        return Some(match family {
+            RouteFamily::Ipv4 => (IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+            RouteFamily::Ipv6 => (IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+        });
+    }
+
+    let (address, prefix) = match destination.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix.parse::<u32>().ok()?)),
+        None => (destination, None),
+    };
+    let address = address.split('%').next().unwrap_or(address);
+
+    match family {
+        RouteFamily::Ipv4 => {
+            let octets: Vec<u8> = address
+                .split('.')
+                .map(|octet| octet.parse().ok())
+                .collect::<Option<_>>()?;
+            if octets.is_empty() || octets.len() > 4 {
+                return None;
+            }
+            let mut padded = [0u8; 4];
+            padded[..octets.len()].copy_from_slice(&octets);
+            let prefix = prefix.unwrap_or(octets.len() as u32 * 8);
+            (prefix <= 32).then_some((IpAddr::V4(Ipv4Addr::from(padded)), prefix))
+        }
+        RouteFamily::Ipv6 => {
+            let address: Ipv6Addr = address.parse().ok()?;
+            let prefix = prefix.unwrap_or(128);
+            (prefix <= 128).then_some((IpAddr::V6(address), prefix))
+        }
+    }
+}
+
+fn prefix_matches(network: IpAddr, prefix: u32, ip: IpAddr) -> bool {
+    match (network, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(network) & mask == u32::from(ip) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(network) & mask == u128::from(ip) & mask
+        }
+        _ => false,
+    }
+}
+
+/// The route `ip` would take: the longest matching prefix, earlier entries on ties.
+///
+/// Interface-scoped routes only serve lookups scoped to their interface, so they
+/// are skipped unless `interface` is given; a scoped lookup only considers routes
+/// through that interface.
+pub fn longest_prefix_match<'a>(
+    routes: &'a [RouteEntry],
+    ip: IpAddr,
+    interface: Option<&str>,
+) -> Option<&'a RouteEntry> {
+    let mut best: Option<(&RouteEntry, u32)> = None;
+
+    for route in routes {
+        let eligible = match interface {
+            Some(interface) => route.netif.as_deref() == Some(interface),
+            None => !route.is_scoped(),
+        };
+        if !eligible {
+            continue;
+        }
+        let Some((network, prefix)) = parse_destination(route.family, &route.destination) else {
+            continue;
+        };
+        if !prefix_matches(network, prefix, ip) {
+            continue;
+        }
+
+        let better = match best {
+            Some((_, best_prefix)) => prefix > best_prefix,
+            None => true,
+        };
+        if better {
+            best = Some((route, prefix));
+        }
+    }
+
+    best.map(|(route, _)| route)
+}
+
+/// Parse `networksetup -getadditionalroutes` output, one
+/// "destination mask gateway" triple per line
+pub fn parse_additional_routes(output: &str) -> Vec<StaticRoute> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens[..] {
+                [destination, subnet_mask, gateway] if destination.parse::<Ipv4Addr>().is_ok() => {
+                    Some(StaticRoute {
+                        destination: destination.to_string(),
+                        subnet_mask: subnet_mask.to_string(),
+                        gateway: gateway.to_string(),
+                    })
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn validate_static_route(route: &StaticRoute) -> Result<(), String> {
+    let destination = parse_ipv4(&route.destination, "Destination")?;
+    let prefix = validate_subnet_mask(&route.subnet_mask)?;
+    parse_ipv4(&route.gateway, "Gateway")?;
+
+    let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+    if u32::from(destination) & !mask != 0 {
+        return Err(format!(
+            "Destination {} has host bits set for mask {}",
+            route.destination, route.subnet_mask
+        ));
+    }
+
+    Ok(())
+}
+
+fn ensure_service_exists(service_name: &str) -> Result<(), String> {
+    if service_name.is_empty() {
+        return Err("Service name cannot be empty".to_string());
+    }
+
+    if let Ok(existing) = get_service_order_internal() {
+        if !existing.iter().any(|s| s == service_name) {
+            return Err(format!("Network service '{}' does not exist", service_name));
+        }
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_routing_table() -> Result<Vec<RouteEntry>, String> {
+    let stdout = system_runner().run("netstat", &["-rn"])?;
+
+    Ok(parse_netstat_routes(&stdout))
+}
+
+/// Which route traffic to `destination` would take, optionally when bound to `interface`
+#[tauri::command]
+pub fn lookup_route(destination: String, interface: Option<String>) -> Result<RouteEntry, String> {
+    let ip: IpAddr = destination
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid IP address", destination))?;
+
+    let routes = get_routing_table()?;
+    let interface = interface.as_deref().filter(|i| !i.is_empty());
+    longest_prefix_match(&routes, ip, interface)
+        .cloned()
+        .ok_or_else(|| match interface {
+            Some(interface) => format!("No route to {} through {}", ip, interface),
+            None => format!("No route to {}", ip),
+        })
+}
+
+#[tauri::command]
+pub fn get_additional_routes(service_name: String) -> Result<Vec<StaticRoute>, String> {
+    ensure_service_exists(&service_name)?;
+
+    let stdout = system_runner().run("networksetup", &["-getadditionalroutes", &service_name])?;
+
+    Ok(parse_additional_routes(&stdout))
+}
+
+/// The root `networksetup -setadditionalroutes` command, every value single-quoted
+fn additional_routes_command(service_name: &str, routes: &[StaticRoute]) -> Result<String, String> {
+    let mut command = format!(
+        "networksetup -setadditionalroutes {}",
+        shell_quote(service_name)
+    );
+    for route in routes {
+        validate_static_route(route)?;
+        command.push_str(&format!(
+            " {} {} {}",
+            shell_quote(route.destination.trim()),
+            shell_quote(route.subnet_mask.trim()),
+            shell_quote(route.gateway.trim())
+        ));
+    }
+    Ok(command)
+}
+
+/// Replace the persistent routes of a service; an empty list removes them all
+#[tauri::command]
+pub fn set_additional_routes(
+    service_name: String,
+    routes: Vec<StaticRoute>,
+) -> Result<String, String> {
+    ensure_service_exists(&service_name)?;
+    let command = additional_routes_command(&service_name, &routes)?;
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!(
+        "{} additional route(s) set for {}",
+        routes.len(),
+        service_name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETSTAT: &str = "\
+Routing tables
+
+Internet:
+Destination        Gateway            Flags               Netif Expire
+default            192.168.1.1        UGScg                 en0
+default            10.0.0.1           UGScIg                en7
+10.0/16            10.0.0.1           UGSc                  en7
+10.0.0/24          link#12            UCS                   en7      !
+10.8/16            link#20            UCSI                utun4
+127                127.0.0.1          UCS                   lo0
+127.0.0.1          127.0.0.1          UH                    lo0
+192.168.1          link#11            UCS                   en0      !
+192.168.1.1        a4:83:e7:00:00:99  UHLWIir               en0   1187
+192.168.1.1/32     link#11            UCS                   en0      !
+
+Internet6:
+Destination                             Gateway                                 Flags               Netif Expire
+default                                 fe80::1%en0                             UGcg                  en0
+::1                                     ::1                                     UHL                   lo0
+fe80::%en0/64                           link#11                                 UCI                   en0
+2001:db8::/32                           link#20                                 UCS                 utun4
+";
+
+    fn lookup(ip: &str, interface: Option<&str>) -> Option<(String, Option<String>)> {
+        let routes = parse_netstat_routes(NETSTAT);
+        longest_prefix_match(&routes, ip.parse().unwrap(), interface)
+            .map(|route| (route.destination.clone(), route.netif.clone()))
+    }
+
+    fn route(destination: &str, netif: &str) -> Option<(String, Option<String>)> {
+        Some((destination.to_string(), Some(netif.to_string())))
+    }
+
+    #[test]
+    fn parses_netstat_sections() {
+        let routes = parse_netstat_routes(NETSTAT);
+        assert_eq!(routes.len(), 14);
+        assert_eq!(routes[0].family, RouteFamily::Ipv4);
+        assert_eq!(routes[0].gateway, "192.168.1.1");
+        assert_eq!(routes[0].netif.as_deref(), Some("en0"));
+        assert!(!routes[0].is_scoped());
+        assert!(routes[1].is_scoped());
+        assert_eq!(routes[8].expire.as_deref(), Some("1187"));
+        assert_eq!(routes[10].family, RouteFamily::Ipv6);
+        assert_eq!(routes[10].gateway, "fe80::1%en0");
+    }
+
+    #[test]
+    fn parses_destinations() {
+        let v4 = |destination| parse_destination(RouteFamily::Ipv4, destination);
+        let v6 = |destination| parse_destination(RouteFamily::Ipv6, destination);
+
+        assert_eq!(v4("default"), Some(("0.0.0.0".parse().unwrap(), 0)));
+        assert_eq!(v4("127"), Some(("127.0.0.0".parse().unwrap(), 8)));
+        assert_eq!(v4("192.168.1"), Some(("192.168.1.0".parse().unwrap(), 24)));
+        assert_eq!(v4("10.0/16"), Some(("10.0.0.0".parse().unwrap(), 16)));
+        assert_eq!(
+            v4("192.168.1.1"),
+            Some(("192.168.1.1".parse().unwrap(), 32))
+        );
+        assert_eq!(v4("10/33"), None);
+        assert_eq!(v4("link#11"), None);
+        assert_eq!(v6("default"), Some(("::".parse().unwrap(), 0)));
+        assert_eq!(v6("fe80::%en0/64"), Some(("fe80::".parse().unwrap(), 64)));
+        assert_eq!(v6("::1"), Some(("::1".parse().unwrap(), 128)));
+    }
+
+    #[test]
+    fn unscoped_lookup_skips_scoped_routes() {
+        // 10.8/16 is scoped to utun4, so the lookup falls back to the default route
+        assert_eq!(lookup("10.8.1.1", None), route("default", "en0"));
+        assert_eq!(lookup("10.0.0.5", None), route("10.0.0/24", "en7"));
+        assert_eq!(lookup("10.0.9.9", None), route("10.0/16", "en7"));
+        // The ARP entry of the gateway is scoped, its /32 route is not
+        assert_eq!(lookup("192.168.1.1", None), route("192.168.1.1/32", "en0"));
+        assert_eq!(lookup("192.168.1.30", None), route("192.168.1", "en0"));
+        assert_eq!(lookup("127.0.0.1", None), route("127.0.0.1", "lo0"));
+        assert_eq!(lookup("1.1.1.1", None), route("default", "en0"));
+        // The unscoped default is listed first, the scoped one is never chosen
+        assert_eq!(lookup("8.8.8.8", None), route("default", "en0"));
+    }
+
+    #[test]
+    fn scoped_lookup_uses_the_interface_routes() {
+        assert_eq!(lookup("10.8.1.1", Some("utun4")), route("10.8/16", "utun4"));
+        assert_eq!(lookup("8.8.8.8", Some("en7")), route("default", "en7"));
+        assert_eq!(lookup("192.168.1.30", Some("en7")), route("default", "en7"));
+        // Scoped and unscoped routes of the interface both qualify, earlier wins
+        assert_eq!(
+            lookup("192.168.1.1", Some("en0")),
+            route("192.168.1.1", "en0")
+        );
+        assert_eq!(lookup("8.8.8.8", Some("utun4")), None);
+        assert_eq!(lookup("8.8.8.8", Some("en9")), None);
+    }
+
+    #[test]
+    fn ipv6_lookups() {
+        // Link-local routes are scoped to their interface
+        assert_eq!(lookup("fe80::1234", None), route("default", "en0"));
+        assert_eq!(
+            lookup("fe80::1234", Some("en0")),
+            route("fe80::%en0/64", "en0")
+        );
+        assert_eq!(lookup("2001:db8::1", None), route("2001:db8::/32", "utun4"));
+        assert_eq!(lookup("::1", None), route("::1", "lo0"));
+        assert_eq!(lookup("2606:4700::1111", None), route("default", "en0"));
+        // Families never mix
+        assert_eq!(lookup("0.0.0.1", Some("lo0")), None);
+    }
+
+    #[test]
+    fn additional_routes_command_quotes_every_value() {
+        let routes = vec![StaticRoute {
+            destination: "10.20.0.0".to_string(),
+            subnet_mask: "255.255.0.0".to_string(),
+            gateway: " 192.168.1.254 ".to_string(),
+        }];
+        assert_eq!(
+            additional_routes_command("Lab $(id)", &routes),
+            Ok("networksetup -setadditionalroutes 'Lab $(id)' \
+                '10.20.0.0' '255.255.0.0' '192.168.1.254'"
+                .to_string())
+        );
+        assert_eq!(
+            additional_routes_command("Wi-Fi", &[]),
+            Ok("networksetup -setadditionalroutes 'Wi-Fi'".to_string())
+        );
+    }
+}
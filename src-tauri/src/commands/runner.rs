@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long read-only command output is reused before running the command again
+const CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Runs external commands and returns their stdout.
+///
+/// Commands go through this trait so probing can be cached, parallelized and
+/// replaced with fixtures.
+pub trait CommandRunner: Send + Sync {
+    fn run(&self, program: &str, args: &[&str]) -> Result<String, String>;
+}
+
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str]) -> Result<String, String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// Wraps another runner and reuses its output for identical commands within `ttl`
+pub struct CachedRunner<R> {
+    inner: R,
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, String)>>,
+}
+
+impl<R: CommandRunner> CachedRunner<R> {
+    pub fn new(inner: R, ttl: Duration) -> Self {
+        CachedRunner {
+            inner,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn invalidate(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+impl<R: CommandRunner> CommandRunner for CachedRunner<R> {
+    fn run(&self, program: &str, args: &[&str]) -> Result<String, String> {
+        let key = format!("{}\0{}", program, args.join("\0"));
+
+        if let Ok(entries) = self.entries.lock() {
+            if let Some((at, stdout)) = entries.get(&key) {
+                if at.elapsed() < self.ttl {
+                    return Ok(stdout.clone());
+                }
+            }
+        }
+
+        // Run without holding the lock so independent commands can proceed in parallel
+        let stdout = self.inner.run(program, args)?;
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key, (Instant::now(), stdout.clone()));
+        }
+
+        Ok(stdout)
+    }
+}
+
+fn shared_runner() -> &'static CachedRunner<SystemRunner> {
+    static RUNNER: OnceLock<CachedRunner<SystemRunner>> = OnceLock::new();
+    RUNNER.get_or_init(|| CachedRunner::new(SystemRunner, CACHE_TTL))
+}
+
+/// Cached runner shared by all commands for read-only probes
+pub(crate) fn system_runner() -> &'static dyn CommandRunner {
+    shared_runner()
+}
+
+/// Drop cached output after anything changes the network configuration
+pub(crate) fn invalidate_command_cache() {
+    shared_runner().invalidate();
+}
@@ -0,0 +1,615 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::dns::{get_dns_servers, get_search_domains, set_dns_servers, set_search_domains};
+use super::ip::{apply_ip_config, get_ip_config_internal, IpConfig};
+use super::mtu::{get_mtu, set_mtu};
+use super::network::{get_network_services, get_service_order_internal, set_service_order};
+use super::proxy::{
+    get_proxy_settings, set_http_proxy, set_https_proxy, set_socks_proxy, ProxyConfig,
+    ProxySettings,
+};
+
+/// Bump when the snapshot layout changes in a way older readers cannot handle
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ServiceSnapshot {
+    pub name: String,
+    pub device: String,
+    pub dns_servers: Vec<String>,
+    pub search_domains: Vec<String>,
+    pub proxies: ProxySettings,
+    pub ip_config: Option<IpConfig>,
+    pub mtu: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NetworkSnapshot {
+    pub version: u32,
+    pub created_at: u64,
+    pub service_order: Vec<String>,
+    pub services: Vec<ServiceSnapshot>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ProxyKind {
+    Http,
+    Https,
+    Socks,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SnapshotChange {
+    ServiceOrder {
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+    DnsServers {
+        service: String,
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+    SearchDomains {
+        service: String,
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+    Proxy {
+        service: String,
+        kind: ProxyKind,
+        from: ProxyConfig,
+        to: ProxyConfig,
+    },
+    IpConfig {
+        service: String,
+        from: Option<Box<IpConfig>>,
+        to: Box<IpConfig>,
+    },
+    Mtu {
+        service: String,
+        device: String,
+        from: Option<u32>,
+        to: u32,
+    },
+    /// The snapshot has a service that no longer exists, so it cannot be restored
+    MissingService {
+        service: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppliedChange {
+    pub change: SnapshotChange,
+    pub success: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreResult {
+    pub dry_run: bool,
+    pub changes: Vec<AppliedChange>,
+}
+
+/// Only the fields that decide proxy behaviour; a disabled proxy's server is irrelevant
+fn proxy_differs(from: &ProxyConfig, to: &ProxyConfig) -> bool {
+    from.enabled != to.enabled || (to.enabled && (from.server != to.server || from.port != to.port))
+}
+
+/// Target service order restricted to services that still exist, with new services kept
+/// at the end in their current relative order
+fn restorable_order(current: &[String], target: &[String]) -> Vec<String> {
+    let mut order: Vec<String> = target
+        .iter()
+        .filter(|name| current.contains(name))
+        .cloned()
+        .collect();
+    for name in current {
+        if !order.contains(name) {
+            order.push(name.clone());
+        }
+    }
+    order
+}
+
+/// Compute the changes needed to turn `current` into `target`
+pub fn diff_snapshots(current: &NetworkSnapshot, target: &NetworkSnapshot) -> Vec<SnapshotChange> {
+    let mut changes = Vec::new();
+
+    let order = restorable_order(&current.service_order, &target.service_order);
+    if order != current.service_order {
+        changes.push(SnapshotChange::ServiceOrder {
+            from: current.service_order.clone(),
+            to: order,
+        });
+    }
+
+    for wanted in &target.services {
+        let Some(existing) = current.services.iter().find(|s| s.name == wanted.name) else {
+            changes.push(SnapshotChange::MissingService {
+                service: wanted.name.clone(),
+            });
+            continue;
+        };
+        let service = wanted.name.clone();
+
+        if existing.dns_servers != wanted.dns_servers {
+            changes.push(SnapshotChange::DnsServers {
+                service: service.clone(),
+                from: existing.dns_servers.clone(),
+                to: wanted.dns_servers.clone(),
+            });
+        }
+
+        if existing.search_domains != wanted.search_domains {
+            changes.push(SnapshotChange::SearchDomains {
+                service: service.clone(),
+                from: existing.search_domains.clone(),
+                to: wanted.search_domains.clone(),
+            });
+        }
+
+        for (kind, from, to) in [
+            (
+                ProxyKind::Http,
+                &existing.proxies.http_proxy,
+                &wanted.proxies.http_proxy,
+            ),
+            (
+                ProxyKind::Https,
+                &existing.proxies.https_proxy,
+                &wanted.proxies.https_proxy,
+            ),
+            (
+                ProxyKind::Socks,
+                &existing.proxies.socks_proxy,
+                &wanted.proxies.socks_proxy,
+            ),
+        ] {
+            if proxy_differs(from, to) {
+                changes.push(SnapshotChange::Proxy {
+                    service: service.clone(),
+                    kind,
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+        }
+
+        if let Some(to) = &wanted.ip_config {
+            let unchanged = existing
+                .ip_config
+                .as_ref()
+                .is_some_and(|from| from.same_settings(to));
+            if !unchanged {
+                changes.push(SnapshotChange::IpConfig {
+                    service: service.clone(),
+                    from: existing.ip_config.clone().map(Box::new),
+                    to: Box::new(to.clone()),
+                });
+            }
+        }
+
+        if let Some(to) = wanted.mtu {
+            if existing.mtu != Some(to) {
+                changes.push(SnapshotChange::Mtu {
+                    service: service.clone(),
+                    device: existing.device.clone(),
+                    from: existing.mtu,
+                    to,
+                });
+            }
+        }
+    }
+
+    changes
+}
+
+/// Apply a single change through the existing setters
+fn apply_change(change: &SnapshotChange) -> Result<String, String> {
+    match change {
+        SnapshotChange::ServiceOrder { to, .. } => set_service_order(to.clone()),
+        SnapshotChange::DnsServers { service, to, .. } => {
+            set_dns_servers(service.clone(), to.clone())
+        }
+        SnapshotChange::SearchDomains { service, to, .. } => {
+            set_search_domains(service.clone(), to.clone())
+        }
+        SnapshotChange::Proxy {
+            service, kind, to, ..
+        } => {
+            let setter = match kind {
+                ProxyKind::Http => set_http_proxy,
+                ProxyKind::Https => set_https_proxy,
+                ProxyKind::Socks => set_socks_proxy,
+            };
+            setter(service.clone(), to.enabled, to.server.clone(), to.port)
+        }
+        SnapshotChange::IpConfig { to, .. } => apply_ip_config(to),
+        SnapshotChange::Mtu { device, to, .. } => set_mtu(device.clone(), *to),
+        SnapshotChange::MissingService { service } => {
+            Err(format!("Network service '{}' no longer exists", service))
+        }
+    }
+}
+
+/// Capture service order, DNS, search domains, proxies, IP configuration and MTU
+#[tauri::command]
+pub fn create_snapshot() -> Result<NetworkSnapshot, String> {
+    let service_order = get_service_order_internal()?;
+
+    let services = get_network_services()?
+        .into_iter()
+        .map(|service| {
+            let name = service.name;
+            let mtu = get_mtu(service.device.clone())
+                .ok()
+                .map(|info| info.current_setting.unwrap_or(info.active));
+
+            Ok(ServiceSnapshot {
+                dns_servers: get_dns_servers(name.clone())?.servers,
+                search_domains: get_search_domains(name.clone())?,
+                proxies: get_proxy_settings(name.clone())?,
+                ip_config: get_ip_config_internal(&name).ok(),
+                mtu,
+                device: service.device,
+                name,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    Ok(NetworkSnapshot {
+        version: SNAPSHOT_VERSION,
+        created_at,
+        service_order,
+        services,
+    })
+}
+
+/// Bring the system back to `snapshot`, touching only the settings that differ
+#[tauri::command]
+pub fn restore_snapshot(
+    snapshot: NetworkSnapshot,
+    dry_run: Option<bool>,
+) -> Result<RestoreResult, String> {
+    if snapshot.version > SNAPSHOT_VERSION {
+        return Err(format!(
+            "Snapshot version {} is newer than supported version {}",
+            snapshot.version, SNAPSHOT_VERSION
+        ));
+    }
+
+    let dry_run = dry_run.unwrap_or(false);
+    let current = create_snapshot()?;
+
+    let changes = diff_snapshots(&current, &snapshot)
+        .into_iter()
+        .map(|change| {
+            let result = match change {
+                SnapshotChange::MissingService { .. } => apply_change(&change),
+                _ if dry_run => Ok("Pending".to_string()),
+                _ => apply_change(&change),
+            };
+            let (success, message) = match result {
+                Ok(message) => (true, message),
+                Err(message) => (false, message),
+            };
+            AppliedChange {
+                change,
+                success,
+                message,
+            }
+        })
+        .collect();
+
+    Ok(RestoreResult { dry_run, changes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ip::{IpMode, Ipv6Mode};
+    use super::*;
+
+    fn ip_config(service: &str, mode: IpMode, address: &str, router: &str) -> IpConfig {
+        IpConfig {
+            service_name: service.to_string(),
+            mode,
+            ip_address: Some(address.to_string()),
+            subnet_mask: Some("255.255.255.0".to_string()),
+            router: Some(router.to_string()),
+            client_id: None,
+            ipv6_mode: Ipv6Mode::Automatic,
+            ipv6_addresses: Vec::new(),
+            ipv6_prefix_length: None,
+            ipv6_router: None,
+        }
+    }
+
+    fn service(name: &str, device: &str) -> ServiceSnapshot {
+        ServiceSnapshot {
+            name: name.to_string(),
+            device: device.to_string(),
+            dns_servers: vec!["1.1.1.1".to_string()],
+            search_domains: Vec::new(),
+            proxies: ProxySettings::default(),
+            ip_config: Some(ip_config(name, IpMode::Dhcp, "192.168.1.20", "192.168.1.1")),
+            mtu: Some(1500),
+        }
+    }
+
+    fn snapshot(services: Vec<ServiceSnapshot>) -> NetworkSnapshot {
+        NetworkSnapshot {
+            version: SNAPSHOT_VERSION,
+            created_at: 1_700_000_000,
+            service_order: services.iter().map(|s| s.name.clone()).collect(),
+            services,
+        }
+    }
+
+    fn proxy(enabled: bool, server: &str, port: u16) -> ProxyConfig {
+        ProxyConfig {
+            enabled,
+            server: server.to_string(),
+            port,
+        }
+    }
+
+    fn names(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn identical_snapshots_need_no_changes() {
+        let current = snapshot(vec![service("Wi-Fi", "en0"), service("Ethernet", "en5")]);
+        let mut target = current.clone();
+        target.created_at += 3600;
+
+        assert!(diff_snapshots(&current, &target).is_empty());
+    }
+
+    #[test]
+    fn removed_service_is_reported_and_left_out_of_the_order() {
+        let current = snapshot(vec![service("Wi-Fi", "en0")]);
+        let target = snapshot(vec![service("USB LAN", "en7"), service("Wi-Fi", "en0")]);
+
+        assert_eq!(
+            diff_snapshots(&current, &target),
+            [SnapshotChange::MissingService {
+                service: "USB LAN".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn added_service_keeps_its_place_at_the_end() {
+        let current = snapshot(vec![
+            service("Thunderbolt Bridge", "bridge0"),
+            service("Ethernet", "en5"),
+            service("Wi-Fi", "en0"),
+        ]);
+        let mut target = snapshot(vec![service("Wi-Fi", "en0"), service("Ethernet", "en5")]);
+
+        assert_eq!(
+            diff_snapshots(&current, &target),
+            [SnapshotChange::ServiceOrder {
+                from: names(&["Thunderbolt Bridge", "Ethernet", "Wi-Fi"]),
+                to: names(&["Wi-Fi", "Ethernet", "Thunderbolt Bridge"]),
+            }]
+        );
+
+        // Same relative order: nothing to reorder, the new service is left alone
+        target.service_order = names(&["Ethernet", "Wi-Fi"]);
+        let current = snapshot(vec![
+            service("Ethernet", "en5"),
+            service("Wi-Fi", "en0"),
+            service("iPhone USB", "en8"),
+        ]);
+        assert!(diff_snapshots(&current, &target).is_empty());
+    }
+
+    #[test]
+    fn dns_and_search_domain_changes() {
+        let current = snapshot(vec![service("Wi-Fi", "en0")]);
+        let mut target = current.clone();
+        target.services[0].dns_servers = names(&["8.8.8.8", "8.8.4.4"]);
+        target.services[0].search_domains = names(&["corp.example"]);
+
+        assert_eq!(
+            diff_snapshots(&current, &target),
+            [
+                SnapshotChange::DnsServers {
+                    service: "Wi-Fi".to_string(),
+                    from: names(&["1.1.1.1"]),
+                    to: names(&["8.8.8.8", "8.8.4.4"]),
+                },
+                SnapshotChange::SearchDomains {
+                    service: "Wi-Fi".to_string(),
+                    from: Vec::new(),
+                    to: names(&["corp.example"]),
+                },
+            ]
+        );
+
+        // Clearing manual servers goes back to the DHCP-provided ones
+        let mut dhcp_dns = current.clone();
+        dhcp_dns.services[0].dns_servers.clear();
+        assert_eq!(
+            diff_snapshots(&current, &dhcp_dns),
+            [SnapshotChange::DnsServers {
+                service: "Wi-Fi".to_string(),
+                from: names(&["1.1.1.1"]),
+                to: Vec::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn proxy_changes() {
+        let mut current = snapshot(vec![service("Wi-Fi", "en0")]);
+        current.services[0].proxies.https_proxy = proxy(true, "127.0.0.1", 7890);
+        current.services[0].proxies.socks_proxy = proxy(false, "old.example", 1080);
+        let mut target = current.clone();
+        // Added, removed and changed
+        target.services[0].proxies.http_proxy = proxy(true, "proxy.corp.example", 3128);
+        target.services[0].proxies.https_proxy = proxy(false, "127.0.0.1", 7890);
+        // A disabled proxy's server does not matter
+        target.services[0].proxies.socks_proxy = proxy(false, "new.example", 1081);
+
+        let changes = diff_snapshots(&current, &target);
+        assert_eq!(
+            changes,
+            [
+                SnapshotChange::Proxy {
+                    service: "Wi-Fi".to_string(),
+                    kind: ProxyKind::Http,
+                    from: ProxyConfig::default(),
+                    to: proxy(true, "proxy.corp.example", 3128),
+                },
+                SnapshotChange::Proxy {
+                    service: "Wi-Fi".to_string(),
+                    kind: ProxyKind::Https,
+                    from: proxy(true, "127.0.0.1", 7890),
+                    to: proxy(false, "127.0.0.1", 7890),
+                },
+            ]
+        );
+
+        target.services[0].proxies.https_proxy = proxy(true, "127.0.0.1", 7891);
+        let changes = diff_snapshots(&current, &target);
+        assert!(changes.contains(&SnapshotChange::Proxy {
+            service: "Wi-Fi".to_string(),
+            kind: ProxyKind::Https,
+            from: proxy(true, "127.0.0.1", 7890),
+            to: proxy(true, "127.0.0.1", 7891),
+        }));
+    }
+
+    #[test]
+    fn router_and_address_changes() {
+        let mut current = snapshot(vec![service("Ethernet", "en5")]);
+        current.services[0].ip_config = Some(ip_config(
+            "Ethernet",
+            IpMode::Manual,
+            "10.0.0.5",
+            "10.0.0.1",
+        ));
+        let mut target = current.clone();
+        target.services[0].ip_config = Some(ip_config(
+            "Ethernet",
+            IpMode::Manual,
+            "10.0.0.5",
+            "10.0.0.254",
+        ));
+
+        assert_eq!(
+            diff_snapshots(&current, &target),
+            [SnapshotChange::IpConfig {
+                service: "Ethernet".to_string(),
+                from: current.services[0].ip_config.clone().map(Box::new),
+                to: Box::new(target.services[0].ip_config.clone().unwrap()),
+            }]
+        );
+
+        // Manual back to DHCP
+        let target = snapshot(vec![service("Ethernet", "en5")]);
+        assert!(matches!(
+            diff_snapshots(&current, &target).as_slice(),
+            [SnapshotChange::IpConfig { to, .. }] if to.mode == IpMode::Dhcp
+        ));
+    }
+
+    #[test]
+    fn dhcp_leases_and_unknown_values_are_not_changes() {
+        let current = snapshot(vec![service("Wi-Fi", "en0")]);
+        let mut target = current.clone();
+        // A new lease with another address and router is the same DHCP setting
+        target.services[0].ip_config = Some(ip_config(
+            "Wi-Fi",
+            IpMode::Dhcp,
+            "172.20.10.3",
+            "172.20.10.1",
+        ));
+        assert!(diff_snapshots(&current, &target).is_empty());
+
+        // Values the snapshot could not read are left as they are
+        target.services[0].ip_config = None;
+        target.services[0].mtu = None;
+        assert!(diff_snapshots(&current, &target).is_empty());
+
+        // ...but a value that cannot be read now is restored
+        let mut unreadable = current.clone();
+        unreadable.services[0].ip_config = None;
+        assert!(matches!(
+            diff_snapshots(&unreadable, &current).as_slice(),
+            [SnapshotChange::IpConfig { from: None, .. }]
+        ));
+    }
+
+    #[test]
+    fn mtu_change_targets_the_current_device() {
+        let current = snapshot(vec![service("USB LAN", "en8")]);
+        let mut target = snapshot(vec![service("USB LAN", "en7")]);
+        target.services[0].mtu = Some(1400);
+
+        assert_eq!(
+            diff_snapshots(&current, &target),
+            [SnapshotChange::Mtu {
+                service: "USB LAN".to_string(),
+                device: "en8".to_string(),
+                from: Some(1500),
+                to: 1400,
+            }]
+        );
+    }
+
+    #[test]
+    fn reads_snapshots_without_the_ipv6_and_client_id_fields() {
+        // Version 1 snapshots written before IpConfig gained its IPv6 and client ID fields
+        let json = r#"{
+            "version": 1,
+            "created_at": 1700000000,
+            "service_order": ["Wi-Fi"],
+            "services": [{
+                "name": "Wi-Fi",
+                "device": "en0",
+                "dns_servers": [],
+                "search_domains": [],
+                "proxies": {
+                    "http_proxy": {"enabled": false, "server": "", "port": 0},
+                    "https_proxy": {"enabled": false, "server": "", "port": 0},
+                    "socks_proxy": {"enabled": false, "server": "", "port": 0},
+                    "service_name": "Wi-Fi"
+                },
+                "ip_config": {
+                    "service_name": "Wi-Fi",
+                    "mode": "Manual",
+                    "ip_address": "192.168.1.20",
+                    "subnet_mask": "255.255.255.0",
+                    "router": "192.168.1.1"
+                },
+                "mtu": 1500
+            }]
+        }"#;
+
+        let old: NetworkSnapshot = serde_json::from_str(json).unwrap();
+        let config = old.services[0].ip_config.as_ref().unwrap();
+        assert_eq!(config.mode, IpMode::Manual);
+        assert_eq!(config.client_id, None);
+        assert_eq!(config.ipv6_mode, Ipv6Mode::Unknown);
+        assert!(config.ipv6_addresses.is_empty());
+
+        let mut current = old.clone();
+        current.services[0].ip_config = Some(ip_config(
+            "Wi-Fi",
+            IpMode::Manual,
+            "192.168.1.20",
+            "192.168.1.1",
+        ));
+        assert!(diff_snapshots(&current, &old).is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+//! Minimal HTTP/1.1 server for tests that exercise commands through curl
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+pub struct Request {
+    pub method: String,
+    /// Path including the query string
+    pub path: String,
+    /// Size of the (possibly chunked) body, which is read and discarded
+    pub body_len: u64,
+}
+
+impl Request {
+    pub fn query(&self, key: &str) -> Option<&str> {
+        let (_, query) = self.path.split_once('?')?;
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v)
+    }
+}
+
+pub struct TestServer {
+    pub addr: SocketAddr,
+}
+
+impl TestServer {
+    /// Serve every request on a new thread with `handler`, which writes the response
+    pub fn start<F>(handler: F) -> TestServer
+    where
+        F: Fn(&Request, &mut TcpStream) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handler = Arc::new(handler);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = handler.clone();
+                thread::spawn(move || {
+                    let mut stream = stream;
+                    // Latency probes connect and close without sending a request
+                    if let Some(request) = read_request(&mut stream) {
+                        handler(&request, &mut stream);
+                    }
+                });
+            }
+        });
+
+        TestServer { addr }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+}
+
+/// A port nothing listens on
+pub fn closed_port_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{}/", addr)
+}
+
+fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.clone())
+    };
+
+    if header("expect").is_some_and(|v| v.eq_ignore_ascii_case("100-continue")) {
+        stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").ok()?;
+    }
+
+    let mut body_len = 0;
+    if header("transfer-encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked")) {
+        loop {
+            let mut size = String::new();
+            reader.read_line(&mut size).ok()?;
+            let size = u64::from_str_radix(size.trim().split(';').next()?, 16).ok()?;
+            if size == 0 {
+                let mut trailer = String::new();
+                reader.read_line(&mut trailer).ok()?;
+                break;
+            }
+            body_len += std::io::copy(&mut (&mut reader).take(size), &mut std::io::sink()).ok()?;
+            let mut crlf = String::new();
+            reader.read_line(&mut crlf).ok()?;
+        }
+    } else if let Some(length) = header("content-length").and_then(|v| v.parse::<u64>().ok()) {
+        body_len = std::io::copy(&mut (&mut reader).take(length), &mut std::io::sink()).ok()?;
+    }
+
+    Some(Request {
+        method,
+        path,
+        body_len,
+    })
+}
+
+/// Write a complete response with a fixed body
+pub fn respond(stream: &mut TcpStream, status: &str, headers: &[(&str, &str)], body: &[u8]) {
+    let mut head = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (key, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    head.push_str("\r\n");
+
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(body);
+}
@@ -0,0 +1,645 @@
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, sleep, JoinHandle};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Event carrying a `ThroughputProgress` while a measurement runs
+pub const PROGRESS_EVENT: &str = "throughput-progress";
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+const LATENCY_INTERVAL: Duration = Duration::from_millis(200);
+const LATENCY_TIMEOUT: Duration = Duration::from_secs(2);
+const IDLE_LATENCY_SAMPLES: usize = 5;
+const CHUNK_SIZE: usize = 64 * 1024;
+const DEFAULT_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum TransferDirection {
+    Download,
+    Upload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThroughputProgress {
+    pub direction: TransferDirection,
+    pub elapsed_ms: u64,
+    pub bytes: u64,
+    /// Rate over the last sample interval
+    pub mbps: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub max_ms: f64,
+    /// Mean difference between consecutive samples
+    pub jitter_ms: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DirectionResult {
+    pub direction: TransferDirection,
+    pub bytes: u64,
+    pub duration_ms: u64,
+    pub mbps: f64,
+    pub samples: Vec<ThroughputProgress>,
+    pub loaded_latency: Option<LatencyStats>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThroughputResult {
+    pub download: Option<DirectionResult>,
+    pub upload: Option<DirectionResult>,
+    pub idle_latency: Option<LatencyStats>,
+    /// Largest rise of the median latency while a transfer was running
+    pub bufferbloat_ms: Option<f64>,
+    pub bufferbloat_grade: Option<String>,
+}
+
+/// Receives progress samples while a measurement runs
+pub trait ProgressSink: Sync {
+    fn progress(&self, progress: &ThroughputProgress);
+}
+
+impl ProgressSink for AppHandle {
+    fn progress(&self, progress: &ThroughputProgress) {
+        let _ = self.emit(PROGRESS_EVENT, progress.clone());
+    }
+}
+
+pub fn mbps(bytes: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds <= 0.0 {
+        return 0.0;
+    }
+    (bytes as f64 * 8.0 / 1_000_000.0 / seconds * 100.0).round() / 100.0
+}
+
+pub fn summarize_latency(samples: &[f64]) -> Option<LatencyStats> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    let median = if sorted.len() % 2 == 1 {
+        sorted[middle]
+    } else {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    };
+    let jitter = if samples.len() > 1 {
+        samples.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<f64>() / (samples.len() - 1) as f64
+    } else {
+        0.0
+    };
+    let round = |value: f64| (value * 10.0).round() / 10.0;
+
+    Some(LatencyStats {
+        samples: samples.len(),
+        min_ms: round(sorted[0]),
+        median_ms: round(median),
+        max_ms: round(sorted[sorted.len() - 1]),
+        jitter_ms: round(jitter),
+    })
+}
+
+/// Letter grade for the latency added under load
+pub fn bufferbloat_grade(increase_ms: f64) -> &'static str {
+    match increase_ms {
+        x if x < 5.0 => "A+",
+        x if x < 30.0 => "A",
+        x if x < 60.0 => "B",
+        x if x < 200.0 => "C",
+        x if x < 400.0 => "D",
+        _ => "F",
+    }
+}
+
+/// Host and port of an http(s) URL, resolved for the TCP latency probe
+fn endpoint(url: &str) -> Result<SocketAddr, String> {
+    let (scheme, rest) = url
+        .split_once("://")
+        .ok_or_else(|| format!("'{}' is not an http(s) URL", url))?;
+    let default_port = match scheme {
+        "http" => 80,
+        "https" => 443,
+        _ => return Err(format!("'{}' is not an http(s) URL", url)),
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+
+    // "host", "host:8080", "[::1]" or "[::1]:8080"
+    let has_port = match authority.rsplit_once(']') {
+        Some((_, after)) => after.starts_with(':'),
+        None => authority.contains(':'),
+    };
+    let address = if has_port {
+        authority.to_string()
+    } else {
+        format!("{}:{}", authority, default_port)
+    };
+
+    address
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", authority, e))?
+        .next()
+        .ok_or_else(|| format!("{} has no addresses", authority))
+}
+
+fn tcp_latency(address: SocketAddr) -> Option<f64> {
+    let start = Instant::now();
+    TcpStream::connect_timeout(&address, LATENCY_TIMEOUT).ok()?;
+    Some(start.elapsed().as_secs_f64() * 1000.0)
+}
+
+/// Measure TCP connect latency in the background until stopped
+struct LatencyProbe {
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<Vec<f64>>,
+}
+
+impl LatencyProbe {
+    fn start(address: SocketAddr) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        let handle = thread::spawn(move || {
+            let mut samples = Vec::new();
+            while !flag.load(Ordering::Relaxed) {
+                samples.extend(tcp_latency(address));
+                sleep(LATENCY_INTERVAL);
+            }
+            samples
+        });
+
+        LatencyProbe { stop, handle }
+    }
+
+    fn finish(self) -> Vec<f64> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.handle.join().unwrap_or_default()
+    }
+}
+
+/// Counts transferred bytes and reports progress every sample interval
+struct Meter<'a> {
+    sink: &'a dyn ProgressSink,
+    direction: TransferDirection,
+    started: Instant,
+    last_sample: Instant,
+    last_bytes: u64,
+    bytes: u64,
+    samples: Vec<ThroughputProgress>,
+}
+
+impl<'a> Meter<'a> {
+    fn new(sink: &'a dyn ProgressSink, direction: TransferDirection) -> Self {
+        let now = Instant::now();
+        Meter {
+            sink,
+            direction,
+            started: now,
+            last_sample: now,
+            last_bytes: 0,
+            bytes: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, bytes: usize) {
+        self.bytes += bytes as u64;
+        if self.last_sample.elapsed() >= SAMPLE_INTERVAL {
+            self.sample();
+        }
+    }
+
+    fn sample(&mut self) {
+        let progress = ThroughputProgress {
+            direction: self.direction,
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            bytes: self.bytes,
+            mbps: mbps(self.bytes - self.last_bytes, self.last_sample.elapsed()),
+        };
+        self.sink.progress(&progress);
+        self.samples.push(progress);
+        self.last_sample = Instant::now();
+        self.last_bytes = self.bytes;
+    }
+
+    fn finish(mut self, loaded: Vec<f64>, error: Option<String>) -> DirectionResult {
+        if self.bytes > self.last_bytes {
+            self.sample();
+        }
+        let elapsed = self.started.elapsed();
+
+        DirectionResult {
+            direction: self.direction,
+            bytes: self.bytes,
+            duration_ms: elapsed.as_millis() as u64,
+            mbps: mbps(self.bytes, elapsed),
+            samples: self.samples,
+            loaded_latency: summarize_latency(&loaded),
+            error,
+        }
+    }
+}
+
+fn spawn_curl(args: &[&str], url: &str, max_duration: Duration) -> Result<Child, String> {
+    Command::new("curl")
+        .args(["-s", "-S", "--fail", "--max-time"])
+        .arg(max_duration.as_secs().max(1).to_string())
+        .args(args)
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute curl: {}", e))
+}
+
+/// Collect curl's error unless it was stopped on purpose
+fn curl_error(child: &mut Child, stopped: bool) -> Option<String> {
+    let status = child.wait().ok()?;
+    if status.success() || stopped {
+        return None;
+    }
+
+    let mut stderr = String::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr);
+    }
+    Some(format!(
+        "curl exited with {}: {}",
+        status.code().unwrap_or(-1),
+        stderr.trim()
+    ))
+}
+
+fn measure_download(
+    sink: &dyn ProgressSink,
+    url: &str,
+    address: SocketAddr,
+    max_duration: Duration,
+) -> Result<DirectionResult, String> {
+    // Transfers are stopped by the timer below, curl's limit is only a safety net
+    let mut child = spawn_curl(&[], url, max_duration + Duration::from_secs(5))?;
+    drop(child.stdin.take());
+    let mut stdout = child.stdout.take().ok_or("curl has no stdout")?;
+
+    let probe = LatencyProbe::start(address);
+    let mut meter = Meter::new(sink, TransferDirection::Download);
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut stopped = false;
+    loop {
+        match stdout.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => meter.add(read),
+        }
+        if meter.started.elapsed() >= max_duration {
+            let _ = child.kill();
+            stopped = true;
+            break;
+        }
+    }
+    let loaded = probe.finish();
+    let error = curl_error(&mut child, stopped);
+
+    Ok(meter.finish(loaded, error))
+}
+
+/// Stream generated bytes into curl. Bytes are counted as curl accepts them, so
+/// the rate includes the pipe and socket buffers.
+fn measure_upload(
+    sink: &dyn ProgressSink,
+    url: &str,
+    address: SocketAddr,
+    max_duration: Duration,
+    upload_bytes: u64,
+) -> Result<DirectionResult, String> {
+    let mut child = spawn_curl(
+        &["-T", "-", "-X", "POST", "-o", "/dev/null"],
+        url,
+        max_duration + Duration::from_secs(5),
+    )?;
+    let mut stdin = child.stdin.take().ok_or("curl has no stdin")?;
+
+    let probe = LatencyProbe::start(address);
+    let mut meter = Meter::new(sink, TransferDirection::Upload);
+    let chunk = vec![0u8; CHUNK_SIZE];
+    let mut stopped = false;
+    while meter.bytes < upload_bytes {
+        let size = (upload_bytes - meter.bytes).min(CHUNK_SIZE as u64) as usize;
+        if stdin.write_all(&chunk[..size]).is_err() {
+            break;
+        }
+        meter.add(size);
+        if meter.started.elapsed() >= max_duration {
+            let _ = child.kill();
+            stopped = true;
+            break;
+        }
+    }
+    drop(stdin);
+    let error = curl_error(&mut child, stopped);
+    let loaded = probe.finish();
+
+    Ok(meter.finish(loaded, error))
+}
+
+/// Measure against the given endpoints, reporting progress to `sink`. Latency is
+/// sampled with TCP connects to the endpoint before and during each transfer.
+pub fn run_throughput(
+    sink: &dyn ProgressSink,
+    download_url: Option<&str>,
+    upload_url: Option<&str>,
+    upload_bytes: u64,
+    max_duration: Duration,
+) -> Result<ThroughputResult, String> {
+    let Some(probe_url) = download_url.or(upload_url) else {
+        return Err("A download or upload URL is required".to_string());
+    };
+    let address = endpoint(probe_url)?;
+    let download_address = download_url.map(endpoint).transpose()?;
+    let upload_address = upload_url.map(endpoint).transpose()?;
+
+    let idle: Vec<f64> = (0..IDLE_LATENCY_SAMPLES)
+        .filter_map(|_| {
+            let latency = tcp_latency(address);
+            sleep(LATENCY_INTERVAL);
+            latency
+        })
+        .collect();
+    let idle_latency = summarize_latency(&idle);
+
+    let download = match (download_url, download_address) {
+        (Some(url), Some(address)) => Some(measure_download(sink, url, address, max_duration)?),
+        _ => None,
+    };
+    let upload = match (upload_url, upload_address) {
+        (Some(url), Some(address)) => Some(measure_upload(
+            sink,
+            url,
+            address,
+            max_duration,
+            upload_bytes,
+        )?),
+        _ => None,
+    };
+
+    let bufferbloat_ms = idle_latency.as_ref().and_then(|idle| {
+        [&download, &upload]
+            .into_iter()
+            .flatten()
+            .filter_map(|result| result.loaded_latency.as_ref())
+            .map(|loaded| (loaded.median_ms - idle.median_ms).max(0.0))
+            .reduce(f64::max)
+    });
+
+    Ok(ThroughputResult {
+        download,
+        upload,
+        idle_latency,
+        bufferbloat_grade: bufferbloat_ms.map(|ms| bufferbloat_grade(ms).to_string()),
+        bufferbloat_ms,
+    })
+}
+
+/// Measure download and upload speed against HTTP endpoints.
+///
+/// `download_url` must serve a large body and `upload_url` must accept a POST of
+/// `upload_bytes`. Progress is emitted as `throughput-progress` events; the command
+/// runs off the main thread so the UI stays responsive while they arrive.
+#[tauri::command(async)]
+pub fn measure_throughput(
+    app: AppHandle,
+    download_url: Option<String>,
+    upload_url: Option<String>,
+    upload_bytes: Option<u64>,
+    max_duration_secs: Option<u64>,
+) -> Result<ThroughputResult, String> {
+    let download_url = download_url.filter(|u| !u.is_empty());
+    let upload_url = upload_url.filter(|u| !u.is_empty());
+    let max_duration = Duration::from_secs(max_duration_secs.unwrap_or(10).clamp(1, 60));
+
+    run_throughput(
+        &app,
+        download_url.as_deref(),
+        upload_url.as_deref(),
+        upload_bytes.unwrap_or(DEFAULT_UPLOAD_BYTES),
+        max_duration,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_http::{closed_port_url, respond, TestServer};
+    use super::*;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<ThroughputProgress>>);
+
+    impl ProgressSink for Recorder {
+        fn progress(&self, progress: &ThroughputProgress) {
+            self.0.lock().unwrap().push(progress.clone());
+        }
+    }
+
+    /// Serves `GET /download?bytes=N&rate=BYTES_PER_SEC` and accepts `POST /upload`,
+    /// recording how many bytes were uploaded
+    fn payload_server() -> (TestServer, Arc<AtomicU64>) {
+        let uploaded = Arc::new(AtomicU64::new(0));
+        let received = uploaded.clone();
+
+        let server = TestServer::start(move |request, stream| {
+            if request.method == "POST" {
+                received.store(request.body_len, Ordering::SeqCst);
+                respond(
+                    stream,
+                    "200 OK",
+                    &[],
+                    request.body_len.to_string().as_bytes(),
+                );
+                return;
+            }
+            if !request.path.starts_with("/download") {
+                respond(stream, "500 Internal Server Error", &[], b"");
+                return;
+            }
+
+            let total: u64 = request.query("bytes").and_then(|v| v.parse().ok()).unwrap();
+            let rate: u64 = request
+                .query("rate")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(u64::MAX);
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", total);
+            if stream.write_all(head.as_bytes()).is_err() {
+                return;
+            }
+            let chunk = [0u8; 16 * 1024];
+            let started = Instant::now();
+            let mut sent = 0;
+            while sent < total {
+                let size = (total - sent).min(chunk.len() as u64) as usize;
+                if stream.write_all(&chunk[..size]).is_err() {
+                    return;
+                }
+                sent += size as u64;
+                let due = Duration::from_secs_f64(sent as f64 / rate as f64);
+                if let Some(wait) = due.checked_sub(started.elapsed()) {
+                    sleep(wait);
+                }
+            }
+        });
+
+        (server, uploaded)
+    }
+
+    #[test]
+    fn downloads_the_whole_payload_with_progress() {
+        let (server, _) = payload_server();
+        let recorder = Recorder::default();
+        let url = server.url("/download?bytes=3000000&rate=4000000");
+
+        let result =
+            run_throughput(&recorder, Some(&url), None, 0, Duration::from_secs(10)).unwrap();
+
+        let download = result.download.unwrap();
+        assert_eq!(download.error, None);
+        assert_eq!(download.bytes, 3_000_000);
+        assert!(download.samples.len() >= 2, "{:?}", download.samples);
+        assert_eq!(download.samples.last().unwrap().bytes, 3_000_000);
+        assert!(download.mbps > 0.0);
+        assert!(download.loaded_latency.is_some());
+        assert_eq!(recorder.0.lock().unwrap().len(), download.samples.len());
+        assert!(result.upload.is_none());
+        assert!(result.idle_latency.is_some());
+        assert!(result.bufferbloat_grade.is_some());
+    }
+
+    #[test]
+    fn uploads_the_requested_size() {
+        let (server, uploaded) = payload_server();
+        let recorder = Recorder::default();
+        let url = server.url("/upload");
+
+        let result = run_throughput(
+            &recorder,
+            None,
+            Some(&url),
+            5_000_000,
+            Duration::from_secs(10),
+        )
+        .unwrap();
+
+        let upload = result.upload.unwrap();
+        assert_eq!(upload.error, None);
+        assert_eq!(upload.bytes, 5_000_000);
+        assert_eq!(uploaded.load(Ordering::SeqCst), 5_000_000);
+        assert!(recorder
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|p| p.direction == TransferDirection::Upload));
+    }
+
+    #[test]
+    fn stops_at_the_duration_limit() {
+        let (server, _) = payload_server();
+        let url = server.url("/download?bytes=1000000000&rate=2000000");
+
+        let result = run_throughput(
+            &Recorder::default(),
+            Some(&url),
+            None,
+            0,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+
+        let download = result.download.unwrap();
+        assert_eq!(download.error, None);
+        assert!(download.duration_ms < 2000, "{}", download.duration_ms);
+        assert!(download.bytes > 0 && download.bytes < 1_000_000_000);
+    }
+
+    #[test]
+    fn reports_http_and_connection_errors() {
+        let (server, _) = payload_server();
+        let url = server.url("/missing");
+        let result = run_throughput(
+            &Recorder::default(),
+            Some(&url),
+            None,
+            0,
+            Duration::from_secs(2),
+        )
+        .unwrap();
+        assert!(result.download.unwrap().error.unwrap().contains("22"));
+
+        let url = closed_port_url();
+        let result = run_throughput(
+            &Recorder::default(),
+            Some(&url),
+            None,
+            0,
+            Duration::from_secs(2),
+        )
+        .unwrap();
+        assert!(result.download.unwrap().error.is_some());
+        assert!(result.idle_latency.is_none());
+    }
+
+    #[test]
+    fn rejects_missing_and_non_http_urls() {
+        let sink = Recorder::default();
+        assert!(run_throughput(&sink, None, None, 0, Duration::from_secs(1)).is_err());
+        let result = run_throughput(&sink, Some("ftp://x/"), None, 0, Duration::from_secs(1));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn resolves_endpoints() {
+        assert_eq!(endpoint("http://127.0.0.1/x").unwrap().port(), 80);
+        assert_eq!(endpoint("https://127.0.0.1").unwrap().port(), 443);
+        assert_eq!(
+            endpoint("http://u:p@127.0.0.1:8080/?a").unwrap().port(),
+            8080
+        );
+        assert_eq!(endpoint("http://[::1]:81/").unwrap().port(), 81);
+        assert_eq!(endpoint("http://[::1]/").unwrap().port(), 80);
+    }
+
+    #[test]
+    fn summarizes_latency() {
+        let stats = summarize_latency(&[10.0, 20.0, 10.0, 30.0]).unwrap();
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.min_ms, 10.0);
+        assert_eq!(stats.median_ms, 15.0);
+        assert_eq!(stats.max_ms, 30.0);
+        assert_eq!(stats.jitter_ms, 13.3);
+        assert_eq!(summarize_latency(&[5.0]).unwrap().jitter_ms, 0.0);
+        assert!(summarize_latency(&[]).is_none());
+    }
+
+    #[test]
+    fn computes_rates_and_grades() {
+        assert_eq!(mbps(1_250_000, Duration::from_secs(1)), 10.0);
+        assert_eq!(mbps(1_000, Duration::ZERO), 0.0);
+        assert_eq!(bufferbloat_grade(2.0), "A+");
+        assert_eq!(bufferbloat_grade(45.0), "B");
+        assert_eq!(bufferbloat_grade(500.0), "F");
+    }
+}
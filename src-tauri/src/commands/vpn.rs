@@ -0,0 +1,508 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use super::interface::{list_interfaces, InterfaceDetails};
+use super::network::get_default_route;
+use super::route::{get_routing_table, RouteEntry};
+use super::runner::{invalidate_command_cache, system_runner};
+
+/// Interface name prefixes used by VPN and tunnel drivers
+const TUNNEL_PREFIXES: [&str; 5] = ["utun", "ipsec", "ppp", "tun", "tap"];
+
+/// Hardware ports of built-in VPN service types
+const VPN_PORTS: [&str; 5] = ["L2TP", "PPTP", "IPSec", "IKEv2", "VPN"];
+
+/// Halves of the IPv4 space VPN clients install to override the default route
+/// without replacing it
+const SPLIT_DEFAULT_ROUTES: [&str; 2] = ["0/1", "128.0/1"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum VpnStatus {
+    Connected,
+    Connecting,
+    Disconnecting,
+    Disconnected,
+    Invalid,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VpnService {
+    pub id: String,
+    pub name: String,
+    /// Connection type as shown by scutil, e.g. "IPSec" or "PPP --> L2TP"
+    pub vpn_type: String,
+    pub subtype: Option<String>,
+    pub enabled: bool,
+    pub status: VpnStatus,
+    /// macOS does not map connections to tunnel devices, so routing and DNS effects
+    /// of the active tunnels are attributed to every connected VPN
+    pub takes_default_route: bool,
+    pub injects_dns: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsResolver {
+    pub nameservers: Vec<String>,
+    pub domains: Vec<String>,
+    pub interface: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TunnelInterface {
+    pub name: String,
+    pub addresses: Vec<String>,
+    pub takes_default_route: bool,
+    pub dns_servers: Vec<String>,
+    pub dns_domains: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VpnState {
+    pub services: Vec<VpnService>,
+    /// Active tunnels, including ones created by VPN apps without a network service
+    pub tunnels: Vec<TunnelInterface>,
+    pub default_route_via_tunnel: bool,
+    pub dns_via_tunnel: bool,
+}
+
+impl VpnState {
+    /// A VPN or tunnel currently influences routing or name resolution
+    pub fn is_active(&self) -> bool {
+        self.default_route_via_tunnel
+            || self.dns_via_tunnel
+            || self
+                .services
+                .iter()
+                .any(|s| s.status == VpnStatus::Connected)
+    }
+
+    /// Name to show for the active VPN, falling back to the tunnel device
+    pub fn active_name(&self) -> Option<String> {
+        self.services
+            .iter()
+            .find(|s| s.status == VpnStatus::Connected)
+            .map(|s| s.name.clone())
+            .or_else(|| self.tunnels.first().map(|t| t.name.clone()))
+    }
+}
+
+/// A tunnel device is a tunnel prefix followed by its unit number, e.g. "utun4"
+pub fn is_tunnel_interface(name: &str) -> bool {
+    TUNNEL_PREFIXES.iter().any(|prefix| {
+        name.strip_prefix(prefix)
+            .is_some_and(|unit| !unit.is_empty() && unit.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// Whether a service from `-listnetworkserviceorder` is a VPN rather than hardware.
+/// Third-party VPNs use their bundle id as the port, e.g. "com.wireguard.macos".
+pub fn is_vpn_port(hardware_port: &str, device: &str) -> bool {
+    VPN_PORTS.contains(&hardware_port)
+        || (device.is_empty() && hardware_port.contains('.'))
+        || is_tunnel_interface(device)
+}
+
+fn parse_status(value: &str) -> VpnStatus {
+    match value {
+        "Connected" => VpnStatus::Connected,
+        "Connecting" => VpnStatus::Connecting,
+        "Disconnecting" => VpnStatus::Disconnecting,
+        "Disconnected" => VpnStatus::Disconnected,
+        "Invalid" => VpnStatus::Invalid,
+        _ => VpnStatus::Unknown,
+    }
+}
+
+/// Parse `scutil --nc list` output, e.g.
+/// `* (Connected)  1A2B-... IPSec  "Office"  [IPSec]`
+pub fn parse_nc_list(output: &str) -> Vec<VpnService> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let enabled = line.starts_with('*');
+            let rest = line.trim_start_matches('*').trim_start();
+            let (status, rest) = rest.strip_prefix('(')?.split_once(')')?;
+            let (id, rest) = rest.trim_start().split_once(char::is_whitespace)?;
+            let (vpn_type, rest) = rest.split_once('"')?;
+            let (name, rest) = rest.split_once('"')?;
+            let subtype = rest
+                .trim()
+                .strip_prefix('[')
+                .and_then(|s| s.strip_suffix(']'))
+                .map(|s| s.to_string());
+
+            Some(VpnService {
+                id: id.to_string(),
+                name: name.to_string(),
+                vpn_type: vpn_type.trim().to_string(),
+                subtype,
+                enabled,
+                status: parse_status(status.trim()),
+                takes_default_route: false,
+                injects_dns: false,
+            })
+        })
+        .collect()
+}
+
+/// Parse the unscoped resolvers of `scutil --dns`
+pub fn parse_scutil_dns(output: &str) -> Vec<DnsResolver> {
+    let mut resolvers: Vec<DnsResolver> = Vec::new();
+
+    for line in output.lines() {
+        let line = line.trim();
+        // Scoped resolvers repeat the per-interface configuration
+        if line.starts_with("DNS configuration (for scoped queries)") {
+            break;
+        }
+        if line.starts_with("resolver #") {
+            resolvers.push(DnsResolver {
+                nameservers: Vec::new(),
+                domains: Vec::new(),
+                interface: None,
+            });
+            continue;
+        }
+
+        let (Some(resolver), Some((key, value))) = (resolvers.last_mut(), line.split_once(':'))
+        else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if key.starts_with("nameserver[") {
+            resolver.nameservers.push(value.to_string());
+        } else if key == "domain" || key.starts_with("search domain[") {
+            resolver.domains.push(value.to_string());
+        } else if key == "if_index" {
+            // "23 (utun3)"
+            resolver.interface = value
+                .split_once('(')
+                .map(|(_, name)| name.trim_end_matches(')').to_string());
+        }
+    }
+
+    resolvers
+}
+
+/// Combine the VPN services, interfaces, routes and resolvers into one view
+pub fn assess_vpn(
+    mut services: Vec<VpnService>,
+    interfaces: &[InterfaceDetails],
+    routes: &[RouteEntry],
+    default_interface: Option<&str>,
+    resolvers: &[DnsResolver],
+) -> VpnState {
+    let tunnels: Vec<TunnelInterface> = interfaces
+        .iter()
+        .filter(|details| is_tunnel_interface(&details.name) && details.has_flag("UP"))
+        .filter_map(|details| {
+            // System tunnels (iCloud Private Relay, AirDrop) only carry link-local IPv6
+            let addresses: Vec<String> = details
+                .ipv4
+                .iter()
+                .map(|a| a.address.clone())
+                .chain(
+                    details
+                        .ipv6
+                        .iter()
+                        .filter(|a| !a.address.starts_with("fe80:"))
+                        .map(|a| a.address.clone()),
+                )
+                .collect();
+            if addresses.is_empty() {
+                return None;
+            }
+
+            let takes_default_route = default_interface == Some(details.name.as_str())
+                || routes.iter().any(|r| {
+                    r.netif.as_deref() == Some(details.name.as_str())
+                        && SPLIT_DEFAULT_ROUTES.contains(&r.destination.as_str())
+                });
+            let dns: Vec<&DnsResolver> = resolvers
+                .iter()
+                .filter(|r| r.interface.as_deref() == Some(details.name.as_str()))
+                .collect();
+
+            Some(TunnelInterface {
+                name: details.name.clone(),
+                addresses,
+                takes_default_route,
+                dns_servers: dns.iter().flat_map(|r| r.nameservers.clone()).collect(),
+                dns_domains: dns.iter().flat_map(|r| r.domains.clone()).collect(),
+            })
+        })
+        .collect();
+
+    let default_route_via_tunnel = tunnels.iter().any(|t| t.takes_default_route);
+    let dns_via_tunnel = tunnels.iter().any(|t| !t.dns_servers.is_empty());
+    for service in services
+        .iter_mut()
+        .filter(|s| s.status == VpnStatus::Connected)
+    {
+        service.takes_default_route = default_route_via_tunnel;
+        service.injects_dns = dns_via_tunnel;
+    }
+
+    VpnState {
+        services,
+        tunnels,
+        default_route_via_tunnel,
+        dns_via_tunnel,
+    }
+}
+
+fn list_vpn_services() -> Result<Vec<VpnService>, String> {
+    let stdout = system_runner().run("scutil", &["--nc", "list"])?;
+
+    Ok(parse_nc_list(&stdout))
+}
+
+/// Resolve a VPN by name or service id
+fn find_vpn(name: &str) -> Result<VpnService, String> {
+    if name.is_empty() {
+        return Err("VPN name cannot be empty".to_string());
+    }
+
+    list_vpn_services()?
+        .into_iter()
+        .find(|s| s.name == name || s.id == name)
+        .ok_or_else(|| format!("VPN '{}' does not exist", name))
+}
+
+fn run_scutil_nc(action: &str, vpn: &VpnService) -> Result<(), String> {
+    let output = Command::new("scutil")
+        .args(["--nc", action, &vpn.id])
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+    invalidate_command_cache();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || stdout.contains("No service") {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("命令执行失败: {}{}", stdout.trim(), stderr.trim()));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_vpn_status() -> Result<VpnState, String> {
+    let services = list_vpn_services()?;
+    let interfaces = list_interfaces().unwrap_or_default();
+    let routes = get_routing_table().unwrap_or_default();
+    let default_interface = get_default_route().map(|route| route.interface);
+    let resolvers = system_runner()
+        .run("scutil", &["--dns"])
+        .map(|stdout| parse_scutil_dns(&stdout))
+        .unwrap_or_default();
+
+    Ok(assess_vpn(
+        services,
+        &interfaces,
+        &routes,
+        default_interface.as_deref(),
+        &resolvers,
+    ))
+}
+
+#[tauri::command]
+pub fn start_vpn(name: String) -> Result<String, String> {
+    let vpn = find_vpn(&name)?;
+    if vpn.status == VpnStatus::Connected {
+        return Ok(format!("VPN '{}' is already connected", vpn.name));
+    }
+
+    run_scutil_nc("start", &vpn)?;
+
+    Ok(format!("VPN '{}' is connecting", vpn.name))
+}
+
+#[tauri::command]
+pub fn stop_vpn(name: String) -> Result<String, String> {
+    let vpn = find_vpn(&name)?;
+    if vpn.status == VpnStatus::Disconnected {
+        return Ok(format!("VPN '{}' is already disconnected", vpn.name));
+    }
+
+    run_scutil_nc("stop", &vpn)?;
+
+    Ok(format!("VPN '{}' disconnected", vpn.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::interface::parse_ifconfig;
+    use super::super::route::parse_netstat_routes;
+    use super::*;
+
+    const NC_LIST: &str = "\
+Available network connection services in the current set (*=enabled):
+* (Connected)      6A1B2C3D-0001 IPSec              \"Office\"          [IPSec]
+* (Disconnected)   6A1B2C3D-0002 PPP --> L2TP       \"Lab L2TP\"        [PPP/L2TP]
+  (Disconnected)   6A1B2C3D-0003 VPN (io.example.vpn) \"Home\"          [VPN/io.example.vpn]
+";
+
+    const SCUTIL_DNS: &str = "\
+DNS configuration
+
+resolver #1
+  search domain[0] : corp.example
+  nameserver[0] : 10.8.0.1
+  nameserver[1] : 10.8.0.2
+  if_index : 20 (utun4)
+  flags    : Supplemental, Request A records
+  reach    : 0x00000003 (Reachable,Transient Connection)
+
+resolver #2
+  nameserver[0] : 192.168.1.1
+  if_index : 11 (en0)
+  flags    : Request A records
+  reach    : 0x00020002 (Reachable,Directly Reachable Address)
+
+resolver #3
+  domain   : local
+  options  : mdns
+  timeout  : 5
+  flags    : Request A records
+  reach    : 0x00000000 (Not Reachable)
+  order    : 300000
+
+DNS configuration (for scoped queries)
+
+resolver #1
+  search domain[0] : home.example
+  nameserver[0] : 192.168.1.1
+  if_index : 11 (en0)
+  flags    : Scoped, Request A records
+  reach    : 0x00020002 (Reachable,Directly Reachable Address)
+";
+
+    const IFCONFIG: &str = "\
+en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500
+\tether a4:83:e7:12:34:56
+\tinet 192.168.1.20 netmask 0xffffff00 broadcast 192.168.1.255
+\tstatus: active
+utun4: flags=8051<UP,POINTOPOINT,RUNNING,MULTICAST> mtu 1380
+\tinet 10.8.0.2 --> 10.8.0.1 netmask 0xffffffff
+\tinet6 fe80::8f3a:2b1c:4d5e:6f70%utun4 prefixlen 64 scopeid 0x14
+utun0: flags=8051<UP,POINTOPOINT,RUNNING,MULTICAST> mtu 1380
+\tinet6 fe80::a1b2:c3d4:e5f6:789%utun0 prefixlen 64 scopeid 0xf
+";
+
+    const SPLIT_ROUTES: &str = "\
+Routing tables
+
+Internet:
+Destination        Gateway            Flags               Netif Expire
+0/1                10.8.0.1           UGScg               utun4
+default            192.168.1.1        UGScg                 en0
+10.8.0.1           10.8.0.2           UH                  utun4
+128.0/1            10.8.0.1           UGSc                utun4
+192.168.1          link#11            UCS                   en0      !
+";
+
+    #[test]
+    fn parses_nc_list() {
+        let services = parse_nc_list(NC_LIST);
+        assert_eq!(services.len(), 3);
+
+        assert_eq!(services[0].id, "6A1B2C3D-0001");
+        assert_eq!(services[0].name, "Office");
+        assert_eq!(services[0].vpn_type, "IPSec");
+        assert_eq!(services[0].subtype.as_deref(), Some("IPSec"));
+        assert!(services[0].enabled);
+        assert_eq!(services[0].status, VpnStatus::Connected);
+
+        assert_eq!(services[1].name, "Lab L2TP");
+        assert_eq!(services[1].vpn_type, "PPP --> L2TP");
+        assert_eq!(services[1].subtype.as_deref(), Some("PPP/L2TP"));
+        assert_eq!(services[1].status, VpnStatus::Disconnected);
+
+        assert_eq!(services[2].name, "Home");
+        assert_eq!(services[2].vpn_type, "VPN (io.example.vpn)");
+        assert!(!services[2].enabled);
+        assert_eq!(services[2].status, VpnStatus::Disconnected);
+    }
+
+    #[test]
+    fn parses_unscoped_resolvers_only() {
+        let resolvers = parse_scutil_dns(SCUTIL_DNS);
+        assert_eq!(resolvers.len(), 3);
+
+        assert_eq!(resolvers[0].nameservers, ["10.8.0.1", "10.8.0.2"]);
+        assert_eq!(resolvers[0].domains, ["corp.example"]);
+        assert_eq!(resolvers[0].interface.as_deref(), Some("utun4"));
+
+        assert_eq!(resolvers[1].nameservers, ["192.168.1.1"]);
+        assert_eq!(resolvers[1].interface.as_deref(), Some("en0"));
+
+        assert!(resolvers[2].nameservers.is_empty());
+        assert_eq!(resolvers[2].domains, ["local"]);
+        assert_eq!(resolvers[2].interface, None);
+
+        assert!(resolvers
+            .iter()
+            .all(|r| !r.domains.contains(&"home.example".to_string())));
+    }
+
+    #[test]
+    fn split_default_routes_via_tunnel() {
+        let state = assess_vpn(
+            parse_nc_list(NC_LIST),
+            &parse_ifconfig(IFCONFIG),
+            &parse_netstat_routes(SPLIT_ROUTES),
+            Some("en0"),
+            &parse_scutil_dns(SCUTIL_DNS),
+        );
+
+        // utun0 only has a link-local address and is not a VPN tunnel
+        assert_eq!(state.tunnels.len(), 1);
+        let tunnel = &state.tunnels[0];
+        assert_eq!(tunnel.name, "utun4");
+        assert_eq!(tunnel.addresses, ["10.8.0.2"]);
+        assert!(tunnel.takes_default_route);
+        assert_eq!(tunnel.dns_servers, ["10.8.0.1", "10.8.0.2"]);
+        assert_eq!(tunnel.dns_domains, ["corp.example"]);
+
+        assert!(state.default_route_via_tunnel);
+        assert!(state.dns_via_tunnel);
+        assert!(state.is_active());
+        assert_eq!(state.active_name().as_deref(), Some("Office"));
+        assert!(state.services[0].takes_default_route && state.services[0].injects_dns);
+        assert!(!state.services[1].takes_default_route && !state.services[1].injects_dns);
+    }
+
+    #[test]
+    fn split_tunnel_keeps_the_default_route() {
+        let routes: Vec<RouteEntry> = parse_netstat_routes(SPLIT_ROUTES)
+            .into_iter()
+            .filter(|r| !r.destination.ends_with("/1"))
+            .collect();
+        let state = assess_vpn(
+            Vec::new(),
+            &parse_ifconfig(IFCONFIG),
+            &routes,
+            Some("en0"),
+            &[],
+        );
+
+        assert_eq!(state.tunnels.len(), 1);
+        assert!(!state.tunnels[0].takes_default_route);
+        assert!(!state.default_route_via_tunnel);
+        assert!(!state.dns_via_tunnel);
+        assert!(!state.is_active());
+        assert_eq!(state.active_name().as_deref(), Some("utun4"));
+    }
+
+    #[test]
+    fn tunnel_interfaces_need_a_unit_number() {
+        for name in ["utun0", "utun12", "ipsec0", "ppp0", "tun3", "tap1"] {
+            assert!(is_tunnel_interface(name), "{}", name);
+        }
+        for name in ["utun", "ppp", "en0", "bridge0", "utunx", "tunnel0"] {
+            assert!(!is_tunnel_interface(name), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use super::network::{run_with_admin_privileges, shell_quote};
+use super::runner::invalidate_command_cache;
+
+/// Security types accepted by `-addpreferredwirelessnetworkatindex`
+const SECURITY_TYPES: [&str; 9] = [
+    "OPEN", "WEP", "WPA", "WPA2", "WPA3", "WPAE", "WPA2E", "WPA3E", "8021XWEP",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum WifiAssociation {
+    Associated,
+    NotAssociated,
+    PoweredOff,
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WifiPower {
+    pub device: String,
+    pub powered: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WifiNetworkStatus {
+    pub device: String,
+    pub state: WifiAssociation,
+    pub ssid: Option<String>,
+}
+
+/// Parse `networksetup -getairportpower`, e.g. "Wi-Fi Power (en0): On"
+pub fn parse_airport_power(output: &str) -> Option<bool> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once("):"))
+        .map(|(_, state)| state.trim().eq_ignore_ascii_case("on"))
+        .next()
+}
+
+/// Parse `networksetup -getairportnetwork` output
+pub fn parse_airport_network(output: &str) -> (WifiAssociation, Option<String>) {
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(ssid) = line.strip_prefix("Current Wi-Fi Network:") {
+            return (WifiAssociation::Associated, Some(ssid.trim().to_string()));
+        }
+        if let Some(ssid) = line.strip_prefix("Current AirPort Network:") {
+            return (WifiAssociation::Associated, Some(ssid.trim().to_string()));
+        }
+        if line.starts_with("You are not associated") {
+            return (WifiAssociation::NotAssociated, None);
+        }
+        if line.contains("power is currently off") {
+            return (WifiAssociation::PoweredOff, None);
+        }
+    }
+
+    (WifiAssociation::Unknown, None)
+}
+
+/// Parse `networksetup -listpreferredwirelessnetworks` output, tab indented SSIDs
+/// below a "Preferred networks on en0:" header
+pub fn parse_preferred_networks(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| line.starts_with('\t') || line.starts_with("  "))
+        .map(|line| line.trim().to_string())
+        .filter(|ssid| !ssid.is_empty())
+        .collect()
+}
+
+/// Wi-Fi device from `networksetup -listallhardwareports` output
+pub fn parse_wifi_device(output: &str) -> Option<String> {
+    let mut is_wifi = false;
+    for line in output.lines() {
+        if let Some(port) = line.strip_prefix("Hardware Port:") {
+            let port = port.trim();
+            is_wifi = port == "Wi-Fi" || port == "AirPort";
+        } else if let Some(device) = line.strip_prefix("Device:") {
+            if is_wifi {
+                return Some(device.trim().to_string());
+            }
+        }
+    }
+
+    None
+}
+
+/// Use the given device, or look up the Wi-Fi hardware port
+fn resolve_device(device: Option<String>) -> Result<String, String> {
+    if let Some(device) = device.filter(|d| !d.is_empty()) {
+        return Ok(device);
+    }
+
+    let output = Command::new("networksetup")
+        .arg("-listallhardwareports")
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    parse_wifi_device(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "No Wi-Fi hardware port found".to_string())
+}
+
+fn run_networksetup(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("networksetup")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    // networksetup often exits 0 and reports errors on stdout
+    if !output.status.success()
+        || stdout.starts_with("Error")
+        || stdout.contains("is not a Wi-Fi interface")
+    {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("命令执行失败: {}{}", stdout.trim(), stderr.trim()));
+    }
+
+    Ok(stdout)
+}
+
+fn validate_security_type(security_type: &str) -> Result<String, String> {
+    let upper = security_type.trim().to_ascii_uppercase();
+    if SECURITY_TYPES.contains(&upper.as_str()) {
+        Ok(upper)
+    } else {
+        Err(format!(
+            "Unknown security type '{}', expected one of {}",
+            security_type,
+            SECURITY_TYPES.join(", ")
+        ))
+    }
+}
+
+fn add_preferred_command(
+    device: &str,
+    ssid: &str,
+    index: u32,
+    security_type: &str,
+    password: Option<&str>,
+) -> String {
+    let mut command = format!(
+        "networksetup -addpreferredwirelessnetworkatindex {} {} {} {}",
+        shell_quote(device),
+        shell_quote(ssid),
+        index,
+        security_type
+    );
+    if let Some(password) = password.filter(|p| !p.is_empty()) {
+        command.push(' ');
+        command.push_str(&shell_quote(password));
+    }
+    command
+}
+
+fn remove_preferred_command(device: &str, ssid: &str) -> String {
+    format!(
+        "networksetup -removepreferredwirelessnetwork {} {}",
+        shell_quote(device),
+        shell_quote(ssid)
+    )
+}
+
+#[tauri::command]
+pub fn get_wifi_power(device: Option<String>) -> Result<WifiPower, String> {
+    let device = resolve_device(device)?;
+    let stdout = run_networksetup(&["-getairportpower", &device])?;
+
+    let powered = parse_airport_power(&stdout)
+        .ok_or_else(|| format!("Failed to read Wi-Fi power: {}", stdout.trim()))?;
+
+    Ok(WifiPower { device, powered })
+}
+
+#[tauri::command]
+pub fn set_wifi_power(device: Option<String>, powered: bool) -> Result<String, String> {
+    let device = resolve_device(device)?;
+    run_networksetup(&[
+        "-setairportpower",
+        &device,
+        if powered { "on" } else { "off" },
+    ])?;
+    invalidate_command_cache();
+
+    Ok(format!(
+        "Wi-Fi power turned {} for {}",
+        if powered { "on" } else { "off" },
+        device
+    ))
+}
+
+#[tauri::command]
+pub fn get_current_wifi_network(device: Option<String>) -> Result<WifiNetworkStatus, String> {
+    let device = resolve_device(device)?;
+    let stdout = run_networksetup(&["-getairportnetwork", &device])?;
+    let (state, ssid) = parse_airport_network(&stdout);
+
+    Ok(WifiNetworkStatus {
+        device,
+        state,
+        ssid,
+    })
+}
+
+#[tauri::command]
+pub fn join_wifi_network(
+    device: Option<String>,
+    ssid: String,
+    password: Option<String>,
+) -> Result<String, String> {
+    if ssid.is_empty() {
+        return Err("SSID cannot be empty".to_string());
+    }
+
+    let device = resolve_device(device)?;
+    let mut args = vec!["-setairportnetwork", &device, &ssid];
+    if let Some(password) = password.as_deref().filter(|p| !p.is_empty()) {
+        args.push(password);
+    }
+
+    let stdout = run_networksetup(&args)?;
+    invalidate_command_cache();
+    // Join failures are reported on stdout, e.g. "Failed to join network X."
+    if stdout.contains("Failed") || stdout.contains("Could not find") {
+        return Err(format!("Failed to join {}: {}", ssid, stdout.trim()));
+    }
+
+    Ok(format!("Joined Wi-Fi network '{}'", ssid))
+}
+
+#[tauri::command]
+pub fn list_preferred_wifi_networks(device: Option<String>) -> Result<Vec<String>, String> {
+    let device = resolve_device(device)?;
+    let stdout = run_networksetup(&["-listpreferredwirelessnetworks", &device])?;
+
+    Ok(parse_preferred_networks(&stdout))
+}
+
+#[tauri::command]
+pub fn add_preferred_wifi_network(
+    device: Option<String>,
+    ssid: String,
+    index: Option<u32>,
+    security_type: String,
+    password: Option<String>,
+) -> Result<String, String> {
+    if ssid.is_empty() {
+        return Err("SSID cannot be empty".to_string());
+    }
+
+    let device = resolve_device(device)?;
+    let security_type = validate_security_type(&security_type)?;
+    if let Ok(existing) = list_preferred_wifi_networks(Some(device.clone())) {
+        if existing.iter().any(|s| s == &ssid) {
+            return Err(format!("'{}' is already a preferred network", ssid));
+        }
+    }
+
+    let command = add_preferred_command(
+        &device,
+        &ssid,
+        index.unwrap_or(0),
+        &security_type,
+        password.as_deref(),
+    );
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!("'{}' added to preferred networks", ssid))
+}
+
+#[tauri::command]
+pub fn remove_preferred_wifi_network(
+    device: Option<String>,
+    ssid: String,
+) -> Result<String, String> {
+    if ssid.is_empty() {
+        return Err("SSID cannot be empty".to_string());
+    }
+
+    let device = resolve_device(device)?;
+    if let Ok(existing) = list_preferred_wifi_networks(Some(device.clone())) {
+        if !existing.iter().any(|s| s == &ssid) {
+            return Err(format!("'{}' is not a preferred network", ssid));
+        }
+    }
+
+    run_with_admin_privileges(&remove_preferred_command(&device, &ssid))?;
+
+    Ok(format!("'{}' removed from preferred networks", ssid))
+}
+
+/// Move a preferred network to a new position. The saved password stays in the
+/// keychain, so re-adding it only needs the security type.
+#[tauri::command]
+pub fn move_preferred_wifi_network(
+    device: Option<String>,
+    ssid: String,
+    index: u32,
+    security_type: String,
+) -> Result<String, String> {
+    if ssid.is_empty() {
+        return Err("SSID cannot be empty".to_string());
+    }
+
+    let device = resolve_device(device)?;
+    let security_type = validate_security_type(&security_type)?;
+    let existing = list_preferred_wifi_networks(Some(device.clone()))?;
+    if !existing.iter().any(|s| s == &ssid) {
+        return Err(format!("'{}' is not a preferred network", ssid));
+    }
+
+    let index = index.min(existing.len().saturating_sub(1) as u32);
+    let command = format!(
+        "{} && {}",
+        remove_preferred_command(&device, &ssid),
+        add_preferred_command(&device, &ssid, index, &security_type, None)
+    );
+
+    run_with_admin_privileges(&command)?;
+
+    Ok(format!("'{}' moved to position {}", ssid, index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    const HOSTILE: [&str; 6] = [
+        "$(touch pwned)",
+        "`touch pwned`",
+        "it's \"quoted\"",
+        "back\\slash $HOME",
+        "semi; touch pwned",
+        "new\nline",
+    ];
+
+    /// Run `command` with a fake `networksetup` on PATH and return the arguments
+    /// it received
+    fn received_args(command: &str) -> (Vec<String>, bool) {
+        let dir = std::env::temp_dir().join(format!(
+            "wifi-quote-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let fake = dir.join("networksetup");
+        fs::write(&fake, "#!/bin/sh\nprintf '%s\\0' \"$@\" > \"$ARGS_FILE\"\n").unwrap();
+        fs::set_permissions(&fake, fs::Permissions::from_mode(0o755)).unwrap();
+        let args_file: PathBuf = dir.join("args");
+
+        let status = Command::new("sh")
+            .args(["-c", command])
+            .current_dir(&dir)
+            .env("PATH", format!("{}:/usr/bin:/bin", dir.display()))
+            .env("ARGS_FILE", &args_file)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let args = fs::read_to_string(&args_file).unwrap();
+        let pwned = dir.join("pwned").exists();
+        fs::remove_dir_all(&dir).unwrap();
+        (
+            args.split_terminator('\0').map(str::to_string).collect(),
+            pwned,
+        )
+    }
+
+    #[test]
+    fn hostile_ssids_and_passwords_reach_networksetup_unchanged() {
+        for value in HOSTILE {
+            let command = add_preferred_command("en0", value, 2, "WPA2", Some(value));
+            let (args, pwned) = received_args(&command);
+            assert_eq!(
+                args,
+                [
+                    "-addpreferredwirelessnetworkatindex",
+                    "en0",
+                    value,
+                    "2",
+                    "WPA2",
+                    value
+                ]
+            );
+            assert!(!pwned, "{:?} ran a command", value);
+
+            let (args, pwned) = received_args(&remove_preferred_command("en0", value));
+            assert_eq!(args, ["-removepreferredwirelessnetwork", "en0", value]);
+            assert!(!pwned, "{:?} ran a command", value);
+        }
+    }
+
+    #[test]
+    fn empty_password_is_omitted() {
+        let (args, _) = received_args(&add_preferred_command("en0", "Home", 0, "OPEN", Some("")));
+        assert_eq!(
+            args,
+            [
+                "-addpreferredwirelessnetworkatindex",
+                "en0",
+                "Home",
+                "0",
+                "OPEN"
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_preferred_networks() {
+        let output = "Preferred networks on en0:\n\tHome Wi-Fi\n\tCafé \"Guest\"\n\n\tiPhone\n";
+        assert_eq!(
+            parse_preferred_networks(output),
+            ["Home Wi-Fi", "Café \"Guest\"", "iPhone"]
+        );
+    }
+
+    #[test]
+    fn parses_empty_preferred_networks() {
+        assert!(parse_preferred_networks("Preferred networks on en0:\n").is_empty());
+        assert!(parse_preferred_networks("en5 is not a Wi-Fi interface.\n").is_empty());
+    }
+
+    #[test]
+    fn parses_airport_state() {
+        assert_eq!(parse_airport_power("Wi-Fi Power (en0): On\n"), Some(true));
+        assert_eq!(parse_airport_power("Wi-Fi Power (en0): Off\n"), Some(false));
+        assert_eq!(
+            parse_airport_network("Current Wi-Fi Network: Home\n"),
+            (WifiAssociation::Associated, Some("Home".to_string()))
+        );
+        assert_eq!(
+            parse_airport_network("You are not associated with an AirPort network.\n"),
+            (WifiAssociation::NotAssociated, None)
+        );
+    }
+}
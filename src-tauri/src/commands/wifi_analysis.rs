@@ -0,0 +1,754 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::process::Command;
+
+use super::network::run_with_admin_privileges;
+
+/// Non-overlapping 2.4 GHz channels worth recommending
+const CHANNELS_2G: [u32; 3] = [1, 6, 11];
+/// 5 GHz channels usable without DFS in most regions
+const CHANNELS_5G: [u32; 9] = [36, 40, 44, 48, 149, 153, 157, 161, 165];
+/// Preferred scanning channels in 6 GHz
+const CHANNELS_6G: [u32; 7] = [5, 21, 37, 53, 69, 85, 101];
+/// A channel must be this much less congested before moving is worth it
+const MIN_IMPROVEMENT: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WifiBand {
+    Band2G,
+    Band5G,
+    Band6G,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SignalQuality {
+    Excellent,
+    Good,
+    Fair,
+    Poor,
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ChannelInfo {
+    pub channel: Option<u32>,
+    pub band: Option<WifiBand>,
+    pub width_mhz: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WifiSignal {
+    pub interface: Option<String>,
+    pub ssid: Option<String>,
+    pub rssi_dbm: Option<i32>,
+    pub noise_dbm: Option<i32>,
+    pub snr_db: Option<i32>,
+    pub channel: ChannelInfo,
+    pub phy_mode: Option<String>,
+    pub tx_rate_mbps: Option<f64>,
+    pub quality: SignalQuality,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NeighborNetwork {
+    pub ssid: String,
+    pub channel: ChannelInfo,
+    pub rssi_dbm: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChannelScore {
+    pub channel: u32,
+    pub band: WifiBand,
+    pub networks: u32,
+    pub congestion: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WifiAnalysis {
+    pub signal: Option<WifiSignal>,
+    pub neighbors: Vec<NeighborNetwork>,
+    pub channel_scores: Vec<ChannelScore>,
+    pub recommended_channel: Option<u32>,
+    pub recommendation: String,
+}
+
+fn band_for_channel(channel: u32) -> WifiBand {
+    if channel <= 14 {
+        WifiBand::Band2G
+    } else {
+        WifiBand::Band5G
+    }
+}
+
+/// Parse a channel description in either `system_profiler` form ("36 (5GHz, 80MHz)")
+/// or `wdutil` form ("5g36/80")
+pub fn parse_channel(value: &str) -> ChannelInfo {
+    let value = value.trim();
+    let mut info = ChannelInfo::default();
+
+    if let Some((band, rest)) = value.split_once('g') {
+        if let Ok(band_ghz) = band.parse::<u32>() {
+            let (channel, width) = rest.split_once('/').unwrap_or((rest, ""));
+            info.channel = channel.trim().parse().ok();
+            info.width_mhz = width.trim().parse().ok();
+            info.band = match band_ghz {
+                2 => Some(WifiBand::Band2G),
+                5 => Some(WifiBand::Band5G),
+                6 => Some(WifiBand::Band6G),
+                _ => None,
+            };
+            return info;
+        }
+    }
+
+    let (channel, details) = value.split_once('(').unwrap_or((value, ""));
+    info.channel = channel.trim().parse().ok();
+    for part in details.trim_end_matches(')').split(',') {
+        let part = part.trim();
+        if let Some(ghz) = part.strip_suffix("GHz") {
+            info.band = match ghz {
+                "2" | "2.4" => Some(WifiBand::Band2G),
+                "5" => Some(WifiBand::Band5G),
+                "6" => Some(WifiBand::Band6G),
+                _ => None,
+            };
+        } else if let Some(mhz) = part.strip_suffix("MHz") {
+            info.width_mhz = mhz.trim().parse().ok();
+        }
+    }
+    if info.band.is_none() {
+        info.band = info.channel.map(band_for_channel);
+    }
+
+    info
+}
+
+fn parse_dbm(value: &str) -> Option<i32> {
+    value.trim().trim_end_matches("dBm").trim().parse().ok()
+}
+
+/// Parse "-56 dBm / -92 dBm" into RSSI and noise
+pub fn parse_signal_noise(value: &str) -> (Option<i32>, Option<i32>) {
+    match value.split_once('/') {
+        Some((signal, noise)) => (parse_dbm(signal), parse_dbm(noise)),
+        None => (parse_dbm(value), None),
+    }
+}
+
+pub fn signal_quality(rssi_dbm: Option<i32>, snr_db: Option<i32>) -> SignalQuality {
+    let Some(rssi) = rssi_dbm else {
+        return SignalQuality::Unknown;
+    };
+    let quality = match rssi {
+        r if r >= -60 => SignalQuality::Excellent,
+        r if r >= -67 => SignalQuality::Good,
+        r if r >= -75 => SignalQuality::Fair,
+        _ => SignalQuality::Poor,
+    };
+
+    // A noisy channel limits the usable rate even with a strong signal
+    match snr_db {
+        Some(snr) if snr < 10 => SignalQuality::Poor,
+        Some(snr)
+            if snr < 20 && matches!(quality, SignalQuality::Excellent | SignalQuality::Good) =>
+        {
+            SignalQuality::Fair
+        }
+        _ => quality,
+    }
+}
+
+fn build_signal(
+    interface: Option<String>,
+    ssid: Option<String>,
+    rssi_dbm: Option<i32>,
+    noise_dbm: Option<i32>,
+    channel: ChannelInfo,
+    phy_mode: Option<String>,
+    tx_rate_mbps: Option<f64>,
+) -> WifiSignal {
+    let snr_db = rssi_dbm.zip(noise_dbm).map(|(rssi, noise)| rssi - noise);
+    WifiSignal {
+        interface,
+        ssid,
+        rssi_dbm,
+        noise_dbm,
+        snr_db,
+        channel,
+        phy_mode,
+        tx_rate_mbps,
+        quality: signal_quality(rssi_dbm, snr_db),
+    }
+}
+
+fn json_text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse `system_profiler SPAirPortDataType -json` into the current link and the
+/// neighbouring networks seen in the last scan
+pub fn parse_system_profiler(
+    json: &str,
+) -> Result<(Option<WifiSignal>, Vec<NeighborNetwork>), String> {
+    let root: Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid system_profiler output: {}", e))?;
+
+    let interfaces = root["SPAirPortDataType"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry["spairport_airport_interfaces"].as_array())
+        .flatten();
+
+    let mut signal = None;
+    let mut neighbors = Vec::new();
+
+    for interface in interfaces {
+        let name = json_text(interface.get("_name"));
+
+        if signal.is_none() {
+            if let Some(current) = interface.get("spairport_current_network_information") {
+                let (rssi, noise) = json_text(current.get("spairport_signal_noise"))
+                    .map(|s| parse_signal_noise(&s))
+                    .unwrap_or((None, None));
+                signal = Some(build_signal(
+                    name.clone(),
+                    json_text(current.get("_name")),
+                    rssi,
+                    noise,
+                    json_text(current.get("spairport_network_channel"))
+                        .map(|c| parse_channel(&c))
+                        .unwrap_or_default(),
+                    json_text(current.get("spairport_network_phymode")),
+                    json_text(current.get("spairport_network_rate")).and_then(|r| r.parse().ok()),
+                ));
+            }
+        }
+
+        for other in interface["spairport_airport_other_local_wireless_networks"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            let (rssi, _) = json_text(other.get("spairport_signal_noise"))
+                .map(|s| parse_signal_noise(&s))
+                .unwrap_or((None, None));
+            neighbors.push(NeighborNetwork {
+                ssid: json_text(other.get("_name")).unwrap_or_default(),
+                channel: json_text(other.get("spairport_network_channel"))
+                    .map(|c| parse_channel(&c))
+                    .unwrap_or_default(),
+                rssi_dbm: rssi,
+            });
+        }
+    }
+
+    Ok((signal, neighbors))
+}
+
+/// Parse the WIFI section of `wdutil info`
+pub fn parse_wdutil_info(output: &str) -> Option<WifiSignal> {
+    let mut fields = std::collections::HashMap::new();
+    for line in output.lines() {
+        if let Some((key, value)) = line.split_once(" : ") {
+            // Keep the first occurrence, later sections reuse some keys
+            fields
+                .entry(key.trim().to_string())
+                .or_insert_with(|| value.trim().to_string());
+        }
+    }
+
+    let rssi = fields.get("RSSI").and_then(|v| parse_dbm(v));
+    let channel = fields.get("Channel").map(|c| parse_channel(c));
+    if rssi.is_none() && channel.is_none() {
+        return None;
+    }
+
+    Some(build_signal(
+        fields.get("Interface Name").cloned(),
+        fields.get("SSID").cloned(),
+        rssi,
+        fields.get("Noise").and_then(|v| parse_dbm(v)),
+        channel.unwrap_or_default(),
+        fields.get("PHY Mode").cloned(),
+        fields
+            .get("Tx Rate")
+            .and_then(|v| v.trim_end_matches("Mbps").trim().parse().ok()),
+    ))
+}
+
+/// 20 MHz channels covered by a transmission of the given width in 5/6 GHz
+fn occupied_channels(channel: u32, width_mhz: Option<u32>, band: WifiBand) -> Vec<u32> {
+    let span = (width_mhz.unwrap_or(20) / 20).max(1);
+    let base = match band {
+        WifiBand::Band6G => 1,
+        _ if channel >= 149 => 149,
+        _ => 36,
+    };
+    if channel < base {
+        return vec![channel];
+    }
+    let start = channel - ((channel - base) / 4 % span) * 4;
+    (0..span).map(|i| start + i * 4).collect()
+}
+
+/// Weight of a neighbour by signal strength: a network at -30 dBm counts fully,
+/// one at -100 dBm barely at all
+fn signal_weight(rssi_dbm: Option<i32>) -> f64 {
+    match rssi_dbm {
+        Some(rssi) => ((rssi + 100).clamp(0, 70) as f64) / 70.0,
+        None => 0.5,
+    }
+}
+
+/// Congestion of each candidate channel in `band` from the neighbour scan
+pub fn channel_congestion(neighbors: &[NeighborNetwork], band: WifiBand) -> Vec<ChannelScore> {
+    let candidates: &[u32] = match band {
+        WifiBand::Band2G => &CHANNELS_2G,
+        WifiBand::Band5G => &CHANNELS_5G,
+        WifiBand::Band6G => &CHANNELS_6G,
+    };
+
+    candidates
+        .iter()
+        .map(|&candidate| {
+            let mut score = ChannelScore {
+                channel: candidate,
+                band,
+                networks: 0,
+                congestion: 0.0,
+            };
+
+            for neighbor in neighbors {
+                let Some(channel) = neighbor.channel.channel else {
+                    continue;
+                };
+                if neighbor
+                    .channel
+                    .band
+                    .unwrap_or_else(|| band_for_channel(channel))
+                    != band
+                {
+                    continue;
+                }
+
+                let overlap = match band {
+                    // 2.4 GHz channels are 5 MHz apart but 20 MHz wide
+                    WifiBand::Band2G => {
+                        let distance = (channel as f64 - candidate as f64).abs();
+                        (1.0 - distance / 5.0).max(0.0)
+                    }
+                    _ => {
+                        if occupied_channels(channel, neighbor.channel.width_mhz, band)
+                            .contains(&candidate)
+                        {
+                            1.0
+                        } else {
+                            0.0
+                        }
+                    }
+                };
+
+                if overlap > 0.0 {
+                    score.networks += 1;
+                    score.congestion += overlap * signal_weight(neighbor.rssi_dbm);
+                }
+            }
+
+            score
+        })
+        .collect()
+}
+
+/// Pick the least congested channel, staying on the current one unless another is
+/// clearly better
+pub fn recommend_channel(scores: &[ChannelScore], current: Option<u32>) -> (Option<u32>, String) {
+    let Some(best) = scores.iter().min_by(|a, b| {
+        a.congestion
+            .partial_cmp(&b.congestion)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.channel.cmp(&b.channel))
+    }) else {
+        return (None, "No channel data available".to_string());
+    };
+
+    let current_score = current.and_then(|c| scores.iter().find(|s| s.channel == c));
+    match current_score {
+        Some(current) if current.congestion - best.congestion < MIN_IMPROVEMENT => (
+            Some(current.channel),
+            format!(
+                "Channel {} is fine ({} overlapping networks)",
+                current.channel, current.networks
+            ),
+        ),
+        Some(current) => (
+            Some(best.channel),
+            format!(
+                "Move the router from channel {} to {} ({} vs {} overlapping networks)",
+                current.channel, best.channel, current.networks, best.networks
+            ),
+        ),
+        None => (
+            Some(best.channel),
+            format!(
+                "Channel {} is the least congested ({} overlapping networks)",
+                best.channel, best.networks
+            ),
+        ),
+    }
+}
+
+/// Combine signal and scan data into an analysis
+pub fn analyze(signal: Option<WifiSignal>, neighbors: Vec<NeighborNetwork>) -> WifiAnalysis {
+    let band = signal
+        .as_ref()
+        .and_then(|s| s.channel.band)
+        .unwrap_or(WifiBand::Band2G);
+    let current = signal.as_ref().and_then(|s| s.channel.channel);
+
+    let channel_scores = channel_congestion(&neighbors, band);
+    let (recommended_channel, recommendation) = recommend_channel(&channel_scores, current);
+
+    WifiAnalysis {
+        signal,
+        neighbors,
+        channel_scores,
+        recommended_channel,
+        recommendation,
+    }
+}
+
+/// Signal, neighbouring networks and a channel recommendation.
+///
+/// Newer macOS versions redact the signal in system_profiler. `wdutil` still reports
+/// it but only to root, so with `elevated` the password is asked for to run it.
+#[tauri::command]
+pub fn analyze_wifi(elevated: Option<bool>) -> Result<WifiAnalysis, String> {
+    let output = Command::new("system_profiler")
+        .args(["SPAirPortDataType", "-json"])
+        .output()
+        .map_err(|e| format!("Failed to execute system_profiler: {}", e))?;
+
+    let (mut signal, neighbors) = parse_system_profiler(&String::from_utf8_lossy(&output.stdout))?;
+
+    let redacted = signal.as_ref().is_none_or(|s| s.rssi_dbm.is_none());
+    if redacted && elevated.unwrap_or(false) {
+        // `do shell script` separates lines with carriage returns
+        let output = run_with_admin_privileges("wdutil info")?.replace('\r', "\n");
+        if let Some(parsed) = parse_wdutil_info(&output) {
+            signal = Some(parsed);
+        }
+    }
+
+    Ok(analyze(signal, neighbors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYSTEM_PROFILER: &str = r#"{
+      "SPAirPortDataType" : [
+        {
+          "spairport_airport_interfaces" : [
+            {
+              "_name" : "en0",
+              "spairport_current_network_information" : {
+                "_name" : "HomeNet",
+                "spairport_network_channel" : "36 (5GHz, 80MHz)",
+                "spairport_network_phymode" : "802.11ax",
+                "spairport_network_rate" : 1200,
+                "spairport_signal_noise" : "-56 dBm / -92 dBm"
+              },
+              "spairport_airport_other_local_wireless_networks" : [
+                {
+                  "_name" : "Neighbor",
+                  "spairport_network_channel" : "6 (2GHz, 20MHz)",
+                  "spairport_signal_noise" : "-70 dBm / -95 dBm"
+                },
+                {
+                  "_name" : "Cafe",
+                  "spairport_network_channel" : "149 (5GHz, 40MHz)"
+                }
+              ]
+            },
+            { "_name" : "awdl0" }
+          ]
+        }
+      ]
+    }"#;
+
+    /// macOS 14.4 and later leave out the signal and name the network "<redacted>"
+    const SYSTEM_PROFILER_REDACTED: &str = r#"{
+      "SPAirPortDataType" : [
+        {
+          "spairport_airport_interfaces" : [
+            {
+              "_name" : "en0",
+              "spairport_current_network_information" : {
+                "_name" : "<redacted>",
+                "spairport_network_channel" : "44 (5GHz, 80MHz)",
+                "spairport_network_phymode" : "802.11ax"
+              }
+            }
+          ]
+        }
+      ]
+    }"#;
+
+    const WDUTIL_INFO: &str = "\
+————————————————————————————————
+WIFI
+————————————————————————————————
+    MAC Address          : a4:83:e7:00:00:01 (hw=a4:83:e7:00:00:01)
+    Interface Name       : en0
+    Power                : On [On]
+    Op Mode              : STA
+    SSID                 : HomeNet
+    BSSID                : <redacted>
+    RSSI                 : -61 dBm
+    CCA                  : 12 %
+    Noise                : -94 dBm
+    Tx Rate              : 573.0 Mbps
+    Security             : WPA2 Personal
+    PHY Mode             : 11ax
+    Channel              : 5g44/80
+————————————————————————————————
+BLUETOOTH
+————————————————————————————————
+    Power                : On
+";
+
+    fn neighbor(channel: &str, rssi_dbm: Option<i32>) -> NeighborNetwork {
+        NeighborNetwork {
+            ssid: format!("net-{}", channel),
+            channel: parse_channel(channel),
+            rssi_dbm,
+        }
+    }
+
+    /// (channel, networks, congestion) of every candidate
+    fn scores(neighbors: &[NeighborNetwork], band: WifiBand) -> Vec<(u32, u32, f64)> {
+        channel_congestion(neighbors, band)
+            .into_iter()
+            .map(|s| {
+                (
+                    s.channel,
+                    s.networks,
+                    (s.congestion * 100.0).round() / 100.0,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_channel_descriptions() {
+        let channel = |channel, band, width| ChannelInfo {
+            channel: Some(channel),
+            band: Some(band),
+            width_mhz: width,
+        };
+        assert_eq!(
+            parse_channel("36 (5GHz, 80MHz)"),
+            channel(36, WifiBand::Band5G, Some(80))
+        );
+        assert_eq!(
+            parse_channel("11 (2GHz, 20MHz)"),
+            channel(11, WifiBand::Band2G, Some(20))
+        );
+        assert_eq!(
+            parse_channel("37 (6GHz, 160MHz)"),
+            channel(37, WifiBand::Band6G, Some(160))
+        );
+        assert_eq!(parse_channel("6"), channel(6, WifiBand::Band2G, None));
+        assert_eq!(parse_channel("149"), channel(149, WifiBand::Band5G, None));
+        assert_eq!(
+            parse_channel("5g44/80"),
+            channel(44, WifiBand::Band5G, Some(80))
+        );
+        assert_eq!(
+            parse_channel("2g1/20"),
+            channel(1, WifiBand::Band2G, Some(20))
+        );
+        assert_eq!(
+            parse_channel("6g5/320"),
+            channel(5, WifiBand::Band6G, Some(320))
+        );
+        assert_eq!(parse_channel(""), ChannelInfo::default());
+    }
+
+    #[test]
+    fn parses_system_profiler_scan() {
+        let (signal, neighbors) = parse_system_profiler(SYSTEM_PROFILER).unwrap();
+        let signal = signal.unwrap();
+
+        assert_eq!(signal.interface.as_deref(), Some("en0"));
+        assert_eq!(signal.ssid.as_deref(), Some("HomeNet"));
+        assert_eq!((signal.rssi_dbm, signal.noise_dbm), (Some(-56), Some(-92)));
+        assert_eq!(signal.snr_db, Some(36));
+        assert_eq!(signal.quality, SignalQuality::Excellent);
+        assert_eq!(signal.channel.channel, Some(36));
+        assert_eq!(signal.channel.width_mhz, Some(80));
+        assert_eq!(signal.tx_rate_mbps, Some(1200.0));
+        assert_eq!(signal.phy_mode.as_deref(), Some("802.11ax"));
+
+        assert_eq!(neighbors.len(), 2);
+        assert_eq!(neighbors[0].ssid, "Neighbor");
+        assert_eq!(neighbors[0].channel.band, Some(WifiBand::Band2G));
+        assert_eq!(neighbors[0].rssi_dbm, Some(-70));
+        assert_eq!(neighbors[1].rssi_dbm, None);
+
+        assert!(parse_system_profiler("not json").is_err());
+        assert_eq!(parse_system_profiler("{}").unwrap(), (None, Vec::new()));
+    }
+
+    #[test]
+    fn redacted_scan_has_no_signal() {
+        let (signal, neighbors) = parse_system_profiler(SYSTEM_PROFILER_REDACTED).unwrap();
+        let signal = signal.unwrap();
+
+        assert_eq!(signal.rssi_dbm, None);
+        assert_eq!(signal.quality, SignalQuality::Unknown);
+        assert_eq!(signal.channel.channel, Some(44));
+        assert!(neighbors.is_empty());
+    }
+
+    #[test]
+    fn parses_wdutil_info() {
+        let signal = parse_wdutil_info(WDUTIL_INFO).unwrap();
+
+        assert_eq!(signal.interface.as_deref(), Some("en0"));
+        assert_eq!(signal.ssid.as_deref(), Some("HomeNet"));
+        assert_eq!((signal.rssi_dbm, signal.noise_dbm), (Some(-61), Some(-94)));
+        assert_eq!(signal.quality, SignalQuality::Good);
+        assert_eq!(signal.tx_rate_mbps, Some(573.0));
+        assert_eq!(signal.phy_mode.as_deref(), Some("11ax"));
+        assert_eq!(signal.channel, parse_channel("5g44/80"));
+
+        // Lines joined by the carriage returns of `do shell script` after normalising
+        let from_admin = WDUTIL_INFO.replace('\n', "\r").replace('\r', "\n");
+        assert_eq!(parse_wdutil_info(&from_admin), Some(signal));
+        assert_eq!(parse_wdutil_info("WIFI\n    Power : Off\n"), None);
+    }
+
+    #[test]
+    fn signal_quality_accounts_for_noise() {
+        assert_eq!(
+            signal_quality(Some(-50), Some(40)),
+            SignalQuality::Excellent
+        );
+        assert_eq!(signal_quality(Some(-65), None), SignalQuality::Good);
+        assert_eq!(signal_quality(Some(-72), None), SignalQuality::Fair);
+        assert_eq!(signal_quality(Some(-80), None), SignalQuality::Poor);
+        assert_eq!(signal_quality(Some(-50), Some(15)), SignalQuality::Fair);
+        assert_eq!(signal_quality(Some(-50), Some(8)), SignalQuality::Poor);
+        assert_eq!(signal_quality(None, Some(30)), SignalQuality::Unknown);
+    }
+
+    #[test]
+    fn overlapping_2g_channels_count_partially() {
+        let neighbors = [
+            neighbor("1", Some(-30)),
+            neighbor("3", Some(-65)),
+            neighbor("11", None),
+            // Other bands do not matter
+            neighbor("36", Some(-30)),
+        ];
+
+        assert_eq!(
+            scores(&neighbors, WifiBand::Band2G),
+            [(1, 2, 1.3), (6, 1, 0.2), (11, 1, 0.5)]
+        );
+    }
+
+    #[test]
+    fn wide_5g_channels_occupy_their_whole_block() {
+        let neighbors = [
+            neighbor("44 (5GHz, 80MHz)", Some(-40)),
+            neighbor("157 (5GHz, 40MHz)", Some(-70)),
+            // DFS channels are not candidates
+            neighbor("100 (5GHz, 20MHz)", Some(-30)),
+        ];
+
+        let busy: Vec<u32> = channel_congestion(&neighbors, WifiBand::Band5G)
+            .into_iter()
+            .filter(|s| s.networks > 0)
+            .map(|s| s.channel)
+            .collect();
+        assert_eq!(busy, [36, 40, 44, 48, 157, 161]);
+
+        let scores = scores(&neighbors, WifiBand::Band5G);
+        assert!(scores.contains(&(36, 1, 0.86)));
+        assert!(scores.contains(&(161, 1, 0.43)));
+        assert!(scores.contains(&(149, 0, 0.0)));
+    }
+
+    #[test]
+    fn wide_6g_channels_occupy_their_whole_block() {
+        let neighbors = [neighbor("6g37/160", None)];
+
+        let busy: Vec<u32> = channel_congestion(&neighbors, WifiBand::Band6G)
+            .into_iter()
+            .filter(|s| s.networks > 0)
+            .map(|s| s.channel)
+            .collect();
+        assert_eq!(busy, [37, 53]);
+    }
+
+    #[test]
+    fn recommends_moving_only_for_a_clear_improvement() {
+        let neighbors = [
+            neighbor("1", Some(-30)),
+            neighbor("3", Some(-65)),
+            neighbor("11", None),
+        ];
+        let scores = channel_congestion(&neighbors, WifiBand::Band2G);
+
+        assert_eq!(
+            recommend_channel(&scores, Some(1)),
+            (
+                Some(6),
+                "Move the router from channel 1 to 6 (2 vs 1 overlapping networks)".to_string()
+            )
+        );
+        assert_eq!(
+            recommend_channel(&scores, Some(11)),
+            (
+                Some(11),
+                "Channel 11 is fine (1 overlapping networks)".to_string()
+            )
+        );
+        assert_eq!(
+            recommend_channel(&scores, None),
+            (
+                Some(6),
+                "Channel 6 is the least congested (1 overlapping networks)".to_string()
+            )
+        );
+        assert_eq!(recommend_channel(&[], Some(1)).0, None);
+    }
+
+    #[test]
+    fn analysis_uses_the_band_of_the_current_channel() {
+        let (signal, neighbors) = parse_system_profiler(SYSTEM_PROFILER).unwrap();
+        let analysis = analyze(signal, neighbors);
+
+        assert!(analysis
+            .channel_scores
+            .iter()
+            .all(|s| s.band == WifiBand::Band5G));
+        assert_eq!(analysis.recommended_channel, Some(36));
+
+        let analysis = analyze(None, vec![neighbor("1", Some(-40))]);
+        assert!(analysis
+            .channel_scores
+            .iter()
+            .all(|s| s.band == WifiBand::Band2G));
+        assert_eq!(analysis.recommended_channel, Some(6));
+    }
+}
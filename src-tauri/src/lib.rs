@@ -1,15 +1,54 @@
 mod commands;
 
+/// Service probing internals for `benches/service_probe.rs`; not a stable API
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::commands::interface::parse_ifconfig;
+    pub use crate::commands::network::{collect_network_services, parse_service_order};
+    pub use crate::commands::runner::{CachedRunner, CommandRunner};
+}
+
 use commands::{
-    diagnostics::{ping_host, reset_network},
-    dns::{flush_dns_cache, get_dns_servers, set_dns_servers},
+    captive::{check_captive_portal, open_captive_portal},
+    deep_reset::{deep_reset_network, list_network_backups, restore_network_backup},
+    dhcp::{get_dhcp_lease, renew_dhcp},
+    diagnostics::{check_dual_stack, ping_host},
+    dns::{
+        flush_dns_cache, get_dns_servers, get_search_domains, set_dns_servers, set_search_domains,
+    },
+    doctor::{diagnose_addressing, run_full_diagnosis},
+    firewall::{get_firewall_status, get_packet_filter_status},
+    https::check_https,
+    interface::{get_interface_details, list_interfaces},
+    ip::{
+        get_ip_config, get_ipv6_config, set_bootp, set_dhcp, set_ipv6_mode, set_manual_ip,
+        set_manual_with_dhcp_router,
+    },
+    location::{
+        create_location, delete_location, get_current_location, list_locations, switch_location,
+    },
+    mtu::{discover_path_mtu, get_mtu, get_valid_mtu_range, set_mtu},
+    neighbor::{delete_neighbor, detect_arp_spoofing, list_neighbors},
     network::{
-        create_network_service, get_available_hardware_ports, get_network_services,
-        get_service_order, remove_network_service, set_service_order,
+        bounce_network_service, create_network_service, duplicate_network_service,
+        get_available_hardware_ports, get_network_services, get_service_order,
+        remove_network_service, rename_network_service, set_service_enabled, set_service_order,
     },
+    ports::{check_proxy_listener, list_listeners, who_owns_port},
     proxy::{
         clear_all_proxies, get_proxy_settings, set_http_proxy, set_https_proxy, set_socks_proxy,
     },
+    reset::{preview_reset_plan, reset_network, run_reset_plan},
+    route::{get_additional_routes, get_routing_table, lookup_route, set_additional_routes},
+    snapshot::{create_snapshot, restore_snapshot},
+    throughput::measure_throughput,
+    vpn::{get_vpn_status, start_vpn, stop_vpn},
+    wifi::{
+        add_preferred_wifi_network, get_current_wifi_network, get_wifi_power, join_wifi_network,
+        list_preferred_wifi_networks, move_preferred_wifi_network, remove_preferred_wifi_network,
+        set_wifi_power,
+    },
+    wifi_analysis::analyze_wifi,
 };
 use tauri::Manager;
 
@@ -39,6 +78,10 @@ pub fn run() {
             get_available_hardware_ports,
             create_network_service,
             remove_network_service,
+            set_service_enabled,
+            bounce_network_service,
+            rename_network_service,
+            duplicate_network_service,
             get_proxy_settings,
             set_http_proxy,
             set_https_proxy,
@@ -47,8 +90,68 @@ pub fn run() {
             get_dns_servers,
             set_dns_servers,
             flush_dns_cache,
+            get_search_domains,
+            set_search_domains,
             ping_host,
             reset_network,
+            get_mtu,
+            get_valid_mtu_range,
+            set_mtu,
+            discover_path_mtu,
+            run_full_diagnosis,
+            preview_reset_plan,
+            run_reset_plan,
+            deep_reset_network,
+            list_network_backups,
+            restore_network_backup,
+            create_snapshot,
+            restore_snapshot,
+            get_ip_config,
+            set_dhcp,
+            set_bootp,
+            set_manual_ip,
+            set_manual_with_dhcp_router,
+            get_ipv6_config,
+            set_ipv6_mode,
+            check_dual_stack,
+            list_locations,
+            get_current_location,
+            switch_location,
+            create_location,
+            delete_location,
+            get_wifi_power,
+            set_wifi_power,
+            get_current_wifi_network,
+            join_wifi_network,
+            list_preferred_wifi_networks,
+            add_preferred_wifi_network,
+            remove_preferred_wifi_network,
+            move_preferred_wifi_network,
+            analyze_wifi,
+            get_interface_details,
+            list_interfaces,
+            get_routing_table,
+            lookup_route,
+            get_additional_routes,
+            set_additional_routes,
+            list_neighbors,
+            delete_neighbor,
+            detect_arp_spoofing,
+            get_dhcp_lease,
+            renew_dhcp,
+            diagnose_addressing,
+            get_vpn_status,
+            start_vpn,
+            stop_vpn,
+            check_captive_portal,
+            open_captive_portal,
+            check_https,
+            measure_throughput,
+            get_firewall_status,
+            get_packet_filter_status,
+            list_listeners,
+            who_owns_port,
+            check_proxy_listener,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -48,6 +48,8 @@ export function NetworkCardsPage() {
     device: string;
     enabled: boolean;
     is_default: boolean;
+    is_primary: boolean;
+    skip_reason: string | null;
     order: number;
     status: string;
   }): NetworkService => ({
@@ -56,6 +58,8 @@ export function NetworkCardsPage() {
     device: service.device,
     enabled: service.enabled,
     isDefault: service.is_default,
+    isPrimary: service.is_primary,
+    skipReason: service.skip_reason,
     order: service.order,
     status: service.status as NetworkService["status"],
   });
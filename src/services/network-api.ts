@@ -2,14 +2,47 @@ import { invoke } from "@tauri-apps/api/core";
 
 export type InterfaceStatus = "Connected" | "Disconnected" | "Unavailable";
 
+export interface Ipv4Address {
+  address: string;
+  prefix_len: number | null;
+  broadcast: string | null;
+  destination: string | null;
+}
+
+export interface Ipv6Address {
+  address: string;
+  prefix_len: number | null;
+  scope_id: string | null;
+  flags: string[];
+}
+
+export interface InterfaceDetails {
+  name: string;
+  flags: string[];
+  mtu: number | null;
+  mac_address: string | null;
+  ipv4: Ipv4Address[];
+  ipv6: Ipv6Address[];
+  media: string | null;
+  duplex: string | null;
+  status: string | null;
+  nd6_options: string[];
+  members: string[];
+}
+
 export interface NetworkService {
   name: string;
   hardware_port: string;
   device: string;
   enabled: boolean;
   is_default: boolean;
+  is_primary: boolean;
+  skip_reason: string | null;
+  is_vpn: boolean;
   order: number;
   status: InterfaceStatus;
+  location: string | null;
+  details: InterfaceDetails | null;
 }
 
 export interface HardwarePort {
@@ -29,11 +62,13 @@ export interface ProxySettings {
   https_proxy: ProxyConfig;
   socks_proxy: ProxyConfig;
   service_name: string;
+  location: string | null;
 }
 
 export interface DnsSettings {
   servers: string[];
   service_name: string;
+  location: string | null;
 }
 
 export interface PingResult {
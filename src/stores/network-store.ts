@@ -8,6 +8,8 @@ export interface NetworkService {
   device: string;
   enabled: boolean;
   isDefault: boolean;
+  isPrimary?: boolean;
+  skipReason?: string | null;
   order: number;
   status: InterfaceStatus;
 }
@@ -1,6 +1,7 @@
 export interface DnsSettings {
   servers: string[];
   service_name: string;
+  location: string | null;
 }
 
 export interface DnsServer {
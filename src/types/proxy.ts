@@ -9,4 +9,5 @@ export interface ProxySettings {
   https_proxy: ProxyConfig;
   socks_proxy: ProxyConfig;
   service_name: string;
+  location: string | null;
 }